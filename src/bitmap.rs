@@ -0,0 +1,659 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// A word-aligned compression scheme for bitmaps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitmapScheme {
+    /// Word-aligned hybrid: 32-bit words, each either a literal of 31 bits or a fill of up to 2^30 - 1 groups of 31 equal bits.
+    Wah,
+    /// Enhanced word-aligned hybrid: 64-bit marker words, each describing a fill of up to 2^32 - 1 words of equal bits followed by up to 2^31 - 1 literal words of 64 bits.
+    Ewah,
+}
+
+impl BitmapScheme {
+    /// Returns the number of bits of a group, the unit that is either a literal or part of a fill.
+    fn group_bits(self) -> u8 {
+        match self {
+            Self::Wah => 31,
+            Self::Ewah => 64,
+        }
+    }
+
+    fn mask(self) -> u64 {
+        u64::MAX >> (64 - self.group_bits())
+    }
+}
+
+/// A group or a run of groups of a bitmap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Chunk {
+    /// A number of groups with all bits equal.
+    Fill(bool, u64),
+    /// A group of mixed bits, with the first bit of the group as least significant bit.
+    Literal(u64),
+}
+
+impl Chunk {
+    fn groups(self) -> u64 {
+        match self {
+            Self::Fill(_, groups) => groups,
+            Self::Literal(_) => 1,
+        }
+    }
+
+    fn word(self, mask: u64) -> u64 {
+        match self {
+            Self::Fill(bit, _) => {
+                if bit {
+                    mask
+                } else {
+                    0
+                }
+            }
+            Self::Literal(word) => word,
+        }
+    }
+}
+
+/// Walks over the groups of a bitmap, splitting fills as needed.
+struct Cursor<'a> {
+    rest: &'a [Chunk],
+    current: Option<Chunk>,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(chunks: &'a [Chunk]) -> Self {
+        Self {
+            rest: chunks,
+            current: None,
+        }
+    }
+
+    fn peek(&mut self) -> Option<Chunk> {
+        if self.current.is_none() {
+            let (first, rest) = self.rest.split_first()?;
+            self.current = Some(*first);
+            self.rest = rest;
+        }
+        self.current
+    }
+
+    fn advance(&mut self, groups: u64) {
+        self.current = match self.current {
+            Some(Chunk::Fill(bit, n)) if n > groups => Some(Chunk::Fill(bit, n - groups)),
+            _ => None,
+        };
+    }
+}
+
+/// A bitmap compressed with word-aligned hybrid encoding (WAH or EWAH).
+///
+/// The bits are divided into groups of the scheme's word size. Groups with all bits equal are merged into fills, which store only the bit and the number of groups, while the other groups are stored as literals. This compresses bitmaps with long runs of equal bits, like the sparse bitmaps of database indexes, while keeping the compressed form word-aligned so that `and` and `or` can operate on it directly, one fill or literal at a time, without decompressing.
+///
+/// Bits are numbered in the order they are pushed or read. The bitmap can be written to and read from a `BitWriter`/`BitReader` of any bit endianness, compressed with `write_to` and `read_from`, or uncompressed with `write_uncompressed` and `from_uncompressed`.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BitmapScheme, CompressedBitmap};
+///
+/// let mut even = CompressedBitmap::new(BitmapScheme::Wah);
+/// let mut small = CompressedBitmap::new(BitmapScheme::Wah);
+/// for i in 0..1000 {
+///     even.push(i % 2 == 0);
+///     small.push(i < 100);
+/// }
+/// let both = even.and(&small);
+/// assert_eq!(both.len(), 1000);
+/// assert_eq!(both.count_ones(), 50);
+/// assert!(both.get(98));
+/// assert!(!both.get(100));
+/// assert_eq!(even.or(&small).ones().take(3).collect::<Vec<_>>(), [0, 1, 2]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressedBitmap {
+    scheme: BitmapScheme,
+    len: u64,
+    /// The complete groups.
+    chunks: Vec<Chunk>,
+    /// The bits of the incomplete last group, with the first bit as least significant bit.
+    pending: u64,
+    pending_len: u8,
+}
+
+impl CompressedBitmap {
+    /// Creates an empty bitmap compressed with `scheme`.
+    #[must_use]
+    pub fn new(scheme: BitmapScheme) -> Self {
+        Self {
+            scheme,
+            len: 0,
+            chunks: vec![],
+            pending: 0,
+            pending_len: 0,
+        }
+    }
+
+    /// Reads `len` uncompressed bits from a `BitReader` and compresses them with `scheme`.
+    pub fn from_uncompressed<E: BitEndianness, R: Read>(
+        reader: &mut BitReader<E, R>,
+        len: u64,
+        scheme: BitmapScheme,
+    ) -> Res<Self> {
+        let mut bitmap = Self::new(scheme);
+        let group_bits = scheme.group_bits();
+        let mut remaining = len;
+        while remaining > 0 {
+            #[allow(clippy::cast_possible_truncation)]
+            let bits = std::cmp::min(remaining, u64::from(group_bits)) as u8;
+            bitmap.push_literal(reader.read_bits_le(bits)?, bits);
+            remaining -= u64::from(bits);
+        }
+        Ok(bitmap)
+    }
+
+    /// Returns the compression scheme of the bitmap.
+    #[inline]
+    #[must_use]
+    pub fn scheme(&self) -> BitmapScheme {
+        self.scheme
+    }
+
+    /// Returns the number of bits in the bitmap.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns whether the bitmap contains no bits.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the total number of set bits.
+    #[must_use]
+    pub fn count_ones(&self) -> u64 {
+        let group_bits = u64::from(self.scheme.group_bits());
+        let chunks: u64 = self
+            .chunks
+            .iter()
+            .map(|chunk| match *chunk {
+                Chunk::Fill(bit, groups) => u64::from(bit) * groups * group_bits,
+                Chunk::Literal(word) => u64::from(word.count_ones()),
+            })
+            .sum();
+        chunks + u64::from(self.pending.count_ones())
+    }
+
+    /// Returns the bit at position `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` >= `len()`.
+    #[must_use]
+    pub fn get(&self, i: u64) -> bool {
+        assert!(i < self.len, "index out of bounds");
+        let group_bits = u64::from(self.scheme.group_bits());
+        let mut group = i / group_bits;
+        let word = self
+            .chunks
+            .iter()
+            .find_map(|&chunk| {
+                if group < chunk.groups() {
+                    Some(chunk.word(self.scheme.mask()))
+                } else {
+                    group -= chunk.groups();
+                    None
+                }
+            })
+            .unwrap_or(self.pending);
+        word >> (i % group_bits) & 1 != 0
+    }
+
+    /// Returns an iterator over the positions of the set bits, in increasing order.
+    ///
+    /// Fills of unset bits are skipped without visiting their bits.
+    pub fn ones(&self) -> impl Iterator<Item = u64> + '_ {
+        let group_bits = u64::from(self.scheme.group_bits());
+        self.chunks
+            .iter()
+            .copied()
+            .chain(std::iter::once(Chunk::Literal(self.pending)))
+            .scan(0, move |start, chunk| {
+                let chunk_start = *start;
+                *start += chunk.groups() * group_bits;
+                Some((chunk_start, chunk))
+            })
+            .flat_map(move |(start, chunk)| {
+                let end = match chunk {
+                    Chunk::Fill(false, _) => start,
+                    Chunk::Fill(true, groups) => start + groups * group_bits,
+                    Chunk::Literal(_) => start + group_bits,
+                };
+                (start..end).filter(move |&i| match chunk {
+                    Chunk::Literal(word) => word >> (i - start) & 1 != 0,
+                    Chunk::Fill(..) => true,
+                })
+            })
+    }
+
+    /// Appends a bit.
+    pub fn push(&mut self, bit: bool) {
+        self.push_literal(u64::from(bit), 1);
+    }
+
+    /// Appends `count` copies of `bit`.
+    ///
+    /// Complete groups are added as a fill without visiting their bits, so long runs are cheap.
+    pub fn push_run(&mut self, bit: bool, count: u64) {
+        let group_bits = self.scheme.group_bits();
+        let mut count = count;
+        while count > 0 && self.pending_len > 0 {
+            self.push(bit);
+            count -= 1;
+        }
+        let groups = count / u64::from(group_bits);
+        if groups > 0 {
+            self.push_fill(bit, groups);
+            self.len += groups * u64::from(group_bits);
+        }
+        for _ in 0..count % u64::from(group_bits) {
+            self.push(bit);
+        }
+    }
+
+    /// Returns the bitwise AND of two bitmaps.
+    ///
+    /// The bitmaps are combined fill by fill and literal by literal, in time proportional to their compressed size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bitmaps have different lengths or schemes.
+    #[must_use]
+    pub fn and(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// Returns the bitwise OR of two bitmaps.
+    ///
+    /// The bitmaps are combined fill by fill and literal by literal, in time proportional to their compressed size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bitmaps have different lengths or schemes.
+    #[must_use]
+    pub fn or(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Writes the uncompressed bits to a `BitWriter`.
+    pub fn write_uncompressed<E: BitEndianness, W: Write>(
+        &self,
+        writer: &mut BitWriter<E, W>,
+    ) -> Res<()> {
+        let group_bits = self.scheme.group_bits();
+        for &chunk in &self.chunks {
+            match chunk {
+                Chunk::Fill(bit, groups) => {
+                    let bits = groups * u64::from(group_bits);
+                    if bit {
+                        writer.write_ones(bits)?;
+                    } else {
+                        writer.write_zeros(bits)?;
+                    }
+                }
+                Chunk::Literal(word) => writer.write_bits_le(word, group_bits)?,
+            }
+        }
+        writer.write_bits_le(self.pending, self.pending_len)
+    }
+
+    /// Writes the compressed bitmap to a `BitWriter`.
+    ///
+    /// The number of bits is written as 64 bits, followed by the words of the scheme. WAH words are a 1-bit fill flag followed by either the fill bit and a 30-bit group count, or a 31-bit literal. EWAH marker words are the fill bit, a 32-bit fill word count and a 31-bit literal word count, followed by the literal words. Literals are written in stream order, the first bit of a literal is the first bit of its group. An incomplete last group is written as a literal padded with zeros.
+    pub fn write_to<E: BitEndianness, W: Write>(&self, writer: &mut BitWriter<E, W>) -> Res<()> {
+        writer.write_bits_wide(self.len, 64)?;
+        let group_bits = self.scheme.group_bits();
+        let mut chunks = self.chunks.clone();
+        if self.pending_len > 0 {
+            chunks.push(Chunk::Literal(self.pending));
+        }
+        match self.scheme {
+            BitmapScheme::Wah => {
+                for chunk in chunks {
+                    match chunk {
+                        Chunk::Fill(bit, mut groups) => {
+                            while groups > 0 {
+                                let count = std::cmp::min(groups, MAX_WAH_FILL);
+                                writer.write_bit(true)?;
+                                writer.write_bit(bit)?;
+                                writer.write_bits_wide(count, 30)?;
+                                groups -= count;
+                            }
+                        }
+                        Chunk::Literal(word) => {
+                            writer.write_bit(false)?;
+                            writer.write_bits_le(word, group_bits)?;
+                        }
+                    }
+                }
+            }
+            BitmapScheme::Ewah => {
+                let mut rest = &chunks[..];
+                while !rest.is_empty() {
+                    let (bit, mut fill) = match rest[0] {
+                        Chunk::Fill(bit, groups) => {
+                            rest = &rest[1..];
+                            (bit, groups)
+                        }
+                        Chunk::Literal(_) => (false, 0),
+                    };
+                    while fill > MAX_EWAH_FILL {
+                        write_marker(writer, bit, MAX_EWAH_FILL, 0)?;
+                        fill -= MAX_EWAH_FILL;
+                    }
+                    let literals = rest
+                        .iter()
+                        .take(MAX_EWAH_LITERALS)
+                        .take_while(|chunk| matches!(chunk, Chunk::Literal(_)))
+                        .count();
+                    write_marker(writer, bit, fill, literals as u64)?;
+                    for chunk in &rest[..literals] {
+                        writer.write_bits_le(chunk.word(u64::MAX), group_bits)?;
+                    }
+                    rest = &rest[literals..];
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a bitmap written with `write_to` from a `BitReader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the words describe more bits than the bitmap's length, or if the padding of the last literal is not zero.
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_from<E: BitEndianness, R: Read>(
+        reader: &mut BitReader<E, R>,
+        scheme: BitmapScheme,
+    ) -> Res<Self> {
+        let len = reader.read_bits_wide(64)?;
+        let mut bitmap = Self::new(scheme);
+        let group_bits = scheme.group_bits();
+        while bitmap.len < len {
+            match scheme {
+                BitmapScheme::Wah => {
+                    if reader.read_bit()? {
+                        let bit = reader.read_bit()?;
+                        let groups = reader.read_bits_wide(30)?;
+                        bitmap.read_fill(bit, groups, len)?;
+                    } else {
+                        bitmap.read_literal(reader.read_bits_le(group_bits)?, len)?;
+                    }
+                }
+                BitmapScheme::Ewah => {
+                    let bit = reader.read_bit()?;
+                    let groups = reader.read_bits_wide(32)?;
+                    let literals = reader.read_bits_wide(31)?;
+                    bitmap.read_fill(bit, groups, len)?;
+                    for _ in 0..literals {
+                        bitmap.read_literal(reader.read_bits_le(group_bits)?, len)?;
+                    }
+                }
+            }
+        }
+        Ok(bitmap)
+    }
+
+    /// Appends a fill of `groups` groups read from compressed data of `len` bits.
+    fn read_fill(&mut self, bit: bool, groups: u64, len: u64) -> Res<()> {
+        let bits = groups
+            .checked_mul(u64::from(self.scheme.group_bits()))
+            .filter(|&bits| bits <= len - self.len)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "fill exceeds bitmap length"))?;
+        self.push_run(bit, bits);
+        Ok(())
+    }
+
+    /// Appends a literal read from compressed data of `len` bits.
+    fn read_literal(&mut self, word: u64, len: u64) -> Res<()> {
+        if self.len >= len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "literal exceeds bitmap length",
+            ));
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let bits = std::cmp::min(len - self.len, u64::from(self.scheme.group_bits())) as u8;
+        if bits < 64 && word >> bits != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "padding of last literal is not zero",
+            ));
+        }
+        self.push_literal(word, bits);
+        Ok(())
+    }
+
+    /// Appends `count` bits of `word`, starting with the least significant bit.
+    fn push_literal(&mut self, word: u64, count: u8) {
+        let group_bits = self.scheme.group_bits();
+        if self.pending_len == 0 && count == group_bits {
+            self.push_group(word);
+        } else {
+            for i in 0..count {
+                self.pending |= (word >> i & 1) << self.pending_len;
+                self.pending_len += 1;
+                if self.pending_len == group_bits {
+                    self.push_group(self.pending);
+                    self.pending = 0;
+                    self.pending_len = 0;
+                }
+            }
+        }
+        self.len += u64::from(count);
+    }
+
+    /// Appends a complete group, merging it into a fill if all of its bits are equal.
+    fn push_group(&mut self, word: u64) {
+        if word == 0 {
+            self.push_fill(false, 1);
+        } else if word == self.scheme.mask() {
+            self.push_fill(true, 1);
+        } else {
+            self.chunks.push(Chunk::Literal(word));
+        }
+    }
+
+    /// Appends complete groups of equal bits, extending the last fill if it has the same bit.
+    fn push_fill(&mut self, bit: bool, groups: u64) {
+        if let Some(Chunk::Fill(last, count)) = self.chunks.last_mut()
+            && *last == bit
+        {
+            *count += groups;
+            return;
+        }
+        self.chunks.push(Chunk::Fill(bit, groups));
+    }
+
+    fn combine(&self, other: &Self, op: impl Fn(u64, u64) -> u64) -> Self {
+        assert_eq!(self.scheme, other.scheme, "bitmaps have different schemes");
+        assert_eq!(self.len, other.len, "bitmaps have different lengths");
+        let mask = self.scheme.mask();
+        let mut result = Self::new(self.scheme);
+        let mut left = Cursor::new(&self.chunks);
+        let mut right = Cursor::new(&other.chunks);
+        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+            let groups = match (a, b) {
+                (Chunk::Fill(_, n), Chunk::Fill(_, m)) => std::cmp::min(n, m),
+                _ => 1,
+            };
+            let word = op(a.word(mask), b.word(mask)) & mask;
+            if groups == 1 {
+                result.push_group(word);
+            } else {
+                // bitwise operations on uniform words give uniform words
+                result.push_fill(word != 0, groups);
+            }
+            left.advance(groups);
+            right.advance(groups);
+        }
+        // the bits past the end of the incomplete group are zero in both
+        result.pending = op(self.pending, other.pending);
+        result.pending_len = self.pending_len;
+        result.len = self.len;
+        result
+    }
+}
+
+/// Maximum number of groups of a WAH fill word.
+const MAX_WAH_FILL: u64 = (1 << 30) - 1;
+/// Maximum number of fill words of an EWAH marker word.
+const MAX_EWAH_FILL: u64 = (1 << 32) - 1;
+/// Maximum number of literal words following an EWAH marker word.
+const MAX_EWAH_LITERALS: usize = (1 << 31) - 1;
+
+fn write_marker<E: BitEndianness, W: Write>(
+    writer: &mut BitWriter<E, W>,
+    bit: bool,
+    fill: u64,
+    literals: u64,
+) -> Res<()> {
+    writer.write_bit(bit)?;
+    writer.write_bits_wide(fill, 32)?;
+    writer.write_bits_wide(literals, 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BEBitReader, BEBitWriter, BitmapScheme, CompressedBitmap, LEBitReader, LEBitWriter,
+    };
+
+    fn bitmap(bits: &[bool], scheme: BitmapScheme) -> CompressedBitmap {
+        let mut bitmap = CompressedBitmap::new(scheme);
+        for &bit in bits {
+            bitmap.push(bit);
+        }
+        bitmap
+    }
+
+    fn sample(len: usize, seed: usize) -> Vec<bool> {
+        // runs of varying length with a few noisy stretches
+        (0..len)
+            .map(|i| match (i + seed) / 97 % 4 {
+                0 => false,
+                1 => true,
+                _ => (i * 31 + seed) % 7 < 3,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        for scheme in [BitmapScheme::Wah, BitmapScheme::Ewah] {
+            for len in [0, 1, 30, 31, 64, 65, 1000, 5000] {
+                let bits = sample(len, 3);
+                let bitmap = bitmap(&bits, scheme);
+                assert_eq!(bitmap.len(), len as u64);
+                for (i, &bit) in bits.iter().enumerate() {
+                    assert_eq!(bitmap.get(i as u64), bit);
+                }
+                let ones: Vec<u64> = (0..)
+                    .zip(&bits)
+                    .filter(|(_, b)| **b)
+                    .map(|(i, _)| i)
+                    .collect();
+                assert!(bitmap.ones().eq(ones.iter().copied()));
+                assert_eq!(bitmap.count_ones(), ones.len() as u64);
+
+                let mut writer = LEBitWriter::new(vec![]);
+                bitmap.write_to(&mut writer).unwrap();
+                writer.write_bits(0x5, 3).unwrap();
+                let data = writer.into_inner().unwrap();
+                let mut reader = LEBitReader::new(&data[..]);
+                assert_eq!(
+                    CompressedBitmap::read_from(&mut reader, scheme).unwrap(),
+                    bitmap
+                );
+                assert_eq!(reader.read_bits(3).unwrap(), 0x5);
+
+                let mut writer = BEBitWriter::new(vec![]);
+                bitmap.write_uncompressed(&mut writer).unwrap();
+                let data = writer.into_inner().unwrap();
+                let mut reader = BEBitReader::new(&data[..]);
+                let read = CompressedBitmap::from_uncompressed(&mut reader, len as u64, scheme);
+                assert_eq!(read.unwrap(), bitmap);
+            }
+        }
+    }
+
+    #[test]
+    fn logical_operations() {
+        for scheme in [BitmapScheme::Wah, BitmapScheme::Ewah] {
+            let left = sample(3000, 0);
+            let right = sample(3000, 150);
+            let and: Vec<bool> = left.iter().zip(&right).map(|(a, b)| a & b).collect();
+            let or: Vec<bool> = left.iter().zip(&right).map(|(a, b)| a | b).collect();
+            let (left, right) = (bitmap(&left, scheme), bitmap(&right, scheme));
+            assert_eq!(left.and(&right), bitmap(&and, scheme));
+            assert_eq!(left.or(&right), bitmap(&or, scheme));
+        }
+    }
+
+    #[test]
+    fn long_runs() {
+        let mut bitmap = CompressedBitmap::new(BitmapScheme::Wah);
+        bitmap.push(true);
+        bitmap.push_run(false, 1 << 40);
+        bitmap.push_run(true, 3);
+        assert_eq!(bitmap.len(), (1 << 40) + 4);
+        assert_eq!(
+            bitmap.ones().collect::<Vec<_>>(),
+            [0, (1 << 40) + 1, (1 << 40) + 2, (1 << 40) + 3]
+        );
+        let mut writer = BEBitWriter::new(vec![]);
+        bitmap.write_to(&mut writer).unwrap();
+        let data = writer.into_inner().unwrap();
+        // header, a literal, 34 fill words of at most 2^30 - 1 groups and the last literal
+        assert_eq!(data.len(), 8 + (1 + 34 + 1) * 4);
+        let mut reader = BEBitReader::new(&data[..]);
+        assert_eq!(
+            CompressedBitmap::read_from(&mut reader, BitmapScheme::Wah).unwrap(),
+            bitmap
+        );
+    }
+
+    #[test]
+    fn wah_word_layout() {
+        let mut bitmap = CompressedBitmap::new(BitmapScheme::Wah);
+        bitmap.push_run(true, 62);
+        bitmap.push(false);
+        bitmap.push(true);
+        let mut writer = BEBitWriter::new(vec![]);
+        bitmap.write_to(&mut writer).unwrap();
+        let data = writer.into_inner().unwrap();
+        assert_eq!(data[..8], 64u64.to_be_bytes());
+        // a fill of 2 groups of ones, then the literal 01 padded with zeros
+        assert_eq!(data[8..], [0xc0, 0, 0, 2, 0x20, 0, 0, 0]);
+    }
+
+    #[test]
+    fn invalid_data() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bits_wide(40, 64).unwrap();
+        writer.write_bits_wide(0xc000_0002, 32).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        let err = CompressedBitmap::read_from(&mut reader, BitmapScheme::Wah).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,450 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::sync::OnceLock;
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// 5b/6b codes `abcdei` for running disparity -, indexed by the low 5 bits of the byte.
+const FIVE_SIX: [u16; 32] = [
+    0b10_0111, 0b01_1101, 0b10_1101, 0b11_0001, 0b11_0101, 0b10_1001, 0b01_1001, 0b11_1000,
+    0b11_1001, 0b10_0101, 0b01_0101, 0b11_0100, 0b00_1101, 0b10_1100, 0b01_1100, 0b01_0111,
+    0b01_1011, 0b10_0011, 0b01_0011, 0b11_0010, 0b00_1011, 0b10_1010, 0b01_1010, 0b11_1010,
+    0b11_0011, 0b10_0110, 0b01_0110, 0b11_0110, 0b00_1110, 0b10_1110, 0b01_1110, 0b10_1011,
+];
+/// 5b/6b code of K.28 for running disparity -.
+const K28_SIX: u16 = 0b00_1111;
+/// 3b/4b codes `fghj` of data symbols for running disparity -, indexed by the high 3 bits of the byte.
+const THREE_FOUR: [u16; 8] = [
+    0b1011, 0b1001, 0b0101, 0b1100, 0b1101, 0b1010, 0b0110, 0b1110,
+];
+/// Alternate 3b/4b code of D.x.7, used where the primary code would make a run of 5 equal bits.
+const A7_FOUR: u16 = 0b0111;
+/// 3b/4b codes of control symbols for running disparity -.
+const CONTROL_FOUR: [u16; 8] = [
+    0b1011, 0b0110, 0b1010, 0b1100, 0b1101, 0b0101, 0b1001, 0b0111,
+];
+/// Marks a valid entry of the decoding tables.
+const VALID: u16 = 0x8000;
+/// Marks a control symbol in the decoding tables.
+const CONTROL: u16 = 0x100;
+
+/// A symbol of the 8b/10b line code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symbol8b10b {
+    /// A data symbol D.x.y, holding its byte.
+    Data(u8),
+    /// A control symbol K.x.y, holding the byte with the same x and y, e.g. `0xbc` for K.28.5.
+    Control(u8),
+}
+
+impl Symbol8b10b {
+    /// Returns whether this is one of the control symbols K.28.1, K.28.5 and K.28.7, whose codes contain the comma pattern used for symbol alignment.
+    #[must_use]
+    pub fn is_comma(self) -> bool {
+        matches!(self, Self::Control(0x3c | 0xbc | 0xfc))
+    }
+}
+
+/// Returns whether `byte` is the x and y of one of the 12 control symbols.
+fn is_valid_control(byte: u8) -> bool {
+    byte & 0x1f == 28 || matches!(byte, 0xf7 | 0xfb | 0xfd | 0xfe)
+}
+
+/// Returns the 10 bit code `abcdeifghj` of `symbol`, with `a` as the most significant bit, and the running disparity after it.
+fn encode(symbol: Symbol8b10b, positive: bool) -> (u16, bool) {
+    let (byte, control) = match symbol {
+        Symbol8b10b::Data(byte) => (byte, false),
+        Symbol8b10b::Control(byte) => (byte, true),
+    };
+    let (x, y) = (usize::from(byte & 0x1f), usize::from(byte >> 5));
+    let mut positive = positive;
+    let mut six = if control && x == 28 {
+        K28_SIX
+    } else {
+        FIVE_SIX[x]
+    };
+    if positive && (six.count_ones() != 3 || x == 7 && !control) {
+        six ^= 0x3f;
+    }
+    if six.count_ones() != 3 {
+        positive = !positive;
+    }
+    let mut four = if control {
+        CONTROL_FOUR[y]
+    } else if y == 7
+        && (!positive && matches!(x, 17 | 18 | 20) || positive && matches!(x, 11 | 13 | 14))
+    {
+        A7_FOUR
+    } else {
+        THREE_FOUR[y]
+    };
+    if positive && (control || four.count_ones() != 2 || y == 3) {
+        four ^= 0xf;
+    }
+    if four.count_ones() != 2 {
+        positive = !positive;
+    }
+    (six << 4 | four, positive)
+}
+
+/// Returns the tables mapping codes to symbols, for running disparity - and +.
+fn decode_tables() -> &'static [[u16; 1024]; 2] {
+    static TABLES: OnceLock<[[u16; 1024]; 2]> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut tables = [[0; 1024]; 2];
+        for (table, positive) in tables.iter_mut().zip([false, true]) {
+            for byte in 0..=255 {
+                let (code, _) = encode(Symbol8b10b::Data(byte), positive);
+                table[usize::from(code)] = VALID | u16::from(byte);
+                if is_valid_control(byte) {
+                    let (code, _) = encode(Symbol8b10b::Control(byte), positive);
+                    table[usize::from(code)] = VALID | CONTROL | u16::from(byte);
+                }
+            }
+        }
+        tables
+    })
+}
+
+/// Reverses the order of the 10 bits of a code.
+fn reverse(code: u16) -> u16 {
+    code.reverse_bits() >> 6
+}
+
+/// Encodes bytes and control symbols with the 8b/10b line code, writing the codes to a [`BitWriter`].
+///
+/// Each symbol is written as 10 bits in transmission order, bit `a` first, so with little endian bit numbering the code of a data byte starts with its least significant bit. The running disparity starts out negative.
+///
+/// Bytes written through the `Write` implementation are encoded as data symbols.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{Encoder8b10b, LEBitWriter, Symbol8b10b};
+/// use std::io::Write;
+///
+/// let mut encoder = Encoder8b10b::new(LEBitWriter::new(vec![]));
+/// encoder.write_symbol(Symbol8b10b::Control(0xbc)).unwrap();
+/// encoder.write_all(b"\x00\x00").unwrap();
+/// assert!(encoder.is_disparity_positive());
+/// let data = encoder.into_inner().into_inner().unwrap();
+/// assert_eq!(data.len(), 4);
+/// ```
+pub struct Encoder8b10b<E: BitEndianness, W: Write> {
+    inner: BitWriter<E, W>,
+    positive: bool,
+}
+
+impl<E: BitEndianness, W: Write> Encoder8b10b<E, W> {
+    /// Creates a new `Encoder8b10b` writing to `inner`, with negative running disparity.
+    #[inline]
+    pub fn new(inner: BitWriter<E, W>) -> Self {
+        Self {
+            inner,
+            positive: false,
+        }
+    }
+
+    /// Gets a reference to the underlying `BitWriter`.
+    #[inline]
+    pub fn get_ref(&self) -> &BitWriter<E, W> {
+        &self.inner
+    }
+
+    /// Unwraps this `Encoder8b10b`, returning the underlying `BitWriter`.
+    #[inline]
+    pub fn into_inner(self) -> BitWriter<E, W> {
+        self.inner
+    }
+
+    /// Returns whether the current running disparity is positive.
+    #[inline]
+    pub fn is_disparity_positive(&self) -> bool {
+        self.positive
+    }
+
+    /// Encodes and writes a symbol.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `symbol` is a control symbol other than K.28.y, K.23.7, K.27.7, K.29.7 and K.30.7.
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_symbol(&mut self, symbol: Symbol8b10b) -> Res<()> {
+        if let Symbol8b10b::Control(byte) = symbol
+            && !is_valid_control(byte)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "K.{}.{} is not an 8b/10b control symbol",
+                    byte & 0x1f,
+                    byte >> 5
+                ),
+            ));
+        }
+        let (code, positive) = encode(symbol, self.positive);
+        let code = if E::LSB_FIRST { reverse(code) } else { code };
+        self.inner.write_bits_wide(u64::from(code), 10)?;
+        self.positive = positive;
+        Ok(())
+    }
+}
+
+/// Encode bytes as data symbols.
+impl<E: BitEndianness, W: Write> Write for Encoder8b10b<E, W> {
+    fn write(&mut self, buf: &[u8]) -> Res<usize> {
+        for &byte in buf {
+            self.write_symbol(Symbol8b10b::Data(byte))?;
+        }
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Res<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decodes symbols of the 8b/10b line code read from a [`BitReader`].
+///
+/// Codes are read as 10 bits in transmission order, like they are written by [`Encoder8b10b`]. The running disparity starts out negative, or is taken from the comma found by `sync`.
+///
+/// # Examples
+///
+/// Find the symbol boundary in a capture starting in the middle of a symbol:
+///
+/// ```
+/// use endio_bit::{BEBitReader, BEBitWriter, Decoder8b10b, Encoder8b10b, Symbol8b10b};
+/// use std::io::Write;
+///
+/// let mut writer = BEBitWriter::new(vec![]);
+/// writer.write_bits(0x5, 3).unwrap();
+/// let mut encoder = Encoder8b10b::new(writer);
+/// encoder.write_symbol(Symbol8b10b::Control(0xbc)).unwrap();
+/// encoder.write_all(b"hi").unwrap();
+/// let data = encoder.into_inner().into_inner().unwrap();
+///
+/// let mut decoder = Decoder8b10b::new(BEBitReader::new(&data[..]));
+/// assert_eq!(decoder.sync().unwrap(), Symbol8b10b::Control(0xbc));
+/// assert_eq!(decoder.read_data().unwrap(), b'h');
+/// assert_eq!(decoder.read_data().unwrap(), b'i');
+/// ```
+pub struct Decoder8b10b<E: BitEndianness, R: Read> {
+    inner: BitReader<E, R>,
+    positive: bool,
+}
+
+impl<E: BitEndianness, R: Read> Decoder8b10b<E, R> {
+    /// Creates a new `Decoder8b10b` reading from `inner`, with negative running disparity.
+    #[inline]
+    pub fn new(inner: BitReader<E, R>) -> Self {
+        Self {
+            inner,
+            positive: false,
+        }
+    }
+
+    /// Gets a reference to the underlying `BitReader`.
+    #[inline]
+    pub fn get_ref(&self) -> &BitReader<E, R> {
+        &self.inner
+    }
+
+    /// Unwraps this `Decoder8b10b`, returning the underlying `BitReader`.
+    #[inline]
+    pub fn into_inner(self) -> BitReader<E, R> {
+        self.inner
+    }
+
+    /// Returns whether the current running disparity is positive.
+    #[inline]
+    pub fn is_disparity_positive(&self) -> bool {
+        self.positive
+    }
+
+    /// Reads and decodes a symbol.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the code is not a valid 8b/10b code, or if it is only valid for the opposite running disparity. In the latter case the running disparity is resynchronized to the code, so decoding can continue.
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_symbol(&mut self) -> Res<Symbol8b10b> {
+        let start = self.inner.bit_position();
+        #[allow(clippy::cast_possible_truncation)]
+        let code = self.inner.read_bits_wide(10)? as u16;
+        let code = if E::LSB_FIRST { reverse(code) } else { code };
+        self.decode(code, start)
+    }
+
+    /// Reads and decodes a data symbol.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the symbol is a control symbol, or for the reasons listed for `read_symbol`.
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_data(&mut self) -> Res<u8> {
+        let start = self.inner.bit_position();
+        match self.read_symbol()? {
+            Symbol8b10b::Data(byte) => Ok(byte),
+            Symbol8b10b::Control(byte) => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "expected data symbol at bit offset {start}, found K.{}.{}",
+                    byte & 0x1f,
+                    byte >> 5
+                ),
+            )),
+        }
+    }
+
+    /// Skips bits until a comma, returning the symbol containing it.
+    ///
+    /// The reader is then aligned to the symbol boundaries, and the running disparity is set from the comma symbol. Use this to decode captures that don't start at a symbol boundary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`UnexpectedEof`] if the stream ends before a comma symbol is found.
+    ///
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn sync(&mut self) -> Res<Symbol8b10b> {
+        let mut window = 0u16;
+        let mut count = 0;
+        loop {
+            window = (window << 1 | u16::from(self.inner.read_bit()?)) & 0x3ff;
+            if count < 10 {
+                count += 1;
+            }
+            if count < 10 || !matches!(window >> 3, 0b001_1111 | 0b110_0000) {
+                continue;
+            }
+            self.positive = window >> 3 == 0b110_0000;
+            let start = self.inner.bit_position() - 10;
+            if let Ok(symbol) = self.decode(window, start)
+                && symbol.is_comma()
+            {
+                return Ok(symbol);
+            }
+        }
+    }
+
+    /// Decodes `code`, updating the running disparity.
+    fn decode(&mut self, code: u16, start: u64) -> Res<Symbol8b10b> {
+        let tables = decode_tables();
+        let current = tables[usize::from(self.positive)][usize::from(code)];
+        let entry = if current == 0 {
+            let opposite = tables[usize::from(!self.positive)][usize::from(code)];
+            if opposite == 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid 8b/10b code {code:#012b} at bit offset {start}"),
+                ));
+            }
+            self.positive = !self.positive;
+            self.update_disparity(code);
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("running disparity error at bit offset {start}"),
+            ));
+        } else {
+            current
+        };
+        self.update_disparity(code);
+        #[allow(clippy::cast_possible_truncation)]
+        let byte = entry as u8;
+        Ok(if entry & CONTROL == 0 {
+            Symbol8b10b::Data(byte)
+        } else {
+            Symbol8b10b::Control(byte)
+        })
+    }
+
+    /// Updates the running disparity after a valid code.
+    fn update_disparity(&mut self, code: u16) {
+        if code.count_ones() != 5 {
+            self.positive = !self.positive;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Write};
+
+    use super::{decode_tables, encode};
+    use crate::{
+        BEBitReader, BEBitWriter, Decoder8b10b, Encoder8b10b, LEBitReader, LEBitWriter, Symbol8b10b,
+    };
+
+    #[test]
+    fn known_codes() {
+        for (symbol, negative, positive) in [
+            (Symbol8b10b::Data(0x00), 0b10_0111_0100, 0b01_1000_1011),
+            (Symbol8b10b::Data(0x07), 0b11_1000_1011, 0b00_0111_0100),
+            (Symbol8b10b::Data(0xf1), 0b10_0011_0111, 0b10_0011_0001),
+            (Symbol8b10b::Data(0xeb), 0b11_0100_1110, 0b11_0100_1000),
+            (Symbol8b10b::Data(0x63), 0b11_0001_1100, 0b11_0001_0011),
+            (Symbol8b10b::Control(0xbc), 0b00_1111_1010, 0b11_0000_0101),
+            (Symbol8b10b::Control(0x3c), 0b00_1111_1001, 0b11_0000_0110),
+            (Symbol8b10b::Control(0xf7), 0b11_1010_1000, 0b00_0101_0111),
+        ] {
+            assert_eq!(encode(symbol, false).0, negative, "{symbol:?}");
+            assert_eq!(encode(symbol, true).0, positive, "{symbol:?}");
+        }
+    }
+
+    #[test]
+    fn codes_are_unique_and_balanced() {
+        for table in decode_tables() {
+            let count = table.iter().filter(|&&entry| entry != 0).count();
+            assert_eq!(count, 256 + 12);
+            for (code, _) in table.iter().enumerate().filter(|(_, entry)| **entry != 0) {
+                let ones = u16::try_from(code).unwrap().count_ones();
+                assert!((4..=6).contains(&ones));
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<u8> = (0..=255).chain((0..=255).rev()).collect();
+        let mut encoder = Encoder8b10b::new(LEBitWriter::new(vec![]));
+        encoder.write_all(&data).unwrap();
+        encoder.write_symbol(Symbol8b10b::Control(0xfe)).unwrap();
+        let bytes = encoder.into_inner().into_inner().unwrap();
+        assert_eq!(bytes.len(), 642);
+        let mut decoder = Decoder8b10b::new(LEBitReader::new(&bytes[..]));
+        for &byte in &data {
+            assert_eq!(decoder.read_symbol().unwrap(), Symbol8b10b::Data(byte));
+        }
+        let err = decoder.read_data().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn disparity_error() {
+        let mut writer = BEBitWriter::new(vec![]);
+        // D.00 encoded for positive running disparity at the start
+        writer.write_bits_wide(0b01_1000_1011, 10).unwrap();
+        let mut encoder = Encoder8b10b::new(writer);
+        encoder.positive = true;
+        encoder.write_all(b"\x00").unwrap();
+        let bytes = encoder.into_inner().into_inner().unwrap();
+        let mut decoder = Decoder8b10b::new(BEBitReader::new(&bytes[..]));
+        let err = decoder.read_symbol().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(decoder.read_symbol().unwrap(), Symbol8b10b::Data(0));
+    }
+
+    #[test]
+    fn invalid_control() {
+        let mut encoder = Encoder8b10b::new(BEBitWriter::new(vec![]));
+        let err = encoder
+            .write_symbol(Symbol8b10b::Control(0x00))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::endian::{BE, BitEndianness, LE};
+
+/// Reads most significant bits first, in `const` contexts.
+pub type BEConstBitReader<'a> = ConstBitReader<'a, BE>;
+/// Reads least significant bits first, in `const` contexts.
+pub type LEConstBitReader<'a> = ConstBitReader<'a, LE>;
+
+/// A minimal bit reader over a byte slice whose methods are `const fn`.
+///
+/// This allows decoding magic numbers and small packed tables into constants at compile time. Values are assembled like `BitReader` does, so a constant decoded with this reader matches what a `BitReader` of the same bit endianness reads at runtime.
+///
+/// Since `const fn` can't return errors with `?`, reading past the end of the data panics, which is a compile error when evaluating a constant.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::BEConstBitReader;
+///
+/// const HEADER: (u64, bool, u64) = {
+///     let mut reader = BEConstBitReader::new(b"\x12\x3f");
+///     let version = reader.read_bits(4);
+///     let flag = reader.read_bit();
+///     let kind = reader.read_bits(11);
+///     (version, flag, kind)
+/// };
+/// assert_eq!(HEADER, (1, false, 0x23f));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ConstBitReader<'a, E: BitEndianness> {
+    data: &'a [u8],
+    /// Number of bits read.
+    position: usize,
+    phantom: std::marker::PhantomData<E>,
+}
+
+impl<'a, E: BitEndianness> ConstBitReader<'a, E> {
+    /// Creates a new reader starting at the first bit of `data`.
+    #[inline]
+    #[must_use]
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns the number of bits left to read.
+    #[inline]
+    #[must_use]
+    pub const fn remaining_bits(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+
+    /// Returns whether the reader is aligned to the byte boundary.
+    #[inline]
+    #[must_use]
+    pub const fn is_aligned(&self) -> bool {
+        self.position.is_multiple_of(8)
+    }
+
+    /// Aligns to byte boundary, skipping the rest of a partially read byte.
+    #[inline]
+    pub const fn align(&mut self) {
+        self.position = self.position.next_multiple_of(8);
+    }
+
+    /// Reads a single bit, returning true for 1, false for 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no bits left.
+    pub const fn read_bit(&mut self) -> bool {
+        assert!(self.position < self.data.len() * 8, "no bits left to read");
+        let byte = self.data[self.position / 8];
+        let offset = self.position % 8;
+        let shift = if E::LSB_FIRST { offset } else { 7 - offset };
+        self.position += 1;
+        byte >> shift & 1 != 0
+    }
+
+    /// Reads up to 64 bits.
+    ///
+    /// With big endian bit numbering, the first bit read is the most significant, with little endian it is the least significant.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64 or if there are less than `count` bits left.
+    pub const fn read_bits(&mut self, count: u8) -> u64 {
+        assert!(count <= 64, "count must be at most 64");
+        assert!(
+            count as usize <= self.remaining_bits(),
+            "not enough bits left to read"
+        );
+        let mut value = 0;
+        let mut i = 0;
+        while i < count {
+            let bit = self.read_bit() as u64;
+            if E::LSB_FIRST {
+                value |= bit << i;
+            } else {
+                value = value << 1 | bit;
+            }
+            i += 1;
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, BEConstBitReader, LEBitReader, LEConstBitReader};
+
+    const DATA: &[u8] = b"\xa5\x3c\x0f\xf0\x96\x69\x12\x34\x56\x78";
+    const WIDTHS: [u8; 6] = [3, 1, 13, 0, 40, 7];
+
+    #[test]
+    fn matches_bit_reader() {
+        let mut be = BEConstBitReader::new(DATA);
+        let mut le = LEConstBitReader::new(DATA);
+        let mut be_reader = BEBitReader::new(DATA);
+        let mut le_reader = LEBitReader::new(DATA);
+        for count in WIDTHS {
+            assert_eq!(
+                be.read_bits(count),
+                be_reader.read_bits_wide(count).unwrap()
+            );
+            assert_eq!(
+                le.read_bits(count),
+                le_reader.read_bits_wide(count).unwrap()
+            );
+        }
+        assert_eq!(be.remaining_bits(), 16);
+        be.align();
+        assert_eq!(be.remaining_bits(), 16);
+        assert!(be.is_aligned());
+    }
+
+    #[test]
+    fn const_evaluation() {
+        const VALUE: u64 = {
+            let mut reader = LEConstBitReader::new(b"\x34\x12");
+            reader.align();
+            reader.read_bits(16)
+        };
+        assert_eq!(VALUE, 0x1234);
+    }
+
+    #[test]
+    #[should_panic(expected = "not enough bits left to read")]
+    fn past_end() {
+        let mut reader = BEConstBitReader::new(b"\x00");
+        reader.read_bit();
+        reader.read_bits(8);
+    }
+}
@@ -0,0 +1,207 @@
+use std::io::Read;
+use std::io::Result as Res;
+use std::ops::{Deref, DerefMut};
+
+use crate::BitReader;
+use crate::endian::BitEndianness;
+
+/// An error with the field path and bit position at which it happened, created by `ContextReader`.
+///
+/// The error is returned wrapped in a `std::io::Error` of the same kind as the original error. Use `std::io::Error::get_ref` and `downcast_ref` to access it.
+#[derive(Debug)]
+pub struct ContextError {
+    path: String,
+    bit_position: u64,
+    source: std::io::Error,
+}
+
+impl ContextError {
+    /// Returns the path of nested contexts, separated by dots.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the number of bits consumed by the reader when the error was returned.
+    #[must_use]
+    pub fn bit_position(&self) -> u64 {
+        self.bit_position
+    }
+
+    /// Returns the original error.
+    #[must_use]
+    pub fn inner(&self) -> &std::io::Error {
+        &self.source
+    }
+}
+
+impl std::fmt::Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at bit {}: {}",
+            self.path, self.bit_position, self.source
+        )
+    }
+}
+
+impl std::error::Error for ContextError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Wraps a `BitReader` to attach the names of the fields being read to errors.
+///
+/// Contexts are nested with `scope`, or with `push_context` and `pop_context`. If an error is returned inside a scope, it is wrapped in a [`ContextError`] with the current path, like `frame_header.flags`, and bit position.
+///
+/// All methods of the `BitReader` are available through `Deref`.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, ContextError, ContextReader};
+///
+/// let mut reader = ContextReader::new(BEBitReader::new(&b"\x12"[..]));
+/// let err = reader
+///     .scope("frame_header", |r| {
+///         r.read_bits(4)?;
+///         r.scope("flags", |r| r.read_bits(8))
+///     })
+///     .unwrap_err();
+/// assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+/// let context = err.get_ref().unwrap().downcast_ref::<ContextError>().unwrap();
+/// assert_eq!(context.path(), "frame_header.flags");
+/// assert_eq!(context.bit_position(), 4);
+/// ```
+pub struct ContextReader<E: BitEndianness, R: Read> {
+    inner: BitReader<E, R>,
+    contexts: Vec<&'static str>,
+}
+
+impl<E: BitEndianness, R: Read> ContextReader<E, R> {
+    /// Creates a new `ContextReader` without any context.
+    #[inline]
+    pub fn new(inner: BitReader<E, R>) -> Self {
+        Self {
+            inner,
+            contexts: vec![],
+        }
+    }
+
+    /// Unwraps this `ContextReader`, returning the `BitReader`.
+    #[inline]
+    pub fn into_inner(self) -> BitReader<E, R> {
+        self.inner
+    }
+
+    /// Enters a nested context.
+    #[inline]
+    pub fn push_context(&mut self, name: &'static str) {
+        self.contexts.push(name);
+    }
+
+    /// Leaves the innermost context.
+    #[inline]
+    pub fn pop_context(&mut self) {
+        self.contexts.pop();
+    }
+
+    /// Returns the current path of nested contexts, separated by dots.
+    #[must_use]
+    pub fn context_path(&self) -> String {
+        self.contexts.join(".")
+    }
+
+    /// Wraps `err` in a [`ContextError`] with the current path and bit position.
+    ///
+    /// Errors that already carry a context, because they were annotated in a nested context, are returned unchanged.
+    #[must_use]
+    pub fn annotate(&self, err: std::io::Error) -> std::io::Error {
+        if err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<ContextError>())
+            .is_some()
+        {
+            return err;
+        }
+        std::io::Error::new(
+            err.kind(),
+            ContextError {
+                path: self.context_path(),
+                bit_position: self.inner.bit_position(),
+                source: err,
+            },
+        )
+    }
+
+    /// Runs `f` in a nested context named `name`, annotating any error it returns.
+    pub fn scope<T>(&mut self, name: &'static str, f: impl FnOnce(&mut Self) -> Res<T>) -> Res<T> {
+        self.push_context(name);
+        let res = f(self).map_err(|e| self.annotate(e));
+        self.pop_context();
+        res
+    }
+}
+
+impl<E: BitEndianness, R: Read> Deref for ContextReader<E, R> {
+    type Target = BitReader<E, R>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<E: BitEndianness, R: Read> DerefMut for ContextReader<E, R> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextError, ContextReader, LEBitReader};
+
+    fn context(err: &std::io::Error) -> &ContextError {
+        err.get_ref().unwrap().downcast_ref().unwrap()
+    }
+
+    #[test]
+    fn nested() {
+        let mut reader = ContextReader::new(LEBitReader::new(&b"\x01\x00"[..]));
+        let err = reader
+            .scope("packet", |r| {
+                r.scope("header", |r| r.read_bits(8))?;
+                r.scope("body", |r| {
+                    r.read_reserved(4)?;
+                    r.scope("payload", |r| r.read_bits(8))
+                })
+            })
+            .unwrap_err();
+        assert_eq!(context(&err).path(), "packet.body.payload");
+        assert_eq!(context(&err).bit_position(), 12);
+        assert_eq!(
+            err.to_string(),
+            "packet.body.payload at bit 12: failed to fill whole buffer"
+        );
+        assert_eq!(reader.context_path(), "");
+    }
+
+    #[test]
+    fn push_and_pop() {
+        let mut reader = ContextReader::new(LEBitReader::new(&b"\x10"[..]));
+        reader.push_context("reserved");
+        let err = reader.read_reserved(8).unwrap_err();
+        let err = reader.annotate(err);
+        reader.pop_context();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(context(&err).path(), "reserved");
+        assert_eq!(
+            context(&err).inner().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        assert!(reader.scope("ok", |r| r.read_bits(0)).is_ok());
+    }
+}
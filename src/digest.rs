@@ -0,0 +1,306 @@
+use std::io::Result as Res;
+use std::io::{Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// A hasher or checksum that can observe the bits flowing through a [`DigestReader`] or [`DigestWriter`].
+///
+/// Implement this for your CRC, Adler or cryptographic hash state by forwarding `update` to the hasher's own update function.
+///
+/// Bits are fed to the digest as bytes, assembled in the bit endianness of the wrapped reader or writer. A trailing partial byte is zero-padded in the same way a `BitWriter` pads when it is aligned, and fed when the wrapper is finished.
+pub trait BitDigest {
+    /// Feeds the next complete bytes of the stream to the digest.
+    fn update(&mut self, bytes: &[u8]);
+}
+
+impl<D: BitDigest + ?Sized> BitDigest for &mut D {
+    #[inline]
+    fn update(&mut self, bytes: &[u8]) {
+        (**self).update(bytes);
+    }
+}
+
+/// Forwards completed bytes from the observing `BitWriter` to the digest.
+#[derive(Debug)]
+struct DigestSink<D: BitDigest>(D);
+
+impl<D: BitDigest> Write for DigestSink<D> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Res<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Res<()> {
+        Ok(())
+    }
+}
+
+/// Feeds every bit read from a [`BitReader`] to a [`BitDigest`].
+///
+/// Use `finish` to pad and feed a trailing partial byte and get the digest back.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, BitDigest, DigestReader};
+///
+/// struct Sum(u32);
+///
+/// impl BitDigest for Sum {
+///     fn update(&mut self, bytes: &[u8]) {
+///         self.0 += bytes.iter().map(|&b| u32::from(b)).sum::<u32>();
+///     }
+/// }
+///
+/// let mut reader = DigestReader::new(BEBitReader::new(&b"\x12\x34"[..]), Sum(0));
+/// reader.read_bits(8).unwrap();
+/// reader.read_bits(4).unwrap();
+/// let (_, sum) = reader.finish().unwrap();
+/// assert_eq!(sum.0, 0x12 + 0x30);
+/// ```
+pub struct DigestReader<E: BitEndianness, R: Read, D: BitDigest> {
+    inner: BitReader<E, R>,
+    observed: BitWriter<E, DigestSink<D>>,
+}
+
+impl<E: BitEndianness, R: Read, D: BitDigest> DigestReader<E, R, D> {
+    /// Creates a new `DigestReader` feeding everything read from `inner` to `digest`.
+    #[inline]
+    pub fn new(inner: BitReader<E, R>, digest: D) -> Self {
+        Self {
+            inner,
+            observed: BitWriter::new(DigestSink(digest)),
+        }
+    }
+
+    /// Gets a reference to the digest.
+    ///
+    /// Only complete bytes have been fed to the digest at this point.
+    #[inline]
+    pub fn digest(&self) -> &D {
+        &self.observed.get_ref().0
+    }
+
+    /// Gets a reference to the underlying `BitReader`.
+    #[inline]
+    pub fn get_ref(&self) -> &BitReader<E, R> {
+        &self.inner
+    }
+
+    /// Returns whether the reader is aligned to the byte boundary.
+    #[inline]
+    pub fn is_aligned(&self) -> bool {
+        self.inner.is_aligned()
+    }
+
+    /// Aligns to byte boundary.
+    ///
+    /// Unlike `BitReader::align`, the rest of the partial byte is read and fed to the digest instead of being discarded, since it is part of the stream being checked.
+    pub fn align(&mut self) -> Res<()> {
+        if !self.is_aligned() {
+            let count = 8 - self.inner.bit_offset();
+            self.read_bits(count)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a single bit, see `BitReader::read_bit`.
+    pub fn read_bit(&mut self) -> Res<bool> {
+        let bit = self.inner.read_bit()?;
+        self.observed.write_bit(bit)?;
+        Ok(bit)
+    }
+
+    /// Reads 8 bits or less, see `BitReader::read_bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8.
+    pub fn read_bits(&mut self, count: u8) -> Res<u8> {
+        let bits = self.inner.read_bits(count)?;
+        self.observed.write_bits(bits, count)?;
+        Ok(bits)
+    }
+
+    /// Feeds a trailing partial byte to the digest, zero-padded, and returns the underlying `BitReader` and the digest.
+    pub fn finish(self) -> Res<(BitReader<E, R>, D)> {
+        let sink = self.observed.into_inner()?;
+        Ok((self.inner, sink.0))
+    }
+}
+
+/// Read bytes while feeding them to the digest.
+impl<E: BitEndianness, R: Read, D: BitDigest> Read for DigestReader<E, R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> Res<usize> {
+        let count_read = self.inner.read(buf)?;
+        self.observed.write_all(&buf[..count_read])?;
+        Ok(count_read)
+    }
+}
+
+/// Feeds every bit written to a [`BitWriter`] to a [`BitDigest`].
+///
+/// The digest sees the exact bytes the underlying writer receives, including the zero padding written when the writer is aligned.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitWriter, BitDigest, DigestWriter};
+///
+/// struct Sum(u32);
+///
+/// impl BitDigest for Sum {
+///     fn update(&mut self, bytes: &[u8]) {
+///         self.0 += bytes.iter().map(|&b| u32::from(b)).sum::<u32>();
+///     }
+/// }
+///
+/// let mut writer = DigestWriter::new(BEBitWriter::new(vec![]), Sum(0));
+/// writer.write_bits(0x12, 8).unwrap();
+/// writer.write_bits(0x03, 4).unwrap();
+/// let (writer, sum) = writer.finish().unwrap();
+/// assert_eq!(sum.0, 0x12 + 0x30);
+/// assert_eq!(writer.into_inner().unwrap(), b"\x12\x30");
+/// ```
+pub struct DigestWriter<E: BitEndianness, W: Write, D: BitDigest> {
+    inner: BitWriter<E, W>,
+    observed: BitWriter<E, DigestSink<D>>,
+}
+
+impl<E: BitEndianness, W: Write, D: BitDigest> DigestWriter<E, W, D> {
+    /// Creates a new `DigestWriter` feeding everything written to `inner` to `digest`.
+    #[inline]
+    pub fn new(inner: BitWriter<E, W>, digest: D) -> Self {
+        Self {
+            inner,
+            observed: BitWriter::new(DigestSink(digest)),
+        }
+    }
+
+    /// Gets a reference to the digest.
+    ///
+    /// Only complete bytes have been fed to the digest at this point.
+    #[inline]
+    pub fn digest(&self) -> &D {
+        &self.observed.get_ref().0
+    }
+
+    /// Gets a reference to the underlying `BitWriter`.
+    #[inline]
+    pub fn get_ref(&self) -> &BitWriter<E, W> {
+        &self.inner
+    }
+
+    /// Returns whether the writer is aligned to the byte boundary.
+    #[inline]
+    pub fn is_aligned(&self) -> bool {
+        self.inner.is_aligned()
+    }
+
+    /// Aligns to byte boundary, see `BitWriter::align`.
+    pub fn align(&mut self) -> Res<()> {
+        self.inner.align()?;
+        self.observed.align()
+    }
+
+    /// Writes a single bit, see `BitWriter::write_bit`.
+    pub fn write_bit(&mut self, bit: bool) -> Res<()> {
+        self.inner.write_bit(bit)?;
+        self.observed.write_bit(bit)
+    }
+
+    /// Writes 8 bits or less, see `BitWriter::write_bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8.
+    pub fn write_bits(&mut self, bits: u8, count: u8) -> Res<()> {
+        self.inner.write_bits(bits, count)?;
+        self.observed.write_bits(bits, count)
+    }
+
+    /// Writes 128 bits or less, see `BitWriter::write_bits_u128`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 128.
+    pub fn write_bits_u128(&mut self, value: u128, count: u8) -> Res<()> {
+        self.inner.write_bits_u128(value, count)?;
+        self.observed.write_bits_u128(value, count)
+    }
+
+    /// Aligns both the underlying `BitWriter` and the digest, and returns them.
+    pub fn finish(mut self) -> Res<(BitWriter<E, W>, D)> {
+        self.inner.align()?;
+        let sink = self.observed.into_inner()?;
+        Ok((self.inner, sink.0))
+    }
+}
+
+/// Write bytes while feeding them to the digest.
+impl<E: BitEndianness, W: Write, D: BitDigest> Write for DigestWriter<E, W, D> {
+    fn write(&mut self, buf: &[u8]) -> Res<usize> {
+        let count_written = self.inner.write(buf)?;
+        self.observed.write_all(&buf[..count_written])?;
+        Ok(count_written)
+    }
+
+    fn flush(&mut self) -> Res<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitDigest;
+    use crate::{BEBitReader, DigestReader, DigestWriter, LEBitWriter};
+    use std::io::{Read, Write};
+
+    #[derive(Default)]
+    struct Collect(Vec<u8>);
+
+    impl BitDigest for Collect {
+        fn update(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    #[test]
+    fn reader_observes_shifted_bytes() {
+        let mut reader = DigestReader::new(
+            BEBitReader::new(&b"\xaa\x8c\xae\x6e\x80"[..]),
+            Collect::default(),
+        );
+        reader.read_bits(3).unwrap();
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"Test");
+        assert_eq!(reader.digest().0, b"\xaa\x8c\xae\x6e");
+        reader.read_bit().unwrap();
+        let (_, digest) = reader.finish().unwrap();
+        assert_eq!(digest.0, b"\xaa\x8c\xae\x6e\x80");
+    }
+
+    #[test]
+    fn reader_align_observes_skipped_bits() {
+        let mut reader = DigestReader::new(BEBitReader::new(&b"\xff\x01"[..]), Collect::default());
+        reader.read_bits(2).unwrap();
+        reader.align().unwrap();
+        assert!(reader.is_aligned());
+        assert_eq!(reader.read_bits(8).unwrap(), 0x01);
+        assert_eq!(reader.finish().unwrap().1.0, b"\xff\x01");
+    }
+
+    #[test]
+    fn writer_matches_output() {
+        let mut writer = DigestWriter::new(LEBitWriter::new(vec![]), Collect::default());
+        writer.write_bit(true).unwrap();
+        writer.write_all(b"Test").unwrap();
+        writer.write_bits(0x05, 3).unwrap();
+        let (writer, digest) = writer.finish().unwrap();
+        assert_eq!(writer.into_inner().unwrap(), digest.0);
+    }
+}
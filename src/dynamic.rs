@@ -0,0 +1,291 @@
+use std::io::Result as Res;
+use std::io::{Read, Write};
+
+use crate::endian::BitOrder;
+use crate::{
+    BEBitReader, BEBitWriter, BitRead, BitWrite, IntoInnerError, LEBitReader, LEBitWriter,
+};
+
+/// A `BitReader` whose bit endianness is chosen at runtime.
+///
+/// Use this when the bit order is only known once the program runs, like a configuration option, and write the decoder against [`BitRead`] so that it is shared between both orders. Each call dispatches on the variant, so prefer a `BitReader` with a type parameter when the order is known at compile time.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BitOrder, BitRead, DynBitReader};
+///
+/// // for example from a configuration file
+/// let order = BitOrder::MsbFirst;
+/// let mut reader = DynBitReader::new(&b"\x12"[..], order);
+/// assert_eq!(reader.read_bits(4).unwrap(), 1);
+/// ```
+pub enum DynBitReader<R: Read> {
+    /// Reads most significant bits first.
+    MsbFirst(BEBitReader<R>),
+    /// Reads least significant bits first.
+    LsbFirst(LEBitReader<R>),
+}
+
+impl<R: Read> DynBitReader<R> {
+    /// Creates a new `DynBitReader` reading from `inner` in the bit order `order`.
+    #[inline]
+    pub fn new(inner: R, order: BitOrder) -> Self {
+        match order {
+            BitOrder::MsbFirst => Self::MsbFirst(BEBitReader::new(inner)),
+            BitOrder::LsbFirst => Self::LsbFirst(LEBitReader::new(inner)),
+        }
+    }
+
+    /// Returns the bit order of this reader.
+    #[inline]
+    pub fn bit_order(&self) -> BitOrder {
+        match self {
+            Self::MsbFirst(_) => BitOrder::MsbFirst,
+            Self::LsbFirst(_) => BitOrder::LsbFirst,
+        }
+    }
+
+    /// Returns the number of bits read so far, see `BitReader::bit_position`.
+    #[inline]
+    pub fn bit_position(&self) -> u64 {
+        match self {
+            Self::MsbFirst(r) => r.bit_position(),
+            Self::LsbFirst(r) => r.bit_position(),
+        }
+    }
+
+    /// Gets a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        match self {
+            Self::MsbFirst(r) => r.get_ref(),
+            Self::LsbFirst(r) => r.get_ref(),
+        }
+    }
+
+    /// Unwraps this `DynBitReader`, returning the underlying reader, see `BitReader::into_inner`.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        match self {
+            Self::MsbFirst(r) => r.into_inner(),
+            Self::LsbFirst(r) => r.into_inner(),
+        }
+    }
+}
+
+impl<R: Read> BitRead for DynBitReader<R> {
+    #[inline]
+    fn read_bit(&mut self) -> Res<bool> {
+        match self {
+            Self::MsbFirst(r) => r.read_bit(),
+            Self::LsbFirst(r) => r.read_bit(),
+        }
+    }
+
+    #[inline]
+    fn read_bits(&mut self, count: u8) -> Res<u8> {
+        match self {
+            Self::MsbFirst(r) => r.read_bits(count),
+            Self::LsbFirst(r) => r.read_bits(count),
+        }
+    }
+
+    #[inline]
+    fn read_bits_u64(&mut self, count: u8) -> Res<u64> {
+        match self {
+            Self::MsbFirst(r) => r.read_bits_u64(count),
+            Self::LsbFirst(r) => r.read_bits_u64(count),
+        }
+    }
+
+    #[inline]
+    fn is_aligned(&self) -> bool {
+        match self {
+            Self::MsbFirst(r) => r.is_aligned(),
+            Self::LsbFirst(r) => r.is_aligned(),
+        }
+    }
+
+    #[inline]
+    fn align(&mut self) -> Res<()> {
+        match self {
+            Self::MsbFirst(r) => r.align(),
+            Self::LsbFirst(r) => r.align(),
+        }
+        Ok(())
+    }
+}
+
+/// Read bytes with bit shifting support, see the `Read` implementation of `BitReader`.
+impl<R: Read> Read for DynBitReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Res<usize> {
+        match self {
+            Self::MsbFirst(r) => r.read(buf),
+            Self::LsbFirst(r) => r.read(buf),
+        }
+    }
+}
+
+/// A `BitWriter` whose bit endianness is chosen at runtime.
+///
+/// This is the writing counterpart of [`DynBitReader`], write the encoder against [`BitWrite`] to share it between both orders.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BitOrder, BitWrite, DynBitWriter};
+///
+/// let mut writer = DynBitWriter::new(vec![], BitOrder::LsbFirst);
+/// writer.write_bits(0x01, 4).unwrap();
+/// assert_eq!(writer.into_inner().unwrap(), b"\x01");
+/// ```
+#[derive(Debug)]
+pub enum DynBitWriter<W: Write> {
+    /// Writes most significant bits first.
+    MsbFirst(BEBitWriter<W>),
+    /// Writes least significant bits first.
+    LsbFirst(LEBitWriter<W>),
+}
+
+impl<W: Write> DynBitWriter<W> {
+    /// Creates a new `DynBitWriter` writing to `inner` in the bit order `order`.
+    #[inline]
+    pub fn new(inner: W, order: BitOrder) -> Self {
+        match order {
+            BitOrder::MsbFirst => Self::MsbFirst(BEBitWriter::new(inner)),
+            BitOrder::LsbFirst => Self::LsbFirst(LEBitWriter::new(inner)),
+        }
+    }
+
+    /// Returns the bit order of this writer.
+    #[inline]
+    pub fn bit_order(&self) -> BitOrder {
+        match self {
+            Self::MsbFirst(_) => BitOrder::MsbFirst,
+            Self::LsbFirst(_) => BitOrder::LsbFirst,
+        }
+    }
+
+    /// Returns the number of bits written so far, see `BitWriter::bit_position`.
+    #[inline]
+    pub fn bit_position(&self) -> u64 {
+        match self {
+            Self::MsbFirst(w) => w.bit_position(),
+            Self::LsbFirst(w) => w.bit_position(),
+        }
+    }
+
+    /// Gets a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        match self {
+            Self::MsbFirst(w) => w.get_ref(),
+            Self::LsbFirst(w) => w.get_ref(),
+        }
+    }
+
+    /// Unwraps this `DynBitWriter`, returning the underlying writer, see `BitWriter::into_inner`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the `DynBitWriter` and the error if flushing the partial byte fails.
+    pub fn into_inner(self) -> Result<W, IntoInnerError<Self>> {
+        match self {
+            Self::MsbFirst(w) => w.into_inner().map_err(|e| e.map(Self::MsbFirst)),
+            Self::LsbFirst(w) => w.into_inner().map_err(|e| e.map(Self::LsbFirst)),
+        }
+    }
+}
+
+impl<W: Write> BitWrite for DynBitWriter<W> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> Res<()> {
+        match self {
+            Self::MsbFirst(w) => w.write_bit(bit),
+            Self::LsbFirst(w) => w.write_bit(bit),
+        }
+    }
+
+    #[inline]
+    fn write_bits(&mut self, bits: u8, count: u8) -> Res<()> {
+        match self {
+            Self::MsbFirst(w) => w.write_bits(bits, count),
+            Self::LsbFirst(w) => w.write_bits(bits, count),
+        }
+    }
+
+    #[inline]
+    fn write_bits_u64(&mut self, value: u64, count: u8) -> Res<()> {
+        match self {
+            Self::MsbFirst(w) => w.write_bits_u64(value, count),
+            Self::LsbFirst(w) => w.write_bits_u64(value, count),
+        }
+    }
+
+    #[inline]
+    fn is_aligned(&self) -> bool {
+        match self {
+            Self::MsbFirst(w) => w.is_aligned(),
+            Self::LsbFirst(w) => w.is_aligned(),
+        }
+    }
+
+    #[inline]
+    fn align(&mut self) -> Res<()> {
+        match self {
+            Self::MsbFirst(w) => w.align(),
+            Self::LsbFirst(w) => w.align(),
+        }
+    }
+}
+
+/// Write bytes with bit shifting support, see the `Write` implementation of `BitWriter`.
+impl<W: Write> Write for DynBitWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Res<usize> {
+        match self {
+            Self::MsbFirst(w) => w.write(buf),
+            Self::LsbFirst(w) => w.write(buf),
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Res<()> {
+        match self {
+            Self::MsbFirst(w) => w.flush(),
+            Self::LsbFirst(w) => w.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::{BitOrder, BitRead, BitWrite, DynBitReader, DynBitWriter};
+
+    fn roundtrip(order: BitOrder) -> Vec<u8> {
+        let mut writer = DynBitWriter::new(vec![], order);
+        assert_eq!(writer.bit_order(), order);
+        writer.write_bit(true).unwrap();
+        writer.write_bits_u64(0x2ab, 10).unwrap();
+        writer.align().unwrap();
+        let data = writer.into_inner().unwrap();
+
+        let mut reader = DynBitReader::new(&data[..], order);
+        assert!(reader.read_bit().unwrap());
+        assert_eq!(reader.read_bits_u64(10).unwrap(), 0x2ab);
+        assert_eq!(reader.bit_position(), 11);
+        reader.align().unwrap();
+        assert_eq!(reader.read(&mut [0; 1]).unwrap(), 0);
+        data
+    }
+
+    #[test]
+    fn both_orders() {
+        assert_eq!(roundtrip(BitOrder::MsbFirst), b"\xd5\x60");
+        assert_eq!(roundtrip(BitOrder::LsbFirst), b"\x57\x05");
+    }
+}
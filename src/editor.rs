@@ -0,0 +1,295 @@
+use std::io::{Read, Write};
+use std::ops::Range;
+
+use crate::endian::{BE, BitEndianness, LE};
+use crate::{BitReader, BitWriter};
+
+/// Edits big endian bit data.
+pub type BEBitEditor = BitEditor<BE>;
+/// Edits little endian bit data.
+pub type LEBitEditor = BitEditor<LE>;
+
+/// Copies `count` bits starting at bit `start` of `data` to `writer`.
+///
+/// Whole bytes go through the shifting `Read` and `Write` implementations, so this doesn't handle the bits one by one.
+fn copy_bits<E: BitEndianness>(
+    writer: &mut BitWriter<E, Vec<u8>>,
+    data: &[u8],
+    start: u64,
+    count: u64,
+) {
+    if count == 0 {
+        return;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let mut reader = BitReader::<E, _>::new(&data[(start / 8) as usize..]);
+    #[allow(clippy::cast_possible_truncation)]
+    let skip = (start % 8) as u8;
+    reader.read_bits(skip).expect("bit range is in bounds");
+    let mut remaining = count;
+    let mut chunk = [0; 256];
+    while remaining >= 8 {
+        #[allow(clippy::cast_possible_truncation)]
+        let len = std::cmp::min(remaining / 8, chunk.len() as u64) as usize;
+        reader
+            .read_exact(&mut chunk[..len])
+            .expect("bit range is in bounds");
+        writer
+            .write_all(&chunk[..len])
+            .expect("writing to a Vec cannot fail");
+        remaining -= len as u64 * 8;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let tail = remaining as u8;
+    let bits = reader.read_bits(tail).expect("bit range is in bounds");
+    writer
+        .write_bits(bits, tail)
+        .expect("writing to a Vec cannot fail");
+}
+
+/// An owned buffer of bits supporting insertion, deletion and replacement of arbitrary bit ranges.
+///
+/// Bits are numbered in stream order, like in [`RankSelect`](crate::RankSelect): bit 0 is the first bit a `BitReader` with the same bit endianness would read from the data. Bits to insert are given the same way, as a byte slice and the number of bits to take from it.
+///
+/// Edits shift the bits after the edited range a byte at a time, using the same shifting as the unaligned `Read` and `Write` implementations, so they take time linear in the length of the buffer regardless of the bit offsets involved.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::BEBitEditor;
+///
+/// let mut editor = BEBitEditor::new(vec![0xf0, 0x0f], 16);
+/// editor.insert(4, b"\xa0", 4);
+/// assert_eq!(editor.as_bytes(), b"\xfa\x00\xf0");
+/// editor.delete(0..8);
+/// assert_eq!(editor.as_bytes(), b"\x00\xf0");
+/// assert_eq!(editor.len(), 12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitEditor<E: BitEndianness> {
+    data: Vec<u8>,
+    len: u64,
+    phantom: std::marker::PhantomData<E>,
+}
+
+impl<E: BitEndianness> BitEditor<E> {
+    /// Creates an editor over the first `len` bits of `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` has less than `len` bits.
+    #[must_use]
+    pub fn new(mut data: Vec<u8>, len: u64) -> Self {
+        assert!(len <= data.len() as u64 * 8, "data is shorter than len");
+        #[allow(clippy::cast_possible_truncation)]
+        data.truncate(len.div_ceil(8) as usize);
+        let mut editor = Self {
+            data,
+            len,
+            phantom: std::marker::PhantomData,
+        };
+        editor.clear_padding();
+        editor
+    }
+
+    /// Returns the number of bits.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns whether there are no bits.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bytes holding the bits, with the bits after `len()` in the last byte set to zero.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the bytes and the number of bits.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> (Vec<u8>, u64) {
+        (self.data, self.len)
+    }
+
+    /// Returns the bit at position `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` >= `len()`.
+    #[must_use]
+    pub fn get(&self, i: u64) -> bool {
+        assert!(i < self.len, "index out of bounds");
+        #[allow(clippy::cast_possible_truncation)]
+        let (byte, bit) = (self.data[(i / 8) as usize], (i % 8) as u8);
+        byte & E::shift_lsb(E::shift_msb(0xff, 7), bit) != 0
+    }
+
+    /// Sets the bit at position `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` >= `len()`.
+    pub fn set(&mut self, i: u64, value: bool) {
+        assert!(i < self.len, "index out of bounds");
+        #[allow(clippy::cast_possible_truncation)]
+        let (byte, bit) = (&mut self.data[(i / 8) as usize], (i % 8) as u8);
+        let mask = E::shift_lsb(E::shift_msb(0xff, 7), bit);
+        if value {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+    }
+
+    /// Inserts the first `count` bits of `bits` before position `at`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` > `len()` or if `bits` has less than `count` bits.
+    pub fn insert(&mut self, at: u64, bits: &[u8], count: u64) {
+        self.splice(at..at, bits, count);
+    }
+
+    /// Removes the bits in `range`, shifting the following bits towards the start.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is decreasing or extends past `len()`.
+    pub fn delete(&mut self, range: Range<u64>) {
+        self.splice(range, &[], 0);
+    }
+
+    /// Replaces the bits in `range` with the first `count` bits of `bits`, which may be more or less than the length of the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is decreasing or extends past `len()`, or if `bits` has less than `count` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::LEBitEditor;
+    ///
+    /// let mut editor = LEBitEditor::new(vec![0xff], 8);
+    /// editor.splice(2..4, b"\x00", 5);
+    /// assert_eq!(editor.len(), 11);
+    /// assert_eq!(editor.as_bytes(), b"\x83\x07");
+    /// ```
+    pub fn splice(&mut self, range: Range<u64>, bits: &[u8], count: u64) {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "range out of bounds"
+        );
+        assert!(count <= bits.len() as u64 * 8, "bits is shorter than count");
+        if range.start == range.end && count == 0 {
+            return;
+        }
+        let new_len = self.len - (range.end - range.start) + count;
+        #[allow(clippy::cast_possible_truncation)]
+        let head_bytes = (range.start / 8) as usize;
+        let mut data = Vec::with_capacity(usize::try_from(new_len.div_ceil(8)).unwrap_or(0));
+        data.extend_from_slice(&self.data[..head_bytes]);
+        let mut writer = BitWriter::<E, _>::new(data);
+        copy_bits(
+            &mut writer,
+            &self.data,
+            head_bytes as u64 * 8,
+            range.start % 8,
+        );
+        copy_bits(&mut writer, bits, 0, count);
+        copy_bits(&mut writer, &self.data, range.end, self.len - range.end);
+        self.data = writer.finish().expect("writing to a Vec cannot fail");
+        self.len = new_len;
+    }
+
+    /// Zeroes the bits after `len` in the last byte, so equal bit sequences have equal bytes.
+    fn clear_padding(&mut self) {
+        #[allow(clippy::cast_possible_truncation)]
+        let used = (self.len % 8) as u8;
+        if let Some(last) = self.data.last_mut()
+            && used != 0
+        {
+            *last &= E::shift_msb(0xff, 8 - used);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitEditor, LEBitEditor};
+
+    /// Bits of a little endian buffer, for comparing with a naive `Vec<bool>` model.
+    fn bits(editor: &LEBitEditor) -> Vec<bool> {
+        (0..editor.len()).map(|i| editor.get(i)).collect()
+    }
+
+    fn to_bits(bytes: &[u8], count: usize) -> Vec<bool> {
+        (0..count)
+            .map(|i| bytes[i / 8] >> (i % 8) & 1 != 0)
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn matches_naive() {
+        let data: Vec<u8> = (0..100u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 11) as u8)
+            .collect();
+        let insert: Vec<u8> = data.iter().rev().copied().collect();
+        let mut editor = LEBitEditor::new(data.clone(), 797);
+        let mut model = to_bits(&data, 797);
+        for (start, end, count) in [
+            (0, 0, 13),
+            (5, 300, 0),
+            (3, 4, 500),
+            (17, 17, 1),
+            (100, 600, 64),
+            (0, 50, 7),
+        ] {
+            editor.splice(start..end, &insert, count);
+            model.splice(
+                start as usize..end as usize,
+                to_bits(&insert, count as usize),
+            );
+            assert_eq!(bits(&editor), model);
+        }
+        editor.delete(0..editor.len());
+        assert!(editor.is_empty());
+        assert!(editor.as_bytes().is_empty());
+    }
+
+    #[test]
+    fn big_endian() {
+        let mut editor = BEBitEditor::new(vec![0b1010_0000], 3);
+        editor.insert(1, b"\xff", 2);
+        assert_eq!(editor.as_bytes(), b"\xe8");
+        editor.set(1, false);
+        assert_eq!(editor.as_bytes(), b"\xa8");
+        assert!(editor.get(0));
+        assert!(!editor.get(1));
+        editor.delete(0..1);
+        assert_eq!(editor.into_inner(), (vec![0x50], 4));
+    }
+
+    #[test]
+    fn padding_is_cleared() {
+        let editor = LEBitEditor::new(vec![0xff, 0xff, 0xff], 12);
+        assert_eq!(editor.as_bytes(), b"\xff\x0f");
+    }
+
+    #[test]
+    #[should_panic(expected = "range out of bounds")]
+    fn delete_out_of_bounds() {
+        let mut editor = LEBitEditor::new(vec![0], 8);
+        editor.delete(4..9);
+    }
+}
@@ -0,0 +1,260 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BEBitReader, BEBitWriter, BERankSelect, BitReader, BitWriter};
+
+/// An Elias-Fano encoded monotone sequence of integers with random access.
+///
+/// Each value is split into `l` low bits, stored verbatim, and the remaining high bits, stored as a unary-coded bit vector indexed with [`RankSelect`](crate::RankSelect). This takes at most `2 + log2(u / n)` bits per value, where `u` is the largest value and `n` the number of values.
+///
+/// The sequence can be written to and read from a `BitWriter`/`BitReader` of any bit endianness with `write_to` and `read_from`.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::EliasFano;
+///
+/// let sequence = EliasFano::new(&[2, 3, 5, 7, 11, 13, 24]).unwrap();
+/// assert_eq!(sequence.len(), 7);
+/// assert_eq!(sequence.get(4), Some(11));
+/// assert_eq!(sequence.get(7), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EliasFano {
+    len: u64,
+    low_bits: u8,
+    lower: Vec<u8>,
+    upper: BERankSelect,
+}
+
+impl EliasFano {
+    /// Encodes a non-decreasing sequence of integers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if the values are not sorted.
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn new(values: &[u64]) -> Res<Self> {
+        if values.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Elias-Fano sequences must be non-decreasing",
+            ));
+        }
+        let len = values.len() as u64;
+        let max = values.last().copied().unwrap_or(0);
+        let universe = max.saturating_add(1);
+        let low_bits = if len == 0 || universe <= len {
+            0
+        } else {
+            (universe / len).ilog2()
+        };
+        #[allow(clippy::cast_possible_truncation)]
+        let low_bits = low_bits as u8;
+        let mut lower = BEBitWriter::new(vec![]);
+        let mut upper = BEBitWriter::new(vec![]);
+        let mut last_high = 0;
+        for &value in values {
+            lower.write_bits_wide(value, low_bits)?;
+            let high = value.checked_shr(u32::from(low_bits)).unwrap_or(0);
+            upper.write_zeros(high - last_high)?;
+            upper.write_bit(true)?;
+            last_high = high;
+        }
+        let upper_len = len + last_high;
+        Ok(Self::from_parts(
+            len,
+            low_bits,
+            lower.into_inner()?,
+            &upper.into_inner()?,
+            upper_len,
+        ))
+    }
+
+    fn from_parts(len: u64, low_bits: u8, lower: Vec<u8>, upper: &[u8], upper_len: u64) -> Self {
+        Self {
+            len,
+            low_bits,
+            lower,
+            upper: BERankSelect::new(upper, upper_len),
+        }
+    }
+
+    /// Returns the number of values in the sequence.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns whether the sequence is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the `i`-th value, or `None` if `i` is out of bounds.
+    #[must_use]
+    pub fn get(&self, i: u64) -> Option<u64> {
+        let high = self.upper.select1(i)? - i;
+        let start = i * u64::from(self.low_bits);
+        let byte = usize::try_from(start / 8).ok()?;
+        let mut reader = BEBitReader::new(&self.lower[byte..]);
+        #[allow(clippy::cast_possible_truncation)]
+        reader.read_bits((start % 8) as u8).ok()?;
+        let low = reader.read_bits_wide(self.low_bits).ok()?;
+        Some(high.checked_shl(u32::from(self.low_bits)).unwrap_or(0) | low)
+    }
+
+    /// Returns an iterator over the values.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.len).filter_map(|i| self.get(i))
+    }
+
+    /// Writes the sequence to a `BitWriter`.
+    ///
+    /// The number of values is written as 64 bits, followed by the 7-bit low bit width, the low bits of every value, the 64-bit length of the high bit vector, and the high bit vector itself.
+    pub fn write_to<E: BitEndianness, W: Write>(&self, writer: &mut BitWriter<E, W>) -> Res<()> {
+        writer.write_bits_wide(self.len, 64)?;
+        writer.write_bits(self.low_bits, 7)?;
+        let mut lower = BEBitReader::new(&self.lower[..]);
+        for _ in 0..self.len {
+            writer.write_bits_wide(lower.read_bits_wide(self.low_bits)?, self.low_bits)?;
+        }
+        writer.write_bits_wide(self.upper.len(), 64)?;
+        for i in 0..self.upper.len() {
+            writer.write_bit(self.upper.get(i))?;
+        }
+        Ok(())
+    }
+
+    /// Reads a sequence written with `write_to` from a `BitReader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the header is inconsistent, like a number of values that doesn't fit in the high bit vector.
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_from<E: BitEndianness, R: Read>(reader: &mut BitReader<E, R>) -> Res<Self> {
+        let len = reader.read_bits_wide(64)?;
+        let low_bits = reader.read_bits(7)?;
+        if low_bits > 64 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("low bit width {low_bits} is larger than 64"),
+            ));
+        }
+        let mut lower = BEBitWriter::new(vec![]);
+        // zero-width values consume no input, so looping over them would spin on a corrupt length
+        if low_bits != 0 {
+            for _ in 0..len {
+                lower.write_bits_wide(reader.read_bits_wide(low_bits)?, low_bits)?;
+            }
+        }
+        let upper_len = reader.read_bits_wide(64)?;
+        if len > upper_len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("high bit vector of {upper_len} bits can't hold {len} values"),
+            ));
+        }
+        let mut upper = BEBitWriter::new(vec![]);
+        let mut ones = 0;
+        for _ in 0..upper_len {
+            let bit = reader.read_bit()?;
+            ones += u64::from(bit);
+            upper.write_bit(bit)?;
+        }
+        if ones != len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("high bit vector has {ones} set bits, expected {len}"),
+            ));
+        }
+        Ok(Self::from_parts(
+            len,
+            low_bits,
+            lower.into_inner()?,
+            &upper.into_inner()?,
+            upper_len,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, EliasFano, LEBitReader, LEBitWriter};
+
+    #[test]
+    fn random_access() {
+        let values: Vec<u64> = (0..1000).map(|i| i * i / 7).collect();
+        let sequence = EliasFano::new(&values).unwrap();
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(sequence.get(i as u64), Some(value));
+        }
+        assert!(sequence.iter().eq(values.iter().copied()));
+    }
+
+    #[test]
+    fn duplicates_and_extremes() {
+        for values in [
+            vec![],
+            vec![0],
+            vec![0, 0, 0],
+            vec![5, 5, 6],
+            vec![1 << 40, 1 << 41],
+            vec![0, u64::MAX],
+            vec![u64::MAX],
+        ] {
+            let sequence = EliasFano::new(&values).unwrap();
+            assert!(sequence.iter().eq(values.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn unsorted() {
+        assert!(EliasFano::new(&[3, 2]).is_err());
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let values = [3, 4, 7, 13, 14, 15, 21, 43];
+        let sequence = EliasFano::new(&values).unwrap();
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bit(true).unwrap();
+        sequence.write_to(&mut writer).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert!(reader.read_bit().unwrap());
+        let read = EliasFano::read_from(&mut reader).unwrap();
+        assert!(read.iter().eq(values.iter().copied()));
+    }
+
+    #[test]
+    fn inconsistent_header() {
+        let mut data = vec![0; 8];
+        data[7] = 2;
+        data.extend([0; 10]);
+        let mut reader = BEBitReader::new(&data[..]);
+        assert!(EliasFano::read_from(&mut reader).is_err());
+    }
+
+    #[test]
+    fn huge_len_without_low_bits() {
+        use crate::BEBitWriter;
+        use std::io::ErrorKind;
+
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bits_wide(u64::MAX, 64).unwrap();
+        writer.write_bits(0, 7).unwrap();
+        writer.write_bits_wide(3, 64).unwrap();
+        writer.write_bits(0x7, 3).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        let err = EliasFano::read_from(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
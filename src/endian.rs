@@ -2,20 +2,29 @@
 ///
 /// You can't implement this trait, it only exists as a trait bound.
 pub trait BitEndianness: private::Sealed {
+    /// Whether the first bit of a byte is its least significant bit.
+    const LSB_FIRST: bool;
     /// Shifts towards the most significant bit.
     fn shift_msb(val: u8, by: u8) -> u8;
     /// Shifts towards the least significant bit.
     fn shift_lsb(val: u8, by: u8) -> u8;
     /// Aligns right.
     fn align_right(val: u8, count: u8) -> u8;
+    /// Appends `count` bits read after the first `len` bits of a wider value.
+    fn push_bits(acc: u64, len: u8, bits: u8, count: u8) -> u64;
+    /// Extracts the `count` bits to be written after the first `offset` bits of a `len`-bit value.
+    fn take_bits(value: u64, len: u8, offset: u8, count: u8) -> u8;
+    /// Shifts eight consecutive bytes towards the least significant bit by 1 to 7 bits, shifting in bits from `carry`, the byte before them.
+    fn shift_lsb_word(bytes: [u8; 8], carry: u8, by: u8) -> [u8; 8];
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct BigEndian;
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct LittleEndian;
 
 impl BitEndianness for BigEndian {
+    const LSB_FIRST: bool = false;
     #[inline]
     fn shift_msb(val: u8, by: u8) -> u8 {
         val << by
@@ -28,8 +37,22 @@ impl BitEndianness for BigEndian {
     fn align_right(val: u8, _count: u8) -> u8 {
         val
     }
+    #[inline]
+    fn push_bits(acc: u64, _len: u8, bits: u8, count: u8) -> u64 {
+        (acc << count) | u64::from(bits)
+    }
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    fn take_bits(value: u64, len: u8, offset: u8, count: u8) -> u8 {
+        (value >> (len - offset - count)) as u8 & (0xff >> (8 - count))
+    }
+    #[inline]
+    fn shift_lsb_word(bytes: [u8; 8], carry: u8, by: u8) -> [u8; 8] {
+        ((u64::from_be_bytes(bytes) >> by) | (u64::from(carry) << (64 - by))).to_be_bytes()
+    }
 }
 impl BitEndianness for LittleEndian {
+    const LSB_FIRST: bool = true;
     #[inline]
     fn shift_msb(val: u8, by: u8) -> u8 {
         val >> by
@@ -42,11 +65,46 @@ impl BitEndianness for LittleEndian {
     fn align_right(val: u8, count: u8) -> u8 {
         Self::shift_msb(val, 8 - count)
     }
+    #[inline]
+    fn push_bits(acc: u64, len: u8, bits: u8, _count: u8) -> u64 {
+        acc | (u64::from(bits) << len)
+    }
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    fn take_bits(value: u64, _len: u8, offset: u8, count: u8) -> u8 {
+        (value >> offset) as u8 & (0xff >> (8 - count))
+    }
+    #[inline]
+    fn shift_lsb_word(bytes: [u8; 8], carry: u8, by: u8) -> [u8; 8] {
+        ((u64::from_le_bytes(bytes) << by) | (u64::from(carry) >> (8 - by))).to_le_bytes()
+    }
 }
 
 pub type BE = BigEndian;
 pub type LE = LittleEndian;
 
+/// Byte order of multi-byte values, for the few helpers dealing with them.
+///
+/// Unlike `BitEndianness`, this is chosen per call, since byte order is usually a property of a field rather than of the whole stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Most significant byte first.
+    Big,
+    /// Least significant byte first.
+    Little,
+}
+
+/// Bit endianness chosen at runtime, for `DynBitReader` and `DynBitWriter`.
+///
+/// This is the runtime counterpart of the `BE` and `LE` type parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Most significant bit first, like `BE`.
+    MsbFirst,
+    /// Least significant bit first, like `LE`.
+    LsbFirst,
+}
+
 // ensures no one else implements the trait
 mod private {
     pub trait Sealed {}
@@ -0,0 +1,173 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Write};
+
+use crate::BitWriter;
+use crate::endian::BitEndianness;
+
+/// A fixed-capacity byte buffer for writing without allocating.
+///
+/// The buffer is backed by an array of `N` bytes. Writing past its capacity fails with an error of kind [`StorageFull`] instead of growing it, so a `BitWriter` over a `FixedBuffer` can build frames of a known maximum size without the output growing. The `BitWriter` itself still allocates its small buffer for unaligned writes when it is created.
+///
+/// A `BitWriter` only writes a byte to the buffer once it is complete, so an overflowing partial byte is reported when it is completed, aligned or flushed. Use `BitWriter::remaining_capacity_bits` to check whether a field fits before writing it.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitWriter, FixedBuffer};
+///
+/// let mut writer = BEBitWriter::new(FixedBuffer::<2>::new());
+/// writer.write_bits(0xab, 8).unwrap();
+/// writer.write_bits(0x5, 4).unwrap();
+/// assert_eq!(writer.remaining_capacity_bits(), 4);
+/// // the partial byte is only written once it is complete
+/// writer.write_bits(0xff, 8).unwrap();
+/// assert!(writer.finish().is_err());
+/// ```
+///
+/// [`StorageFull`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.StorageFull
+#[derive(Clone, Debug)]
+pub struct FixedBuffer<const N: usize> {
+    data: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedBuffer<N> {
+    /// Creates a new empty buffer.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            data: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the written bytes.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    /// Returns the number of written bytes.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether no bytes have been written.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the total number of bytes the buffer can hold.
+    #[inline]
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of bytes that can still be written.
+    #[inline]
+    #[must_use]
+    pub const fn remaining(&self) -> usize {
+        N - self.len
+    }
+
+    /// Removes all written bytes.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for FixedBuffer<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes as many bytes as fit, failing with [`StorageFull`] only if the buffer is already full.
+///
+/// [`StorageFull`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.StorageFull
+impl<const N: usize> Write for FixedBuffer<N> {
+    fn write(&mut self, buf: &[u8]) -> Res<usize> {
+        if !buf.is_empty() && self.remaining() == 0 {
+            return Err(Error::new(
+                ErrorKind::StorageFull,
+                format!("fixed buffer capacity of {N} bytes exceeded"),
+            ));
+        }
+        let count = std::cmp::min(buf.len(), self.remaining());
+        self.data[self.len..self.len + count].copy_from_slice(&buf[..count]);
+        self.len += count;
+        Ok(count)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Res<()> {
+        Ok(())
+    }
+}
+
+impl<E: BitEndianness, const N: usize> BitWriter<E, FixedBuffer<N>> {
+    /// Returns the number of bits that can still be written before the buffer is full.
+    ///
+    /// This accounts for the bits of a partially written byte.
+    #[must_use]
+    pub fn remaining_capacity_bits(&self) -> u64 {
+        let pending = usize::from(!self.is_aligned());
+        let bytes = self
+            .get_ref()
+            .remaining()
+            .saturating_sub(self.buffer().len() + pending);
+        bytes as u64 * 8 + u64::from((8 - self.bit_offset()) % 8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Write};
+
+    use crate::{FixedBuffer, LEBitWriter};
+
+    #[test]
+    fn capacity() {
+        let mut writer = LEBitWriter::new(FixedBuffer::<3>::new());
+        assert_eq!(writer.remaining_capacity_bits(), 24);
+        writer.write_bits(0x3f, 6).unwrap();
+        assert_eq!(writer.remaining_capacity_bits(), 18);
+        writer.write_ones(18).unwrap();
+        assert_eq!(writer.remaining_capacity_bits(), 0);
+        writer.write_bit(true).unwrap();
+        let err = writer.align().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::StorageFull);
+        assert_eq!(writer.get_ref().as_slice(), b"\xff\xff\xff");
+    }
+
+    #[test]
+    fn partial_write() {
+        let mut buffer = FixedBuffer::<4>::new();
+        assert_eq!(buffer.write(b"abcdef").unwrap(), 4);
+        assert_eq!(buffer.as_slice(), b"abcd");
+        assert!(buffer.write_all(b"e").is_err());
+        buffer.clear();
+        assert!(buffer.is_empty());
+        buffer.write_all(b"xy").unwrap();
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.remaining(), 2);
+    }
+
+    #[test]
+    fn with_buffer() {
+        let mut writer = LEBitWriter::with_buffer(2, FixedBuffer::<2>::new());
+        writer.write_bits(1, 1).unwrap();
+        writer.write_bits(0xff, 8).unwrap();
+        assert_eq!(writer.remaining_capacity_bits(), 7);
+        assert_eq!(writer.finish().unwrap().as_slice(), b"\xff\x01");
+    }
+}
@@ -0,0 +1,604 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+fn zero_elias() -> Error {
+    Error::new(ErrorKind::InvalidInput, "Elias codes can't code 0")
+}
+
+impl<E: BitEndianness, R: Read> BitReader<E, R> {
+    /// Reads a unary code, returning the number of bits before the first bit equal to `terminator`.
+    ///
+    /// The terminating bit is consumed as well. With `terminator` set to true, the value `n` is coded as `n` zeros followed by a one, otherwise as `n` ones followed by a zero. The bits are examined up to a byte at a time instead of bit by bit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x00\x5e"[..]);
+    /// assert_eq!(reader.read_unary(true).unwrap(), 9);
+    /// assert_eq!(reader.read_unary(false).unwrap(), 0);
+    /// assert_eq!(reader.read_unary(false).unwrap(), 4);
+    /// ```
+    pub fn read_unary(&mut self, terminator: bool) -> Res<u64> {
+        let mut count = 0;
+        loop {
+            let mut available = 8 - self.bit_offset();
+            if let Some(remaining) = self.scope_remaining() {
+                // the code may end right at the end of the scope, at the end of it the peek fails with the scope's error
+                #[allow(clippy::cast_possible_truncation)]
+                let remaining = remaining.min(8) as u8;
+                available = available.min(remaining).max(1);
+            }
+            let bits = self.peek_bits(available)?;
+            // make the terminator a one and the bits before it zeros
+            let marked = if terminator { bits } else { !bits };
+            #[allow(clippy::cast_possible_truncation)]
+            let run = if E::LSB_FIRST {
+                (u32::from(marked) | 1 << available).trailing_zeros() as u8
+            } else {
+                (marked << (8 - available)).leading_zeros() as u8
+            };
+            if run < available {
+                self.read_bits(run + 1)?;
+                return Ok(count + u64::from(run));
+            }
+            self.read_bits(available)?;
+            count += u64::from(available);
+        }
+    }
+
+    /// Reads a Rice code with parameter `k`, which is a Golomb code with divisor `2^k`.
+    ///
+    /// The quotient `value >> k` is coded in unary as zeros terminated by a one, followed by the remainder in `k` bits like `read_bits_u64(k)`. This is the coding of the residuals in FLAC.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` > 63.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the value does not fit in a `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x2e"[..]);
+    /// assert_eq!(reader.read_rice(2).unwrap(), 9);
+    /// assert_eq!(reader.read_rice(2).unwrap(), 2);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_rice(&mut self, k: u8) -> Res<u64> {
+        assert!(k <= 63);
+        let quotient = self.read_unary(true)?;
+        let remainder = self.read_bits_u64(k)?;
+        if quotient > u64::MAX >> k {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Rice code does not fit in 64 bits",
+            ));
+        }
+        Ok(quotient << k | remainder)
+    }
+
+    /// Reads a signed Rice code with parameter `k`.
+    ///
+    /// The value is read with `read_rice` and mapped to a signed integer like in FLAC, with even values mapped to non-negative integers and odd values to negative ones: 0, -1, 1, -2, 2 and so on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` > 63.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the value does not fit in an `i64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x2e"[..]);
+    /// assert_eq!(reader.read_rice_signed(2).unwrap(), -5);
+    /// assert_eq!(reader.read_rice_signed(2).unwrap(), 1);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_rice_signed(&mut self, k: u8) -> Res<i64> {
+        let value = self.read_rice(k)?;
+        #[allow(clippy::cast_possible_wrap)]
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    /// Reads an unsigned Exp-Golomb code, `ue(v)` in H.264 and H.265.
+    ///
+    /// The value plus one, which has `n + 1` significant bits, is coded as `n` zeros followed by its leading one, followed by its other `n` bits read like `read_bits_u64(n)`. With big endian bit numbering, this is the value plus one in `n + 1` bits after the zeros.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the value does not fit in a `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x22\x67"[..]);
+    /// assert_eq!(reader.read_ue().unwrap(), 3);
+    /// assert_eq!(reader.read_ue().unwrap(), 1);
+    /// assert_eq!(reader.read_ue().unwrap(), 2);
+    /// assert_eq!(reader.read_ue().unwrap(), 6);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_ue(&mut self) -> Res<u64> {
+        let code = self.read_exp_golomb()?;
+        u64::try_from(code).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                "Exp-Golomb code does not fit in 64 bits",
+            )
+        })
+    }
+
+    /// Reads a signed Exp-Golomb code, `se(v)` in H.264 and H.265.
+    ///
+    /// The value is read like with `read_ue` and mapped to 0, 1, -1, 2, -2 and so on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the value does not fit in an `i64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x22\x67"[..]);
+    /// assert_eq!(reader.read_se().unwrap(), 2);
+    /// assert_eq!(reader.read_se().unwrap(), 1);
+    /// assert_eq!(reader.read_se().unwrap(), -1);
+    /// assert_eq!(reader.read_se().unwrap(), -3);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_se(&mut self) -> Res<i64> {
+        let code = self.read_exp_golomb()?;
+        #[allow(clippy::cast_possible_wrap)]
+        let magnitude = code.div_ceil(2) as i128;
+        let value = if code % 2 == 0 { -magnitude } else { magnitude };
+        i64::try_from(value).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                "Exp-Golomb code does not fit in 64 bits",
+            )
+        })
+    }
+
+    /// Reads an Elias gamma code, which codes integers >= 1.
+    ///
+    /// A value with `n + 1` significant bits is coded as `n` zeros followed by its leading one, followed by its other `n` bits read like `read_bits_u64(n)`. This is the same as the Exp-Golomb code of the value minus one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the value does not fit in a `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\xa6\x40"[..]);
+    /// assert_eq!(reader.read_elias_gamma().unwrap(), 1);
+    /// assert_eq!(reader.read_elias_gamma().unwrap(), 2);
+    /// assert_eq!(reader.read_elias_gamma().unwrap(), 3);
+    /// assert_eq!(reader.read_elias_gamma().unwrap(), 4);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_elias_gamma(&mut self) -> Res<u64> {
+        let code = self.read_exp_golomb()?;
+        u64::try_from(code + 1).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                "Elias gamma code does not fit in 64 bits",
+            )
+        })
+    }
+
+    /// Reads an Elias delta code, which codes integers >= 1.
+    ///
+    /// The number of significant bits of the value is coded with `read_elias_gamma`, followed by the bits of the value below its leading one, read like `read_bits_u64`. This is shorter than the gamma code for values of more than a few bits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the value does not fit in a `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\xb4\xa2"[..]);
+    /// assert_eq!(reader.read_elias_delta().unwrap(), 1);
+    /// assert_eq!(reader.read_elias_delta().unwrap(), 5);
+    /// assert_eq!(reader.read_elias_delta().unwrap(), 17);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_elias_delta(&mut self) -> Res<u64> {
+        let len = self.read_elias_gamma()?;
+        if len > 64 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Elias delta code does not fit in 64 bits",
+            ));
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let rest = len as u8 - 1;
+        Ok(1 << rest | self.read_bits_u64(rest)?)
+    }
+
+    /// Reads an Exp-Golomb code of up to 65 bits.
+    fn read_exp_golomb(&mut self) -> Res<u128> {
+        let zeros = self.read_unary(true)?;
+        if zeros > 64 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Exp-Golomb code with {zeros} leading zeros is too long"),
+            ));
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let zeros = zeros as u8;
+        let rest = u128::from(self.read_bits_u64(zeros)?);
+        Ok((1 << zeros) - 1 + rest)
+    }
+}
+
+impl<E: BitEndianness, W: Write> BitWriter<E, W> {
+    /// Writes a unary code of `value` bits not equal to `terminator`, followed by `terminator`.
+    ///
+    /// See `BitReader::read_unary` for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_unary(9, true).unwrap();
+    /// writer.write_unary(0, false).unwrap();
+    /// writer.write_unary(4, false).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x00\x5e");
+    /// ```
+    pub fn write_unary(&mut self, value: u64, terminator: bool) -> Res<()> {
+        if terminator {
+            self.write_zeros(value)?;
+        } else {
+            self.write_ones(value)?;
+        }
+        self.write_bit(terminator)
+    }
+
+    /// Writes a Rice code of `value` with parameter `k`.
+    ///
+    /// See `BitReader::read_rice` for the format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` > 63.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_rice(2, 9).unwrap();
+    /// writer.write_rice(2, 2).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x2e");
+    /// ```
+    pub fn write_rice(&mut self, k: u8, value: u64) -> Res<()> {
+        assert!(k <= 63);
+        self.write_unary(value >> k, true)?;
+        self.write_bits_wide(value, k)
+    }
+
+    /// Writes a signed Rice code of `value` with parameter `k`.
+    ///
+    /// See `BitReader::read_rice_signed` for the format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` > 63.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_rice_signed(2, -5).unwrap();
+    /// writer.write_rice_signed(2, 1).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x2e");
+    /// ```
+    pub fn write_rice_signed(&mut self, k: u8, value: i64) -> Res<()> {
+        #[allow(clippy::cast_sign_loss)]
+        self.write_rice(k, (value << 1 ^ value >> 63) as u64)
+    }
+
+    /// Writes an unsigned Exp-Golomb code, `ue(v)` in H.264 and H.265.
+    ///
+    /// See `BitReader::read_ue` for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// for value in [3, 1, 2, 6] {
+    ///     writer.write_ue(value).unwrap();
+    /// }
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x22\x67");
+    /// ```
+    pub fn write_ue(&mut self, value: u64) -> Res<()> {
+        self.write_exp_golomb(u128::from(value))
+    }
+
+    /// Writes a signed Exp-Golomb code, `se(v)` in H.264 and H.265.
+    ///
+    /// See `BitReader::read_se` for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// for value in [2, 1, -1, -3] {
+    ///     writer.write_se(value).unwrap();
+    /// }
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x22\x67");
+    /// ```
+    pub fn write_se(&mut self, value: i64) -> Res<()> {
+        let magnitude = u128::from(value.unsigned_abs());
+        if value > 0 {
+            self.write_exp_golomb(magnitude * 2 - 1)
+        } else {
+            self.write_exp_golomb(magnitude * 2)
+        }
+    }
+
+    /// Writes an Elias gamma code.
+    ///
+    /// See `BitReader::read_elias_gamma` for the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `value` is 0, which can't be coded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// for value in [1, 2, 3, 4] {
+    ///     writer.write_elias_gamma(value).unwrap();
+    /// }
+    /// assert_eq!(writer.into_inner().unwrap(), b"\xa6\x40");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_elias_gamma(&mut self, value: u64) -> Res<()> {
+        if value == 0 {
+            return Err(zero_elias());
+        }
+        self.write_exp_golomb(u128::from(value - 1))
+    }
+
+    /// Writes an Elias delta code.
+    ///
+    /// See `BitReader::read_elias_delta` for the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `value` is 0, which can't be coded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// for value in [1, 5, 17] {
+    ///     writer.write_elias_delta(value).unwrap();
+    /// }
+    /// assert_eq!(writer.into_inner().unwrap(), b"\xb4\xa2");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_elias_delta(&mut self, value: u64) -> Res<()> {
+        if value == 0 {
+            return Err(zero_elias());
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let rest = value.ilog2() as u8;
+        self.write_elias_gamma(u64::from(rest) + 1)?;
+        self.write_bits_wide(value, rest)
+    }
+
+    /// Writes an Exp-Golomb code of up to 65 bits.
+    fn write_exp_golomb(&mut self, code: u128) -> Res<()> {
+        let value = code + 1;
+        #[allow(clippy::cast_possible_truncation)]
+        let zeros = value.ilog2() as u8;
+        self.write_unary(zeros.into(), true)?;
+        self.write_bits_u128(value, zeros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, BEBitWriter, LEBitReader, LEBitWriter};
+
+    const VALUES: [u64; 8] = [0, 1, 2, 7, 8, 9, 100, 1000];
+
+    #[test]
+    fn unary_roundtrip() {
+        for terminator in [false, true] {
+            let mut writer = LEBitWriter::new(vec![]);
+            writer.write_bits(0x5, 3).unwrap();
+            for value in VALUES {
+                writer.write_unary(value, terminator).unwrap();
+            }
+            let data = writer.into_inner().unwrap();
+            let mut reader = LEBitReader::new(&data[..]);
+            assert_eq!(reader.read_bits(3).unwrap(), 0x5);
+            for value in VALUES {
+                assert_eq!(reader.read_unary(terminator).unwrap(), value);
+            }
+
+            let mut writer = BEBitWriter::new(vec![]);
+            for value in VALUES {
+                writer.write_unary(value, terminator).unwrap();
+            }
+            let data = writer.into_inner().unwrap();
+            let mut reader = BEBitReader::new(&data[..]);
+            for value in VALUES {
+                assert_eq!(reader.read_unary(terminator).unwrap(), value);
+            }
+        }
+    }
+
+    #[test]
+    fn unary_at_scope_end() {
+        let mut reader = BEBitReader::new(&b"\x3f"[..]);
+        assert_eq!(reader.read_scope(3, |r| r.read_unary(true)).unwrap(), 2);
+        assert!(reader.read_scope(3, |r| r.read_unary(false)).is_err());
+        let mut reader = LEBitReader::new(&b"\xfc"[..]);
+        assert_eq!(reader.read_scope(3, |r| r.read_unary(true)).unwrap(), 2);
+        let mut reader = BEBitReader::new(&b"\x00\x10"[..]);
+        let mut take = reader.take_bits(12);
+        assert_eq!(take.read_unary(true).unwrap(), 11);
+    }
+
+    #[test]
+    fn rice_roundtrip() {
+        for k in [0, 1, 4, 13, 63] {
+            let mut writer = LEBitWriter::new(vec![]);
+            writer.write_bit(true).unwrap();
+            for value in VALUES {
+                writer.write_rice(k, value).unwrap();
+                writer
+                    .write_rice_signed(k, -i64::try_from(value).unwrap())
+                    .unwrap();
+            }
+            let data = writer.into_inner().unwrap();
+            let mut reader = LEBitReader::new(&data[..]);
+            assert!(reader.read_bit().unwrap());
+            for value in VALUES {
+                assert_eq!(reader.read_rice(k).unwrap(), value);
+                let signed = reader.read_rice_signed(k).unwrap();
+                assert_eq!(signed, -i64::try_from(value).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn rice_extremes() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_rice(60, u64::MAX).unwrap();
+        writer.write_rice_signed(62, i64::MIN).unwrap();
+        writer.write_rice_signed(62, i64::MAX).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        assert_eq!(reader.read_rice(60).unwrap(), u64::MAX);
+        assert_eq!(reader.read_rice_signed(62).unwrap(), i64::MIN);
+        assert_eq!(reader.read_rice_signed(62).unwrap(), i64::MAX);
+
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_unary(16, true).unwrap();
+        writer.write_zeros(60).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        let err = reader.read_rice(60).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn exp_golomb_roundtrip() {
+        let unsigned = [0, 1, 2, 3, 254, 255, 256, 1 << 32, u64::MAX - 1, u64::MAX];
+        let signed = [0, 1, -1, 1000, -1000, i64::MAX, i64::MIN];
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bits(0x3, 2).unwrap();
+        for value in unsigned {
+            writer.write_ue(value).unwrap();
+        }
+        for value in signed {
+            writer.write_se(value).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert_eq!(reader.read_bits(2).unwrap(), 0x3);
+        for value in unsigned {
+            assert_eq!(reader.read_ue().unwrap(), value);
+        }
+        for value in signed {
+            assert_eq!(reader.read_se().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn exp_golomb_too_long() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_zeros(65).unwrap();
+        writer.write_ones(66).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        let err = reader.read_ue().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_zeros(64).unwrap();
+        writer.write_ones(65).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        let err = reader.read_ue().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn elias_roundtrip() {
+        let values = [1, 2, 3, 4, 5, 17, 1000, 1 << 40, u64::MAX];
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bit(true).unwrap();
+        for value in values {
+            writer.write_elias_gamma(value).unwrap();
+            writer.write_elias_delta(value).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert!(reader.read_bit().unwrap());
+        for value in values {
+            assert_eq!(reader.read_elias_gamma().unwrap(), value);
+            assert_eq!(reader.read_elias_delta().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn elias_invalid() {
+        let mut writer = BEBitWriter::new(vec![]);
+        let err = writer.write_elias_gamma(0).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let err = writer.write_elias_delta(0).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        writer.write_elias_gamma(65).unwrap();
+        writer.write_zeros(64).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        let err = reader.read_elias_delta().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn unary_unterminated() {
+        let mut reader = LEBitReader::new(&b"\xff\xff"[..]);
+        reader.read_bits(3).unwrap();
+        let err = reader.read_unary(false).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+}
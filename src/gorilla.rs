@@ -0,0 +1,362 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// Classes of delta of delta timestamps after the zero class: number of prefix ones and width of the value.
+const DOD_CLASSES: [(u8, u8); 4] = [(1, 7), (2, 9), (3, 12), (4, 32)];
+/// Value following the `1111` prefix that marks the end of the stream. Delta of deltas that fit in a smaller class never use the 32 bit class, so this can't be a timestamp.
+const END_MARKER: u64 = 0xffff_ffff;
+
+/// Compresses time series of timestamps and floating point values with the encoding of Facebook's Gorilla database.
+///
+/// The first point is written as a 64 bit timestamp and a 64 bit value. Each following timestamp is written as the difference of its delta to the previous delta, in 1 to 36 bits, and each following value as its XOR with the previous value, omitting leading and trailing zeros. Slowly changing series compress to a few bits per point.
+///
+/// Delta of deltas must fit in 32 bits. `finish` writes an end marker, which `GorillaDecoder` stops at.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, BEBitWriter, GorillaDecoder, GorillaEncoder};
+///
+/// let mut encoder = GorillaEncoder::new(BEBitWriter::new(vec![]));
+/// for i in 0..100 {
+///     encoder.push(1_700_000_000 + i * 60, 21.5).unwrap();
+/// }
+/// let data = encoder.finish().unwrap().into_inner().unwrap();
+/// // 16 bytes for the first point, then 2 bits per point
+/// assert_eq!(data.len(), 47);
+///
+/// let mut decoder = GorillaDecoder::new(BEBitReader::new(&data[..]));
+/// let mut count = 0;
+/// while let Some((timestamp, value)) = decoder.read_point().unwrap() {
+///     assert_eq!(timestamp, 1_700_000_000 + count * 60);
+///     assert_eq!(value, 21.5);
+///     count += 1;
+/// }
+/// assert_eq!(count, 100);
+/// ```
+pub struct GorillaEncoder<E: BitEndianness, W: Write> {
+    inner: BitWriter<E, W>,
+    state: Option<State>,
+}
+
+/// State shared by the encoder and decoder after the first point.
+struct State {
+    timestamp: i64,
+    delta: i64,
+    value: u64,
+    /// Leading and trailing zeros of the last written XOR window.
+    window: Option<(u32, u32)>,
+}
+
+impl<E: BitEndianness, W: Write> GorillaEncoder<E, W> {
+    /// Creates a new `GorillaEncoder` writing to `inner`.
+    #[inline]
+    pub fn new(inner: BitWriter<E, W>) -> Self {
+        Self { inner, state: None }
+    }
+
+    /// Gets a reference to the underlying `BitWriter`.
+    #[inline]
+    pub fn get_ref(&self) -> &BitWriter<E, W> {
+        &self.inner
+    }
+
+    /// Compresses and writes a point.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if the difference between the delta to the previous timestamp and the previous delta doesn't fit in 32 bits.
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn push(&mut self, timestamp: i64, value: f64) -> Res<()> {
+        let value = value.to_bits();
+        let Some(state) = &mut self.state else {
+            #[allow(clippy::cast_sign_loss)]
+            self.inner.write_bits_wide(timestamp as u64, 64)?;
+            self.inner.write_bits_wide(value, 64)?;
+            self.state = Some(State {
+                timestamp,
+                delta: 0,
+                value,
+                window: None,
+            });
+            return Ok(());
+        };
+        let delta = timestamp.wrapping_sub(state.timestamp);
+        let dod = delta.wrapping_sub(state.delta);
+        if dod == 0 {
+            self.inner.write_bit(false)?;
+        } else {
+            let Some(&(ones, width)) = DOD_CLASSES.iter().find(|&&(_, width)| fits(dod, width))
+            else {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "delta of delta {dod} of timestamp {timestamp} does not fit in 32 bits"
+                    ),
+                ));
+            };
+            for _ in 0..ones {
+                self.inner.write_bit(true)?;
+            }
+            if ones < 4 {
+                self.inner.write_bit(false)?;
+            }
+            #[allow(clippy::cast_sign_loss)]
+            self.inner
+                .write_bits_wide(dod as u64 & mask(width), width)?;
+        }
+        let xor = value ^ state.value;
+        if xor == 0 {
+            self.inner.write_bit(false)?;
+        } else {
+            self.inner.write_bit(true)?;
+            let leading = std::cmp::min(xor.leading_zeros(), 31);
+            let trailing = xor.trailing_zeros();
+            match state.window {
+                Some((prev_leading, prev_trailing))
+                    if leading >= prev_leading && trailing >= prev_trailing =>
+                {
+                    self.inner.write_bit(false)?;
+                    write_window(&mut self.inner, xor, prev_leading, prev_trailing)?;
+                }
+                _ => {
+                    self.inner.write_bit(true)?;
+                    let len = 64 - leading - trailing;
+                    self.inner.write_bits_wide(u64::from(leading), 5)?;
+                    self.inner.write_bits_wide(u64::from(len % 64), 6)?;
+                    write_window(&mut self.inner, xor, leading, trailing)?;
+                    state.window = Some((leading, trailing));
+                }
+            }
+        }
+        state.timestamp = timestamp;
+        state.delta = delta;
+        state.value = value;
+        Ok(())
+    }
+
+    /// Writes the end marker if any points were written, and returns the underlying `BitWriter`.
+    pub fn finish(mut self) -> Res<BitWriter<E, W>> {
+        if self.state.is_some() {
+            for _ in 0..4 {
+                self.inner.write_bit(true)?;
+            }
+            self.inner.write_bits_wide(END_MARKER, 32)?;
+        }
+        Ok(self.inner)
+    }
+}
+
+/// Returns whether `dod` is within the range of a class of `width` bits, which is asymmetric like in the Gorilla paper: -63 to 64 for 7 bits.
+fn fits(dod: i64, width: u8) -> bool {
+    let half = 1i64 << (width - 1);
+    dod > -half && dod <= half
+}
+
+fn mask(width: u8) -> u64 {
+    if width == 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+fn write_window<E: BitEndianness, W: Write>(
+    writer: &mut BitWriter<E, W>,
+    xor: u64,
+    leading: u32,
+    trailing: u32,
+) -> Res<()> {
+    #[allow(clippy::cast_possible_truncation)]
+    let len = (64 - leading - trailing) as u8;
+    writer.write_bits_wide(xor >> trailing, len)
+}
+
+/// Decompresses time series written by [`GorillaEncoder`].
+///
+/// See `GorillaEncoder` for an example.
+pub struct GorillaDecoder<E: BitEndianness, R: Read> {
+    inner: BitReader<E, R>,
+    state: Option<State>,
+    done: bool,
+}
+
+impl<E: BitEndianness, R: Read> GorillaDecoder<E, R> {
+    /// Creates a new `GorillaDecoder` reading from `inner`.
+    #[inline]
+    pub fn new(inner: BitReader<E, R>) -> Self {
+        Self {
+            inner,
+            state: None,
+            done: false,
+        }
+    }
+
+    /// Gets a reference to the underlying `BitReader`.
+    #[inline]
+    pub fn get_ref(&self) -> &BitReader<E, R> {
+        &self.inner
+    }
+
+    /// Unwraps this `GorillaDecoder`, returning the underlying `BitReader`.
+    #[inline]
+    pub fn into_inner(self) -> BitReader<E, R> {
+        self.inner
+    }
+
+    /// Reads the next point, or returns `None` at the end marker or if the stream is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`UnexpectedEof`] if the stream ends before the end marker.
+    ///
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn read_point(&mut self) -> Res<Option<(i64, f64)>> {
+        if self.done {
+            return Ok(None);
+        }
+        let Some(state) = &mut self.state else {
+            let Some(first) = self.inner.read_bits_opt(8)? else {
+                self.done = true;
+                return Ok(None);
+            };
+            let rest = self.inner.read_bits_wide(56)?;
+            let timestamp = if E::LSB_FIRST {
+                u64::from(first) | rest << 8
+            } else {
+                u64::from(first) << 56 | rest
+            };
+            #[allow(clippy::cast_possible_wrap)]
+            let timestamp = timestamp as i64;
+            let value = self.inner.read_bits_wide(64)?;
+            self.state = Some(State {
+                timestamp,
+                delta: 0,
+                value,
+                window: None,
+            });
+            return Ok(Some((timestamp, f64::from_bits(value))));
+        };
+        let mut ones = 0;
+        while ones < 4 && self.inner.read_bit()? {
+            ones += 1;
+        }
+        let dod = if ones == 0 {
+            0
+        } else {
+            let width = DOD_CLASSES[ones - 1].1;
+            let bits = self.inner.read_bits_wide(width)?;
+            if ones == 4 && bits == END_MARKER {
+                self.done = true;
+                return Ok(None);
+            }
+            #[allow(clippy::cast_possible_wrap)]
+            if bits > 1 << (width - 1) {
+                (bits as i64) - (1 << width)
+            } else {
+                bits as i64
+            }
+        };
+        if self.inner.read_bit()? {
+            let (leading, trailing) = if self.inner.read_bit()? {
+                #[allow(clippy::cast_possible_truncation)]
+                let leading = self.inner.read_bits_wide(5)? as u32;
+                #[allow(clippy::cast_possible_truncation)]
+                let len = self.inner.read_bits_wide(6)? as u32;
+                let len = if len == 0 { 64 } else { len };
+                if leading + len > 64 {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "XOR window of {leading} leading zeros and {len} bits exceeds 64 bits"
+                        ),
+                    ));
+                }
+                let window = (leading, 64 - leading - len);
+                state.window = Some(window);
+                window
+            } else {
+                state.window.ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        "XOR window reused before being defined",
+                    )
+                })?
+            };
+            #[allow(clippy::cast_possible_truncation)]
+            let len = (64 - leading - trailing) as u8;
+            state.value ^= self.inner.read_bits_wide(len)? << trailing;
+        }
+        state.delta = state.delta.wrapping_add(dod);
+        state.timestamp = state.timestamp.wrapping_add(state.delta);
+        Ok(Some((state.timestamp, f64::from_bits(state.value))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use crate::{
+        BEBitReader, BEBitWriter, GorillaDecoder, GorillaEncoder, LEBitReader, LEBitWriter,
+    };
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn round_trip() {
+        let mut points = vec![(-5, 0.0), (-5, -0.0), (0, f64::NAN), (-100, f64::INFINITY)];
+        let mut timestamp = -100i64;
+        for i in 0..500 {
+            timestamp += [1, 60, 61, 100, 300, 3000, 100_000, 1][i % 8];
+            points.push((timestamp, (i as f64 * 0.37).sin() * 1e3));
+        }
+
+        let mut encoder = GorillaEncoder::new(LEBitWriter::new(vec![]));
+        for &(timestamp, value) in &points {
+            encoder.push(timestamp, value).unwrap();
+        }
+        let data = encoder.finish().unwrap().into_inner().unwrap();
+        let mut decoder = GorillaDecoder::new(LEBitReader::new(&data[..]));
+        for &(timestamp, value) in &points {
+            let (t, v) = decoder.read_point().unwrap().unwrap();
+            assert_eq!(t, timestamp);
+            assert_eq!(v.to_bits(), value.to_bits());
+        }
+        assert_eq!(decoder.read_point().unwrap(), None);
+        assert_eq!(decoder.read_point().unwrap(), None);
+    }
+
+    #[test]
+    fn empty() {
+        let encoder = GorillaEncoder::new(BEBitWriter::new(vec![]));
+        let data = encoder.finish().unwrap().into_inner().unwrap();
+        assert!(data.is_empty());
+        let mut decoder = GorillaDecoder::new(BEBitReader::new(&data[..]));
+        assert_eq!(decoder.read_point().unwrap(), None);
+    }
+
+    #[test]
+    fn dod_too_large() {
+        let mut encoder = GorillaEncoder::new(BEBitWriter::new(vec![]));
+        encoder.push(0, 1.0).unwrap();
+        encoder.push(1 << 31, 1.0).unwrap();
+        let err = encoder.push(1 << 40, 1.0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut encoder = GorillaEncoder::new(BEBitWriter::new(vec![]));
+        encoder.push(0, 1.0).unwrap();
+        encoder.push(10, 2.0).unwrap();
+        let data = encoder.finish().unwrap().into_inner().unwrap();
+        let mut decoder = GorillaDecoder::new(BEBitReader::new(&data[..17]));
+        assert!(decoder.read_point().unwrap().is_some());
+        let err = decoder.read_point().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,749 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// Maximum code length of a canonical Huffman code.
+const MAX_CODE_LEN: u8 = 15;
+/// Maximum code length of the code length code in DEFLATE.
+const MAX_CL_CODE_LEN: u8 = 7;
+/// Order in which the code length code lengths are stored in DEFLATE.
+const CL_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn invalid_data(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// A canonical Huffman code built from code lengths, with codes assigned in order of length, then symbol.
+#[derive(Debug, Clone)]
+pub(crate) struct Canonical {
+    /// Number of codes of each length.
+    counts: [u16; MAX_CODE_LEN as usize + 1],
+    /// Symbols ordered by code.
+    symbols: Vec<u16>,
+    /// Code of each symbol, valid where the length is nonzero.
+    codes: Vec<u16>,
+    lengths: Vec<u8>,
+}
+
+impl Canonical {
+    /// Builds the code, failing if the lengths are over-subscribed.
+    ///
+    /// Incomplete codes are accepted, reading an unassigned code fails when decoding.
+    pub(crate) fn new(lengths: &[u8]) -> Res<Self> {
+        // symbols are stored as u16
+        if lengths.len() > 1 << 16 {
+            return Err(invalid_data("too many symbols"));
+        }
+        let mut wide_counts = [0u32; MAX_CODE_LEN as usize + 1];
+        for &len in lengths {
+            if len > MAX_CODE_LEN {
+                return Err(invalid_data("code length is larger than 15"));
+            }
+            wide_counts[usize::from(len)] += 1;
+        }
+        wide_counts[0] = 0;
+        let mut left = 1i64;
+        for &count in &wide_counts[1..] {
+            left = (left << 1) - i64::from(count);
+            if left < 0 {
+                return Err(invalid_data("code lengths are over-subscribed"));
+            }
+        }
+        // at most 2^len codes of each length are left after the check above
+        #[allow(clippy::cast_possible_truncation)]
+        let counts = wide_counts.map(|count| count as u16);
+        let mut next = [0u16; MAX_CODE_LEN as usize + 2];
+        let mut offsets = [0u16; MAX_CODE_LEN as usize + 2];
+        for len in 1..=usize::from(MAX_CODE_LEN) {
+            next[len + 1] = (next[len] + counts[len]) << 1;
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; usize::from(offsets[usize::from(MAX_CODE_LEN) + 1])];
+        let mut codes = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                let len = usize::from(len);
+                #[allow(clippy::cast_possible_truncation)]
+                {
+                    symbols[usize::from(offsets[len])] = symbol as u16;
+                }
+                offsets[len] += 1;
+                codes[symbol] = next[len];
+                next[len] += 1;
+            }
+        }
+        Ok(Self {
+            counts,
+            symbols,
+            codes,
+            lengths: lengths.to_vec(),
+        })
+    }
+
+    /// Decodes one symbol, reading the code bit by bit, most significant bit first.
+    pub(crate) fn decode<E: BitEndianness, R: Read>(
+        &self,
+        reader: &mut BitReader<E, R>,
+    ) -> Res<u16> {
+        let mut code = 0i32;
+        let mut first = 0i32;
+        let mut index = 0i32;
+        for &count in &self.counts[1..] {
+            code |= i32::from(reader.read_bit()?);
+            let count = i32::from(count);
+            if code - first < count {
+                #[allow(clippy::cast_sign_loss)]
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid_data("invalid Huffman code"))
+    }
+
+    /// Encodes one symbol, writing the code bit by bit, most significant bit first.
+    pub(crate) fn encode<E: BitEndianness, W: Write>(
+        &self,
+        writer: &mut BitWriter<E, W>,
+        symbol: u16,
+    ) -> Res<()> {
+        let len = self.lengths.get(usize::from(symbol)).copied().unwrap_or(0);
+        if len == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("symbol {symbol} has no code"),
+            ));
+        }
+        let code = self.codes[usize::from(symbol)];
+        for i in (0..len).rev() {
+            writer.write_bit(code >> i & 1 != 0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of bits decoded at once with the root lookup table of a `HuffmanTable`.
+const LOOKUP_BITS: u8 = 8;
+
+/// An entry of the lookup tables of a `HuffmanTable`.
+#[derive(Debug, Clone, Copy)]
+enum Lookup {
+    /// No code starts with these bits.
+    Invalid,
+    /// A symbol and the full length of its code.
+    Symbol(u16, u8),
+    /// The code is longer, and is continued in the subtable at `start`, indexed by the next `bits` bits.
+    Subtable { start: usize, bits: u8 },
+}
+
+/// A canonical Huffman code for decoding symbols with `BitReader::read_huffman`.
+///
+/// Codes are assigned from the code lengths of the symbols as in DEFLATE (RFC 1951, section 3.2.2) and JPEG: shorter codes come first, and codes of the same length are in order of symbol. A length of zero means the symbol has no code.
+///
+/// Codes are read and written starting with their most significant bit, so a little endian reader decodes DEFLATE streams and a big endian reader decodes JPEG streams.
+#[derive(Debug, Clone)]
+pub struct HuffmanTable {
+    code: Canonical,
+    /// The root table, with an entry for each value of the next `LOOKUP_BITS` bits, most significant bit first, followed by the subtables for longer codes.
+    lookup: Vec<Lookup>,
+}
+
+impl HuffmanTable {
+    /// Builds the table from the code length of each symbol.
+    ///
+    /// Incomplete codes are accepted, reading a code that isn't assigned to a symbol fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if there are more than 65536 symbols, if a length is larger than 15 or if there are too many codes for their lengths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::HuffmanTable;
+    ///
+    /// assert!(HuffmanTable::from_lengths(&[2, 1, 3, 3]).is_ok());
+    /// assert!(HuffmanTable::from_lengths(&[1, 1, 1]).is_err());
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn from_lengths(lengths: &[u8]) -> Res<Self> {
+        let code = Canonical::new(lengths)?;
+        let mut lookup = vec![Lookup::Invalid; 1 << LOOKUP_BITS];
+        // a subtable for each prefix of longer codes, as wide as the longest code with it
+        let mut sub_bits = [0u8; 1 << LOOKUP_BITS];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len > LOOKUP_BITS {
+                let prefix = usize::from(code.codes[symbol] >> (len - LOOKUP_BITS));
+                sub_bits[prefix] = sub_bits[prefix].max(len - LOOKUP_BITS);
+            }
+        }
+        for (prefix, &bits) in sub_bits.iter().enumerate() {
+            if bits != 0 {
+                lookup[prefix] = Lookup::Subtable {
+                    start: lookup.len(),
+                    bits,
+                };
+                lookup.extend(std::iter::repeat_n(Lookup::Invalid, 1 << bits));
+            }
+        }
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len == 0 {
+                continue;
+            }
+            let code_bits = usize::from(code.codes[symbol]);
+            let (base, free) = if len <= LOOKUP_BITS {
+                let free = LOOKUP_BITS - len;
+                (code_bits << free, free)
+            } else {
+                let rest_len = len - LOOKUP_BITS;
+                let Lookup::Subtable { start, bits } = lookup[code_bits >> rest_len] else {
+                    unreachable!()
+                };
+                let free = bits - rest_len;
+                (start + ((code_bits & ((1 << rest_len) - 1)) << free), free)
+            };
+            #[allow(clippy::cast_possible_truncation)]
+            lookup[base..base + (1 << free)].fill(Lookup::Symbol(symbol as u16, len));
+        }
+        Ok(Self { code, lookup })
+    }
+
+    /// Returns the code length of each symbol.
+    #[must_use]
+    pub fn lengths(&self) -> &[u8] {
+        &self.code.lengths
+    }
+}
+
+/// Computes Huffman code lengths for the given symbol frequencies, limited to `max_len` bits.
+///
+/// If the optimal code is too long, the frequencies are flattened until it fits. A single used symbol gets a 1-bit code.
+pub(crate) fn code_lengths(freqs: &[u32], max_len: u8) -> Vec<u8> {
+    let mut freqs = freqs.to_vec();
+    loop {
+        let lengths = unlimited_code_lengths(&freqs);
+        if lengths.iter().all(|&len| len <= max_len) {
+            return lengths;
+        }
+        for freq in &mut freqs {
+            if *freq != 0 {
+                *freq = (*freq >> 1) | 1;
+            }
+        }
+    }
+}
+
+fn unlimited_code_lengths(freqs: &[u32]) -> Vec<u8> {
+    let mut lengths = vec![0u8; freqs.len()];
+    // each node is its weight and the symbols below it
+    let mut nodes: Vec<(u64, Vec<usize>)> = freqs
+        .iter()
+        .enumerate()
+        .filter(|&(_, &freq)| freq != 0)
+        .map(|(symbol, &freq)| (u64::from(freq), vec![symbol]))
+        .collect();
+    if nodes.len() == 1 {
+        lengths[nodes[0].1[0]] = 1;
+    }
+    while nodes.len() > 1 {
+        nodes.sort_by_key(|node| std::cmp::Reverse(node.0));
+        let (weight_a, symbols_a) = nodes.pop().unwrap_or_default();
+        let (weight_b, mut symbols_b) = nodes.pop().unwrap_or_default();
+        for &symbol in symbols_a.iter().chain(&symbols_b) {
+            lengths[symbol] += 1;
+        }
+        symbols_b.extend(symbols_a);
+        nodes.push((weight_a + weight_b, symbols_b));
+    }
+    lengths
+}
+
+/// How a sequence of Huffman code lengths is stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeLengthFormat {
+    /// Every code length as a 4-bit field.
+    Raw,
+    /// Run-length encoded and Huffman coded as in the dynamic block header of DEFLATE (RFC 1951, section 3.2.7), starting at the HCLEN field.
+    ///
+    /// The `HLIT` and `HDIST` fields are not included, write the counts before the code lengths, and pass the literal/length and distance code lengths as one sequence, as DEFLATE does. The output is DEFLATE-compatible when using a little endian `BitWriter`.
+    Deflate,
+}
+
+impl<E: BitEndianness, R: Read> BitReader<E, R> {
+    /// Reads `count` Huffman code lengths written with `BitWriter::write_code_lengths`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the encoding is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{CodeLengthFormat, LEBitReader};
+    ///
+    /// let mut reader = LEBitReader::new(&b"\x21\x03"[..]);
+    /// let lengths = reader.read_code_lengths(3, CodeLengthFormat::Raw).unwrap();
+    /// assert_eq!(lengths, [1, 2, 3]);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_code_lengths(&mut self, count: usize, format: CodeLengthFormat) -> Res<Vec<u8>> {
+        match format {
+            CodeLengthFormat::Raw => (0..count).map(|_| self.read_bits(4)).collect(),
+            CodeLengthFormat::Deflate => self.read_code_lengths_deflate(count),
+        }
+    }
+
+    /// Reads one symbol coded with the given Huffman table.
+    ///
+    /// Codes are looked up from the next 8 bits at once, and codes longer than 8 bits continue in a second table indexed by the bits after them. Only near the end of the stream or scope, where fewer bits are left than a table is indexed by, the code is read one bit at a time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the bits don't form a code of the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{BEBitReader, HuffmanTable};
+    ///
+    /// // codes 10, 0, 110, 111
+    /// let table = HuffmanTable::from_lengths(&[2, 1, 3, 3]).unwrap();
+    /// let mut reader = BEBitReader::new(&b"\x5c"[..]);
+    /// assert_eq!(reader.read_huffman(&table).unwrap(), 1);
+    /// assert_eq!(reader.read_huffman(&table).unwrap(), 0);
+    /// assert_eq!(reader.read_huffman(&table).unwrap(), 3);
+    /// assert_eq!(reader.read_huffman(&table).unwrap(), 1);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_huffman(&mut self, table: &HuffmanTable) -> Res<u16> {
+        let (mut start, mut bits, mut consumed) = (0, LOOKUP_BITS, 0);
+        loop {
+            let Ok(index) = self.peek_bits(bits) else {
+                // the rest of the stream may still hold a shorter code
+                return self.read_huffman_bitwise(table, start, bits, consumed);
+            };
+            let index = if E::LSB_FIRST {
+                index.reverse_bits() >> (8 - bits)
+            } else {
+                index
+            };
+            match table.lookup[start + usize::from(index)] {
+                Lookup::Symbol(symbol, len) => {
+                    self.read_bits(len - consumed)?;
+                    return Ok(symbol);
+                }
+                Lookup::Subtable {
+                    start: sub_start,
+                    bits: sub_bits,
+                } => {
+                    self.read_bits(bits)?;
+                    consumed += bits;
+                    (start, bits) = (sub_start, sub_bits);
+                }
+                Lookup::Invalid => return Err(invalid_data("invalid Huffman code")),
+            }
+        }
+    }
+
+    /// Continues `read_huffman` one bit at a time from the table at `start`, after `consumed` bits of the code.
+    fn read_huffman_bitwise(
+        &mut self,
+        table: &HuffmanTable,
+        mut start: usize,
+        mut bits: u8,
+        mut consumed: u8,
+    ) -> Res<u16> {
+        let (mut index, mut read) = (0, 0);
+        loop {
+            index = index << 1 | usize::from(self.read_bit()?);
+            read += 1;
+            // the entries of a code cover all values of the bits after it
+            match table.lookup[start + (index << (bits - read))] {
+                Lookup::Symbol(symbol, len) if len - consumed == read => return Ok(symbol),
+                Lookup::Subtable {
+                    start: sub_start,
+                    bits: sub_bits,
+                } if read == bits => {
+                    consumed += bits;
+                    (start, bits) = (sub_start, sub_bits);
+                    (index, read) = (0, 0);
+                }
+                _ if read == bits => return Err(invalid_data("invalid Huffman code")),
+                _ => {}
+            }
+        }
+    }
+
+    fn read_code_lengths_deflate(&mut self, count: usize) -> Res<Vec<u8>> {
+        let hclen = usize::from(self.read_bits(4)?) + 4;
+        let mut cl_lengths = [0; 19];
+        for &symbol in &CL_ORDER[..hclen] {
+            cl_lengths[symbol] = self.read_bits(3)?;
+        }
+        let cl_code = Canonical::new(&cl_lengths)?;
+        let mut lengths = Vec::with_capacity(count);
+        while lengths.len() < count {
+            let symbol = cl_code.decode(self)?;
+            let (len, repeat) = match symbol {
+                0..=15 => (u8::try_from(symbol).unwrap_or_default(), 1),
+                16 => {
+                    let prev = *lengths
+                        .last()
+                        .ok_or_else(|| invalid_data("repeat code without previous length"))?;
+                    (prev, 3 + self.read_bits(2)?)
+                }
+                17 => (0, 3 + self.read_bits(3)?),
+                _ => (0, 11 + self.read_bits(7)?),
+            };
+            if lengths.len() + usize::from(repeat) > count {
+                return Err(invalid_data(
+                    "code length repeat exceeds the number of lengths",
+                ));
+            }
+            lengths.extend(std::iter::repeat_n(len, usize::from(repeat)));
+        }
+        Ok(lengths)
+    }
+}
+
+/// Run-length encodes code lengths with the DEFLATE code length alphabet, as pairs of symbol and extra bits.
+fn deflate_runs(lengths: &[u8]) -> Vec<(u16, u8)> {
+    let mut runs = vec![];
+    let mut i = 0;
+    while i < lengths.len() {
+        let len = lengths[i];
+        let mut run = lengths[i..].iter().take_while(|&&l| l == len).count();
+        i += run;
+        if len == 0 {
+            while run >= 11 {
+                let n = std::cmp::min(run, 138);
+                #[allow(clippy::cast_possible_truncation)]
+                runs.push((18, (n - 11) as u8));
+                run -= n;
+            }
+            if run >= 3 {
+                #[allow(clippy::cast_possible_truncation)]
+                runs.push((17, (run - 3) as u8));
+                run = 0;
+            }
+        } else {
+            runs.push((u16::from(len), 0));
+            run -= 1;
+            while run >= 3 {
+                let n = std::cmp::min(run, 6);
+                #[allow(clippy::cast_possible_truncation)]
+                runs.push((16, (n - 3) as u8));
+                run -= n;
+            }
+        }
+        runs.extend(std::iter::repeat_n((u16::from(len), 0), run));
+    }
+    runs
+}
+
+impl<E: BitEndianness, W: Write> BitWriter<E, W> {
+    /// Writes Huffman code lengths in the given format.
+    ///
+    /// The number of lengths is not written, it has to be known when reading them back with `BitReader::read_code_lengths`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if a length is larger than 15.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{CodeLengthFormat, LEBitWriter};
+    ///
+    /// let mut writer = LEBitWriter::new(vec![]);
+    /// writer.write_code_lengths(&[1, 2, 3], CodeLengthFormat::Raw).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x21\x03");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_code_lengths(&mut self, lengths: &[u8], format: CodeLengthFormat) -> Res<()> {
+        if lengths.iter().any(|&len| len > MAX_CODE_LEN) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "code length is larger than 15",
+            ));
+        }
+        match format {
+            CodeLengthFormat::Raw => {
+                for &len in lengths {
+                    self.write_bits(len, 4)?;
+                }
+                Ok(())
+            }
+            CodeLengthFormat::Deflate => self.write_code_lengths_deflate(lengths),
+        }
+    }
+
+    /// Writes the code of a symbol of the given Huffman table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if the symbol has no code in the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{BEBitWriter, HuffmanTable};
+    ///
+    /// let table = HuffmanTable::from_lengths(&[2, 1, 3, 3]).unwrap();
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// for symbol in [1, 0, 3, 1] {
+    ///     writer.write_huffman(&table, symbol).unwrap();
+    /// }
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x5c");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_huffman(&mut self, table: &HuffmanTable, symbol: u16) -> Res<()> {
+        table.code.encode(self, symbol)
+    }
+
+    fn write_code_lengths_deflate(&mut self, lengths: &[u8]) -> Res<()> {
+        let runs = deflate_runs(lengths);
+        let mut freqs = [0u32; 19];
+        for &(symbol, _) in &runs {
+            freqs[usize::from(symbol)] += 1;
+        }
+        let cl_lengths = code_lengths(&freqs, MAX_CL_CODE_LEN);
+        let cl_code = Canonical::new(&cl_lengths)?;
+        let hclen = CL_ORDER
+            .iter()
+            .rposition(|&symbol| cl_lengths[symbol] != 0)
+            .map_or(4, |last| std::cmp::max(last + 1, 4));
+        #[allow(clippy::cast_possible_truncation)]
+        self.write_bits((hclen - 4) as u8, 4)?;
+        for &symbol in &CL_ORDER[..hclen] {
+            self.write_bits(cl_lengths[symbol], 3)?;
+        }
+        for (symbol, extra) in runs {
+            cl_code.encode(self, symbol)?;
+            match symbol {
+                16 => self.write_bits(extra, 2)?,
+                17 => self.write_bits(extra, 3)?,
+                18 => self.write_bits(extra, 7)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Canonical, HuffmanTable, code_lengths};
+    use crate::{BEBitReader, BEBitWriter, CodeLengthFormat, LEBitReader, LEBitWriter};
+
+    /// Code lengths of the fixed literal/length and distance codes of DEFLATE.
+    fn fixed_lengths() -> Vec<u8> {
+        let mut lengths = vec![8; 144];
+        lengths.extend([9; 112]);
+        lengths.extend([7; 24]);
+        lengths.extend([8; 8]);
+        lengths.extend([5; 30]);
+        lengths
+    }
+
+    #[test]
+    fn canonical_codes() {
+        // example from RFC 1951, section 3.2.2
+        let code = Canonical::new(&[3, 3, 3, 3, 3, 2, 4, 4]).unwrap();
+        assert_eq!(code.codes, [2, 3, 4, 5, 6, 0, 14, 15]);
+        let mut writer = BEBitWriter::new(vec![]);
+        for symbol in [5, 7, 0] {
+            code.encode(&mut writer, symbol).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        assert_eq!(data, b"\x3d\x00");
+        let mut reader = BEBitReader::new(&data[..]);
+        for symbol in [5, 7, 0] {
+            assert_eq!(code.decode(&mut reader).unwrap(), symbol);
+        }
+    }
+
+    #[test]
+    fn over_subscribed() {
+        assert!(Canonical::new(&[1, 1, 1]).is_err());
+    }
+
+    #[test]
+    fn many_symbols() {
+        use std::io::ErrorKind;
+
+        let err = HuffmanTable::from_lengths(&vec![15; 1 << 16]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(HuffmanTable::from_lengths(&vec![0; 1 << 16]).is_ok());
+        let err = HuffmanTable::from_lengths(&vec![0; 70000]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let mut lengths = vec![0; 1 << 16];
+        lengths[0xffff] = 1;
+        let table = HuffmanTable::from_lengths(&lengths).unwrap();
+        let mut reader = BEBitReader::new(&b"\x00"[..]);
+        assert_eq!(reader.read_huffman(&table).unwrap(), 0xffff);
+    }
+
+    #[test]
+    fn length_limit() {
+        let freqs: Vec<u32> = (0..19).map(|i| 1 << i).collect();
+        let lengths = code_lengths(&freqs, 7);
+        assert!(lengths.iter().all(|&len| (1..=7).contains(&len)));
+        assert!(Canonical::new(&lengths).is_ok());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let tables = [
+            fixed_lengths(),
+            vec![],
+            vec![0; 300],
+            vec![4],
+            vec![1, 0, 0, 0, 1],
+            vec![3, 3, 3, 3, 3, 2, 4, 4],
+        ];
+        for format in [CodeLengthFormat::Raw, CodeLengthFormat::Deflate] {
+            let mut writer = LEBitWriter::new(vec![]);
+            for lengths in &tables {
+                writer.write_code_lengths(lengths, format).unwrap();
+            }
+            let data = writer.into_inner().unwrap();
+            let mut reader = LEBitReader::new(&data[..]);
+            for lengths in &tables {
+                assert_eq!(
+                    &reader.read_code_lengths(lengths.len(), format).unwrap(),
+                    lengths
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn huffman_table() {
+        let lengths = fixed_lengths();
+        let table = HuffmanTable::from_lengths(&lengths[..288]).unwrap();
+        let symbols: Vec<u16> = (0..288).chain([256, 0, 143, 287]).collect();
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bits(0x05, 3).unwrap();
+        for &symbol in &symbols {
+            writer.write_huffman(&table, symbol).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert_eq!(reader.read_bits(3).unwrap(), 0x05);
+        for &symbol in &symbols {
+            assert_eq!(reader.read_huffman(&table).unwrap(), symbol);
+        }
+        // incomplete code, 11 is unassigned
+        let table = HuffmanTable::from_lengths(&[1, 2]).unwrap();
+        let mut reader = BEBitReader::new(&b"\x56\x00\x00"[..]);
+        assert_eq!(reader.read_huffman(&table).unwrap(), 0);
+        assert_eq!(reader.read_huffman(&table).unwrap(), 1);
+        assert_eq!(reader.read_huffman(&table).unwrap(), 1);
+        assert_eq!(
+            reader.read_huffman(&table).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        let mut writer = BEBitWriter::new(vec![]);
+        assert_eq!(
+            writer.write_huffman(&table, 2).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn long_codes() {
+        use super::Lookup;
+
+        // one code of each length from 1 to 15 bits, and a second one of 15 bits
+        let mut lengths: Vec<u8> = (1..=15).collect();
+        lengths.push(15);
+        let table = HuffmanTable::from_lengths(&lengths).unwrap();
+        assert!(matches!(
+            table.lookup[0xff],
+            Lookup::Subtable { bits: 7, .. }
+        ));
+        let symbols: Vec<u16> = (0..16).chain([15, 9, 14, 0, 8]).rev().collect();
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bits(0x05, 3).unwrap();
+        for &symbol in &symbols {
+            writer.write_huffman(&table, symbol).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert_eq!(reader.read_bits(3).unwrap(), 0x05);
+        for &symbol in &symbols {
+            assert_eq!(reader.read_huffman(&table).unwrap(), symbol);
+        }
+        let mut writer = BEBitWriter::new(vec![]);
+        for &symbol in &symbols {
+            writer.write_huffman(&table, symbol).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        for &symbol in &symbols {
+            assert_eq!(reader.read_huffman(&table).unwrap(), symbol);
+        }
+        // a 10-bit code at the end of a scope, shorter than the subtable is indexed by
+        let mut reader = BEBitReader::new(&b"\xff\x80"[..]);
+        assert_eq!(
+            reader.read_scope(10, |r| r.read_huffman(&table)).unwrap(),
+            9
+        );
+        // incomplete code, 1111_1111_1 is unassigned
+        let table = HuffmanTable::from_lengths(&[1, 9]).unwrap();
+        let mut reader = BEBitReader::new(&b"\x80\x00\xff\x80"[..]);
+        assert_eq!(reader.read_huffman(&table).unwrap(), 1);
+        reader.read_bits(7).unwrap();
+        assert_eq!(
+            reader.read_huffman(&table).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn deflate_is_compact() {
+        let mut writer = LEBitWriter::new(vec![]);
+        writer
+            .write_code_lengths(&fixed_lengths(), CodeLengthFormat::Deflate)
+            .unwrap();
+        // 159 bytes in the raw form
+        assert!(writer.into_inner().unwrap().len() < 32);
+    }
+
+    #[test]
+    fn deflate_repeat_without_previous() {
+        // HCLEN = 0: lengths of symbols 16, 17, 18, 0 are 1, 0, 0, 1, then symbol 16 (code 0)
+        let mut reader = LEBitReader::new(&b"\x10\x02\x00"[..]);
+        let err = reader
+            .read_code_lengths(4, CodeLengthFormat::Deflate)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn deflate_dynamic_block_header() {
+        // start of a dynamic block produced by zlib
+        let data = b"\xe5\x8c\x57\x15\x80\x30\x0c\x45\xad\x3c\x05\x18\x40\x4d\x0b\xe9\x60\x34\xdd\x05\xd4\x93\x83\x0d\xbe\xef\xa8\x8e\x90\x9a\x5f\x76\xe8\xcc\x23\xc0\xf0\x85\xad\x9d\xb1\x80\x3b\x65\x54\xc1\x87\x7a";
+        let mut reader = LEBitReader::new(&data[..]);
+        assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+        let hlit = usize::from(reader.read_bits(5).unwrap()) + 257;
+        let hdist = usize::from(reader.read_bits(5).unwrap()) + 1;
+        let lengths = reader
+            .read_code_lengths(hlit + hdist, CodeLengthFormat::Deflate)
+            .unwrap();
+        assert_ne!(lengths[256], 0);
+        Canonical::new(&lengths[..hlit]).unwrap();
+        Canonical::new(&lengths[hlit..]).unwrap();
+    }
+}
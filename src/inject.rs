@@ -0,0 +1,187 @@
+use std::collections::BTreeSet;
+use std::io::Result as Res;
+use std::io::{Read, Write};
+
+use crate::endian::{BE, BitEndianness, LE};
+
+/// Injects errors into big endian bit streams.
+pub type BEErrorInjector<T> = ErrorInjector<BE, T>;
+/// Injects errors into little endian bit streams.
+pub type LEErrorInjector<T> = ErrorInjector<LE, T>;
+
+/// Flips bits of the data flowing through a reader or writer, for testing how decoders handle corrupted input.
+///
+/// Bits can be flipped at specific offsets, and at random with a given rate. Offsets count bits in stream order from the start of the data passing through the injector, so bit 0 is the first bit a `BitReader` with the same bit endianness would read. Random flips are drawn from a generator seeded at construction, so a test failure can be reproduced with the same seed. The offsets of all flipped bits are recorded.
+///
+/// Use it as the underlying reader of a `BitReader`, or as the underlying writer of a `BitWriter`. Writes always pass the whole buffer to the inner writer with `write_all`, so the same seed gives the same flips regardless of how the data is split.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, BEErrorInjector};
+///
+/// let mut injector = BEErrorInjector::new(&b"\x00\x00"[..], 42);
+/// injector.flip_at(0);
+/// injector.flip_at(9);
+/// let mut reader = BEBitReader::new(injector);
+/// assert_eq!(reader.read_bits(8).unwrap(), 0x80);
+/// assert_eq!(reader.read_bits(8).unwrap(), 0x40);
+/// assert_eq!(reader.into_inner().flipped(), [0, 9]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ErrorInjector<E: BitEndianness, T> {
+    inner: T,
+    /// State of the splitmix64 generator for random flips.
+    state: u64,
+    /// Random flips happen when the next random number is less than this.
+    threshold: u64,
+    offsets: BTreeSet<u64>,
+    flipped: Vec<u64>,
+    position: u64,
+    phantom: std::marker::PhantomData<E>,
+}
+
+impl<E: BitEndianness, T> ErrorInjector<E, T> {
+    /// Creates a new `ErrorInjector` which passes data through unchanged until flips are configured, with `seed` as the seed for random flips.
+    #[inline]
+    pub fn new(inner: T, seed: u64) -> Self {
+        Self {
+            inner,
+            state: seed,
+            threshold: 0,
+            offsets: BTreeSet::new(),
+            flipped: vec![],
+            position: 0,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets the probability of flipping each bit at random.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is not between 0 and 1.
+    pub fn set_rate(&mut self, rate: f64) {
+        assert!((0.0..=1.0).contains(&rate), "rate must be between 0 and 1");
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let threshold = (rate * 2f64.powi(64)) as u64;
+        self.threshold = threshold;
+    }
+
+    /// Flips the bit at `offset` when it passes through.
+    pub fn flip_at(&mut self, offset: u64) {
+        self.offsets.insert(offset);
+    }
+
+    /// Returns the offsets of the bits flipped so far, in increasing order.
+    #[inline]
+    pub fn flipped(&self) -> &[u64] {
+        &self.flipped
+    }
+
+    /// Returns the number of bits that have passed through.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Gets a reference to the underlying reader or writer.
+    #[inline]
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwraps this `ErrorInjector`, returning the underlying reader or writer.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns the next number of the splitmix64 generator.
+    fn next_random(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Flips bits of `buf`, which holds the next bytes of the stream.
+    fn corrupt(&mut self, buf: &mut [u8]) {
+        for byte in buf {
+            for bit in 0..8 {
+                let offset = self.position + u64::from(bit);
+                let random = self.threshold != 0 && self.next_random() < self.threshold;
+                if random || self.offsets.remove(&offset) {
+                    *byte ^= E::shift_lsb(E::shift_msb(0xff, 7), bit);
+                    self.flipped.push(offset);
+                }
+            }
+            self.position += 8;
+        }
+    }
+}
+
+/// Read bytes with bits flipped.
+impl<E: BitEndianness, R: Read> Read for ErrorInjector<E, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Res<usize> {
+        let count_read = self.inner.read(buf)?;
+        self.corrupt(&mut buf[..count_read]);
+        Ok(count_read)
+    }
+}
+
+/// Write bytes with bits flipped.
+impl<E: BitEndianness, W: Write> Write for ErrorInjector<E, W> {
+    fn write(&mut self, buf: &[u8]) -> Res<usize> {
+        let mut corrupted = buf.to_vec();
+        self.corrupt(&mut corrupted);
+        self.inner.write_all(&corrupted)?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Res<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use crate::{BEErrorInjector, LEBitWriter, LEErrorInjector};
+
+    fn corrupt(seed: u64, rate: f64) -> (Vec<u8>, Vec<u64>) {
+        let mut injector = BEErrorInjector::new(&[0u8; 1000][..], seed);
+        injector.set_rate(rate);
+        let mut data = vec![];
+        injector.read_to_end(&mut data).unwrap();
+        (data, injector.flipped().to_vec())
+    }
+
+    #[test]
+    fn random_flips() {
+        let (data, flipped) = corrupt(7, 0.01);
+        let ones: u32 = data.iter().map(|b| b.count_ones()).sum();
+        assert_eq!(ones as usize, flipped.len());
+        assert!((40..120).contains(&flipped.len()));
+        assert_eq!(corrupt(7, 0.01), (data, flipped));
+        assert_ne!(corrupt(8, 0.01).1, corrupt(7, 0.01).1);
+        assert!(corrupt(7, 0.0).1.is_empty());
+        assert_eq!(corrupt(7, 1.0).1.len(), 8000);
+    }
+
+    #[test]
+    fn writer() {
+        let mut injector = LEErrorInjector::new(vec![], 0);
+        injector.flip_at(3);
+        injector.flip_at(12);
+        let mut writer = LEBitWriter::new(injector);
+        writer.write_all(b"\x00\xff").unwrap();
+        let injector = writer.into_inner().unwrap();
+        assert_eq!(injector.position(), 16);
+        assert_eq!(injector.flipped(), [3, 12]);
+        assert_eq!(injector.into_inner(), b"\x08\xef");
+    }
+}
@@ -0,0 +1,164 @@
+use std::io::Read;
+use std::io::Result as Res;
+
+use crate::BitReader;
+use crate::endian::BitEndianness;
+
+/// An integer type that bits can be read into with `BitReader::read_bits_into`.
+///
+/// This is implemented for all integer types from `u8` to `u128` and `i8` to `i128`, so that generic code can read fields of any width without dispatching on the type. Values of signed types are read as two's complement numbers of the given number of bits and sign-extended.
+///
+/// You can't implement this trait, it only exists as a trait bound.
+pub trait BitIntTarget: Copy + Default + private::Sealed {
+    /// Maximum number of bits that can be read into the type.
+    const MAX_BITS: u8;
+
+    /// Appends `count` <= 8 bits to the `len` bits already assembled in `acc`.
+    ///
+    /// With big endian bit numbering, earlier bits are more significant, with little endian less significant.
+    fn push_bits<E: BitEndianness>(acc: Self, len: u8, bits: u8, count: u8) -> Self;
+
+    /// Converts the `count` bits assembled in `acc` to a value, sign-extending them for signed types.
+    fn from_assembled(acc: Self, count: u8) -> Self;
+}
+
+macro_rules! impl_bit_int_target {
+    ($($t:ty => $u:ty),*) => {$(
+        impl private::Sealed for $t {}
+
+        impl BitIntTarget for $t {
+            #[allow(clippy::cast_possible_truncation)]
+            const MAX_BITS: u8 = <$t>::BITS as u8;
+
+            #[inline]
+            #[allow(clippy::cast_sign_loss, clippy::cast_possible_wrap)]
+            fn push_bits<E: BitEndianness>(acc: Self, len: u8, bits: u8, count: u8) -> Self {
+                let (acc, bits) = (acc as $u, <$u>::from(bits));
+                let res = if E::LSB_FIRST {
+                    acc | bits << len
+                } else {
+                    // a full first chunk of u8 shifts out all bits
+                    acc.checked_shl(count.into()).unwrap_or(0) | bits
+                };
+                res as $t
+            }
+
+            #[inline]
+            fn from_assembled(acc: Self, count: u8) -> Self {
+                let shift = Self::MAX_BITS - count;
+                acc.checked_shl(shift.into()).map_or(0, |value| value >> shift)
+            }
+        }
+    )*};
+}
+
+impl_bit_int_target!(
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128
+);
+
+impl<E: BitEndianness, R: Read> BitReader<E, R> {
+    /// Reads `count` bits into an integer of type `T`.
+    ///
+    /// The bits are assembled like with `read_bits_u16` and friends: with big endian bit numbering, the first bit read is the most significant, with little endian it is the least significant. For signed types, the bits are a two's complement number, so the most significant of the `count` bits is the sign bit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is larger than the number of bits of `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12\x3f\xff\xe0"[..]);
+    /// assert_eq!(reader.read_bits_into::<u16>(12).unwrap(), 0x123);
+    /// assert_eq!(reader.read_bits_into::<i64>(15).unwrap(), -1);
+    /// assert_eq!(reader.read_bits_into::<i8>(5).unwrap(), 0);
+    /// ```
+    pub fn read_bits_into<T: BitIntTarget>(&mut self, count: u8) -> Res<T> {
+        assert!(
+            count <= T::MAX_BITS,
+            "count must not be larger than the number of bits of the type"
+        );
+        let mut acc = T::default();
+        let mut len = 0;
+        while len < count {
+            let chunk = std::cmp::min(count - len, 8);
+            acc = T::push_bits::<E>(acc, len, self.read_bits(chunk)?, chunk);
+            len += chunk;
+        }
+        Ok(T::from_assembled(acc, count))
+    }
+
+    /// Reads a two's complement signed integer of `count` bits, sign-extending it to 64 bits.
+    ///
+    /// This is the same as `read_bits_into::<i64>(count)`, use `read_bits_into` to read into other signed types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitReader;
+    /// let mut reader = LEBitReader::new(&b"\x3b\x01"[..]);
+    /// assert_eq!(reader.read_signed_bits(4).unwrap(), -5);
+    /// assert_eq!(reader.read_signed_bits(5).unwrap(), -13);
+    /// ```
+    pub fn read_signed_bits(&mut self, count: u8) -> Res<i64> {
+        self.read_bits_into(count)
+    }
+}
+
+// ensures no one else implements the trait
+mod private {
+    pub trait Sealed {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, BEBitWriter, LEBitReader, LEBitWriter};
+
+    #[test]
+    fn unsigned() {
+        let mut reader = BEBitReader::new(&b"\xab\xcd\xef\x01"[..]);
+        assert_eq!(reader.read_bits_into::<u8>(8).unwrap(), 0xab);
+        assert_eq!(reader.read_bits_into::<u128>(20).unwrap(), 0xcdef0);
+        assert_eq!(reader.read_bits_into::<u32>(0).unwrap(), 0);
+        let mut reader = LEBitReader::new(&b"\xab\xcd\xef\x01"[..]);
+        assert_eq!(reader.read_bits_into::<u8>(8).unwrap(), 0xab);
+        assert_eq!(reader.read_bits_into::<u128>(20).unwrap(), 0x1_efcd);
+    }
+
+    #[test]
+    fn signed() {
+        for value in [-1000i64, -1, 0, 1, 4095, -4096] {
+            let mut writer = BEBitWriter::new(vec![]);
+            writer.write_bit(true).unwrap();
+            #[allow(clippy::cast_sign_loss)]
+            writer.write_bits_u128(value as u128, 13).unwrap();
+            let data = writer.into_inner().unwrap();
+            let mut reader = BEBitReader::new(&data[..]);
+            reader.read_bit().unwrap();
+            assert_eq!(reader.read_bits_into::<i64>(13).unwrap(), value);
+        }
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bits_u128(u128::MAX - 1, 128).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert_eq!(reader.read_bits_into::<i128>(128).unwrap(), -2);
+
+        let mut reader = BEBitReader::new(&b"\x80\x00\x00\x00\x00\x00\x00\x00\x40"[..]);
+        assert_eq!(reader.read_signed_bits(64).unwrap(), i64::MIN);
+        assert_eq!(reader.read_signed_bits(1).unwrap(), 0);
+        assert_eq!(reader.read_signed_bits(1).unwrap(), -1);
+    }
+
+    #[test]
+    #[should_panic(expected = "number of bits of the type")]
+    fn too_many_bits() {
+        let mut reader = BEBitReader::new(&b"\x00\x00"[..]);
+        let _ = reader.read_bits_into::<i8>(9);
+    }
+}
@@ -0,0 +1,160 @@
+use std::io::Result as Res;
+use std::io::{Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// Interleaves and deinterleaves blocks of bits in a matrix of `rows` by `columns`.
+///
+/// Interleaving reads a block of `rows * columns` bits from the input row by row and writes it to the output column by column, so bits that are adjacent in the input end up `rows` bits apart. Deinterleaving does the opposite, restoring the original order. A burst of errors in the interleaved stream is spread over many rows after deinterleaving, where forward error correction can handle it.
+///
+/// Bits are taken in stream order, so the input and output may use different bit endianness.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, BEBitWriter, BlockInterleaver};
+///
+/// let interleaver = BlockInterleaver::new(2, 4);
+/// let mut reader = BEBitReader::new(&b"\xf0"[..]);
+/// let mut writer = BEBitWriter::new(vec![]);
+/// interleaver.interleave(&mut reader, &mut writer).unwrap();
+/// let data = writer.into_inner().unwrap();
+/// assert_eq!(data, b"\xaa");
+///
+/// let mut reader = BEBitReader::new(&data[..]);
+/// let mut writer = BEBitWriter::new(vec![]);
+/// interleaver.deinterleave(&mut reader, &mut writer).unwrap();
+/// assert_eq!(writer.into_inner().unwrap(), b"\xf0");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockInterleaver {
+    rows: usize,
+    columns: usize,
+}
+
+impl BlockInterleaver {
+    /// Creates an interleaver for blocks of `rows` by `columns` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows` or `columns` is zero.
+    #[must_use]
+    pub fn new(rows: usize, columns: usize) -> Self {
+        assert!(rows > 0 && columns > 0, "dimensions must be nonzero");
+        Self { rows, columns }
+    }
+
+    /// Returns the number of rows.
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    #[must_use]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of bits in a block.
+    #[must_use]
+    pub fn block_bits(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// Reads a block in row order from `reader` and writes it in column order to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`UnexpectedEof`] if `reader` ends before a whole block is read. Nothing is written in that case.
+    ///
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn interleave<E1: BitEndianness, R: Read, E2: BitEndianness, W: Write>(
+        &self,
+        reader: &mut BitReader<E1, R>,
+        writer: &mut BitWriter<E2, W>,
+    ) -> Res<()> {
+        let block = read_block(reader, self.block_bits())?;
+        for column in 0..self.columns {
+            for row in 0..self.rows {
+                writer.write_bit(block[row * self.columns + column])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a block in column order from `reader` and writes it in row order to `writer`, undoing `interleave`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`UnexpectedEof`] if `reader` ends before a whole block is read. Nothing is written in that case.
+    ///
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn deinterleave<E1: BitEndianness, R: Read, E2: BitEndianness, W: Write>(
+        &self,
+        reader: &mut BitReader<E1, R>,
+        writer: &mut BitWriter<E2, W>,
+    ) -> Res<()> {
+        let block = read_block(reader, self.block_bits())?;
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                writer.write_bit(block[column * self.rows + row])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_block<E: BitEndianness, R: Read>(
+    reader: &mut BitReader<E, R>,
+    count: usize,
+) -> Res<Vec<bool>> {
+    (0..count).map(|_| reader.read_bit()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, BEBitWriter, BlockInterleaver, LEBitReader, LEBitWriter};
+
+    #[test]
+    fn round_trip() {
+        let data: Vec<u8> = (0..30u8).map(|i| i.wrapping_mul(37) ^ 0x5a).collect();
+        let interleaver = BlockInterleaver::new(5, 12);
+        let mut reader = LEBitReader::new(&data[..]);
+        let mut writer = BEBitWriter::new(vec![]);
+        for _ in 0..4 {
+            interleaver.interleave(&mut reader, &mut writer).unwrap();
+        }
+        let mixed = writer.into_inner().unwrap();
+        assert_ne!(mixed, data);
+        let mut reader = BEBitReader::new(&mixed[..]);
+        let mut writer = LEBitWriter::new(vec![]);
+        for _ in 0..4 {
+            interleaver.deinterleave(&mut reader, &mut writer).unwrap();
+        }
+        assert_eq!(writer.into_inner().unwrap(), data);
+    }
+
+    #[test]
+    fn burst_is_spread() {
+        // a block of 3 rows of 8 bits, the first row all ones
+        let interleaver = BlockInterleaver::new(3, 8);
+        let mut reader = BEBitReader::new(&b"\xff\x00\x00"[..]);
+        let mut writer = BEBitWriter::new(vec![]);
+        interleaver.interleave(&mut reader, &mut writer).unwrap();
+        assert_eq!(writer.into_inner().unwrap(), b"\x92\x49\x24");
+    }
+
+    #[test]
+    fn short_block() {
+        let interleaver = BlockInterleaver::new(3, 3);
+        let mut reader = BEBitReader::new(&b"\xff"[..]);
+        let mut writer = BEBitWriter::new(vec![]);
+        let err = interleaver
+            .interleave(&mut reader, &mut writer)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert!(writer.is_aligned());
+    }
+}
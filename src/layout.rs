@@ -0,0 +1,548 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// A decoded field value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// An unsigned integer, from a `uN` field.
+    UInt(u64),
+    /// A signed integer in two's complement, from an `iN` field.
+    Int(i64),
+    /// A single bit, from a `bool` field.
+    Bool(bool),
+    /// The elements of a repeated field.
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// Returns the value as an unsigned integer, for use as a repeat count or in a condition.
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::UInt(value) => Some(*value),
+            Self::Int(value) => u64::try_from(*value).ok(),
+            Self::Bool(value) => Some(u64::from(*value)),
+            Self::Array(_) => None,
+        }
+    }
+}
+
+/// The field values of a record decoded with a `Layout`, in layout order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Record {
+    fields: Vec<(String, Value)>,
+}
+
+impl Record {
+    /// Creates an empty record.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value of a field, or `None` if it is not present.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Sets the value of a field, replacing any previous value.
+    pub fn set(&mut self, name: &str, value: Value) {
+        match self.fields.iter_mut().find(|(field, _)| field == name) {
+            Some((_, old)) => *old = value,
+            None => self.fields.push((name.to_string(), value)),
+        }
+    }
+
+    /// Returns an iterator over the field names and values.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    fn get_u64(&self, name: &str) -> Res<u64> {
+        self.get(name).and_then(Value::as_u64).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("field `{name}` is not present or not an integer"),
+            )
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Type {
+    UInt(u8),
+    Int(u8),
+    Bool,
+}
+
+impl Type {
+    fn width(self) -> u8 {
+        match self {
+            Self::UInt(width) | Self::Int(width) => width,
+            Self::Bool => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Count {
+    Fixed(u64),
+    Field(String),
+}
+
+#[derive(Clone, Debug)]
+struct Condition {
+    field: String,
+    equal: bool,
+    value: u64,
+}
+
+#[derive(Clone, Debug)]
+struct Field {
+    /// `None` for padding fields, named `_`.
+    name: Option<String>,
+    ty: Type,
+    repeat: Option<Count>,
+    condition: Option<Condition>,
+}
+
+#[derive(Clone, Debug)]
+enum Item {
+    Align,
+    Field(Field),
+}
+
+/// A bit layout parsed from a textual description at runtime.
+///
+/// Only available with the `layout` feature.
+///
+/// A layout is a list of fields, one per line, each consisting of a name, a type, an optional repeat count and an optional condition:
+///
+/// ```text
+/// name: type [count] if condition
+/// ```
+///
+/// - Types are `uN` for an `N`-bit unsigned integer, `iN` for an `N`-bit signed integer in two's complement, with `N` from 1 to 64, and `bool` for a single bit.
+/// - The count, in brackets, repeats the field, either a fixed number of times or as often as the value of an earlier field.
+/// - The condition makes the field optional. It is either the name of an earlier field, which has to be nonzero, or a comparison of an earlier field with a number using `==` or `!=`.
+/// - Fields named `_` are padding: they are skipped when decoding and written as zeros when encoding.
+/// - A line containing just `align` aligns to the byte boundary.
+/// - Everything after `#` is a comment.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, BEBitWriter, Layout, Value};
+///
+/// let layout = Layout::parse("
+///     version: u3
+///     has_crc: bool
+///     _: u4
+///     len: u8
+///     data: u8[len]
+///     crc: u16 if has_crc
+/// ").unwrap();
+///
+/// let data = b"\x50\x02\xab\xcd\x12\x34";
+/// let record = layout.decode(&mut BEBitReader::new(&data[..])).unwrap();
+/// assert_eq!(record.get("version"), Some(&Value::UInt(2)));
+/// assert_eq!(record.get("data"), Some(&Value::Array(vec![Value::UInt(0xab), Value::UInt(0xcd)])));
+/// assert_eq!(record.get("crc"), Some(&Value::UInt(0x1234)));
+///
+/// let mut writer = BEBitWriter::new(vec![]);
+/// layout.encode(&record, &mut writer).unwrap();
+/// assert_eq!(writer.into_inner().unwrap(), data);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Layout {
+    items: Vec<Item>,
+}
+
+fn parse_error(line: usize, msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("line {line}: {msg}"))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Layout {
+    /// Parses a layout description.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if the description is invalid, with the line number in the message.
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn parse(description: &str) -> Res<Self> {
+        let mut items = vec![];
+        let mut names: Vec<&str> = vec![];
+        for (index, line) in description.lines().enumerate() {
+            let number = index + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line == "align" {
+                items.push(Item::Align);
+                continue;
+            }
+            let (name, rest) = line
+                .split_once(':')
+                .ok_or_else(|| parse_error(number, "expected `name: type`"))?;
+            let name = name.trim();
+            if !is_identifier(name) {
+                return Err(parse_error(number, &format!("invalid field name `{name}`")));
+            }
+            if name != "_" && names.contains(&name) {
+                return Err(parse_error(number, &format!("duplicate field `{name}`")));
+            }
+            let (spec, condition) = match rest.split_once(" if ") {
+                Some((spec, condition)) => (spec, Some(condition.trim())),
+                None => (rest, None),
+            };
+            let (ty, repeat) = match spec.split_once('[') {
+                Some((ty, count)) => {
+                    let count = count
+                        .trim()
+                        .strip_suffix(']')
+                        .ok_or_else(|| parse_error(number, "expected `]`"))?
+                        .trim();
+                    (ty.trim(), Some(Self::parse_count(number, count, &names)?))
+                }
+                None => (spec.trim(), None),
+            };
+            let ty = Self::parse_type(number, ty)?;
+            let condition = condition
+                .map(|condition| Self::parse_condition(number, condition, &names))
+                .transpose()?;
+            let name = (name != "_").then(|| {
+                names.push(name);
+                name.to_string()
+            });
+            items.push(Item::Field(Field {
+                name,
+                ty,
+                repeat,
+                condition,
+            }));
+        }
+        Ok(Self { items })
+    }
+
+    fn parse_type(line: usize, ty: &str) -> Res<Type> {
+        if ty == "bool" {
+            return Ok(Type::Bool);
+        }
+        let width = |digits: &str| {
+            digits
+                .parse::<u8>()
+                .ok()
+                .filter(|width| (1..=64).contains(width))
+                .ok_or_else(|| parse_error(line, &format!("invalid width in type `{ty}`")))
+        };
+        if let Some(digits) = ty.strip_prefix('u') {
+            Ok(Type::UInt(width(digits)?))
+        } else if let Some(digits) = ty.strip_prefix('i') {
+            Ok(Type::Int(width(digits)?))
+        } else {
+            Err(parse_error(line, &format!("unknown type `{ty}`")))
+        }
+    }
+
+    fn parse_reference(line: usize, name: &str, names: &[&str]) -> Res<String> {
+        if names.contains(&name) {
+            Ok(name.to_string())
+        } else {
+            Err(parse_error(
+                line,
+                &format!("`{name}` does not refer to an earlier field"),
+            ))
+        }
+    }
+
+    fn parse_count(line: usize, count: &str, names: &[&str]) -> Res<Count> {
+        match count.parse() {
+            Ok(count) => Ok(Count::Fixed(count)),
+            Err(_) => Ok(Count::Field(Self::parse_reference(line, count, names)?)),
+        }
+    }
+
+    fn parse_condition(line: usize, condition: &str, names: &[&str]) -> Res<Condition> {
+        let (field, equal, value) = if let Some((field, value)) = condition.split_once("==") {
+            (field, true, value)
+        } else if let Some((field, value)) = condition.split_once("!=") {
+            (field, false, value)
+        } else {
+            (condition, false, "0")
+        };
+        let value = value.trim().parse().map_err(|_| {
+            parse_error(line, &format!("invalid number in condition `{condition}`"))
+        })?;
+        Ok(Condition {
+            field: Self::parse_reference(line, field.trim(), names)?,
+            equal,
+            value,
+        })
+    }
+
+    /// Returns whether the condition of a field, if any, is true.
+    fn is_present(field: &Field, record: &Record) -> Res<bool> {
+        match &field.condition {
+            Some(condition) => {
+                Ok((record.get_u64(&condition.field)? == condition.value) == condition.equal)
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// Returns how often a field is repeated, or `None` if it is not an array.
+    fn repeat(field: &Field, record: &Record) -> Res<Option<u64>> {
+        Ok(match &field.repeat {
+            None => None,
+            Some(Count::Fixed(count)) => Some(*count),
+            Some(Count::Field(name)) => Some(record.get_u64(name)?),
+        })
+    }
+
+    /// Decodes a record.
+    ///
+    /// Fields whose condition is false are not present in the record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if a repeat count or condition refers to a field that is not present or not an integer.
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn decode<E: BitEndianness, R: Read>(&self, reader: &mut BitReader<E, R>) -> Res<Record> {
+        let mut record = Record::new();
+        for item in &self.items {
+            let field = match item {
+                Item::Align => {
+                    reader.align();
+                    continue;
+                }
+                Item::Field(field) => field,
+            };
+            if !Self::is_present(field, &record)? {
+                continue;
+            }
+            let repeat = Self::repeat(field, &record)?;
+            let value = match repeat {
+                None => Self::decode_value(reader, field.ty)?,
+                Some(count) => Value::Array(
+                    (0..count)
+                        .map(|_| Self::decode_value(reader, field.ty))
+                        .collect::<Res<_>>()?,
+                ),
+            };
+            if let Some(name) = &field.name {
+                record.set(name, value);
+            }
+        }
+        Ok(record)
+    }
+
+    fn decode_value<E: BitEndianness, R: Read>(
+        reader: &mut BitReader<E, R>,
+        ty: Type,
+    ) -> Res<Value> {
+        let bits = reader.read_bits_wide(ty.width())?;
+        Ok(match ty {
+            Type::UInt(_) => Value::UInt(bits),
+            Type::Bool => Value::Bool(bits != 0),
+            Type::Int(width) => {
+                let shift = 64 - u32::from(width);
+                #[allow(clippy::cast_possible_wrap)]
+                Value::Int(((bits << shift) as i64) >> shift)
+            }
+        })
+    }
+
+    /// Encodes a record.
+    ///
+    /// Padding fields are written as zeros and need not be present in the record. Fields whose condition is false are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if a field is missing, has the wrong type, does not fit in its width, or a repeated field has the wrong number of elements.
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn encode<E: BitEndianness, W: Write>(
+        &self,
+        record: &Record,
+        writer: &mut BitWriter<E, W>,
+    ) -> Res<()> {
+        for item in &self.items {
+            let field = match item {
+                Item::Align => {
+                    writer.align()?;
+                    continue;
+                }
+                Item::Field(field) => field,
+            };
+            if !Self::is_present(field, record)? {
+                continue;
+            }
+            let repeat = Self::repeat(field, record)?;
+            let Some(name) = &field.name else {
+                let count = repeat.unwrap_or(1) * u64::from(field.ty.width());
+                writer.write_zeros(count)?;
+                continue;
+            };
+            let value = record.get(name).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("field `{name}` is missing"),
+                )
+            })?;
+            match (repeat, value) {
+                (None, value) => Self::encode_value(writer, name, field.ty, value)?,
+                (Some(count), Value::Array(values)) if values.len() as u64 == count => {
+                    for value in values {
+                        Self::encode_value(writer, name, field.ty, value)?;
+                    }
+                }
+                (Some(count), _) => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("field `{name}` must be an array of {count} elements"),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_value<E: BitEndianness, W: Write>(
+        writer: &mut BitWriter<E, W>,
+        name: &str,
+        ty: Type,
+        value: &Value,
+    ) -> Res<()> {
+        let width = ty.width();
+        let bits = match (ty, value) {
+            (Type::Bool, Value::Bool(value)) => Some(u64::from(*value)),
+            (Type::UInt(_), Value::UInt(value)) => {
+                (width == 64 || value >> width == 0).then_some(*value)
+            }
+            (Type::Int(_), Value::Int(value)) => {
+                let shift = 64 - u32::from(width);
+                #[allow(clippy::cast_sign_loss)]
+                ((value << shift) >> shift == *value).then_some(*value as u64 & (u64::MAX >> shift))
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("field `{name}` has the wrong type"),
+                ));
+            }
+        };
+        let bits = bits.ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("value of field `{name}` does not fit in {width} bits"),
+            )
+        })?;
+        writer.write_bits_wide(bits, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layout, Record, Value};
+    use crate::{LEBitReader, LEBitWriter};
+
+    #[test]
+    fn roundtrip() {
+        let layout = Layout::parse(
+            "
+            kind: u2      # record kind
+            delta: i5
+            flags: bool[3]
+            align
+            extra: u12 if kind == 3
+            samples: i7[2] if kind != 3
+            ",
+        )
+        .unwrap();
+        for (kind, delta) in [(3, -16), (1, 15), (0, -1)] {
+            let mut record = Record::new();
+            record.set("kind", Value::UInt(kind));
+            record.set("delta", Value::Int(delta));
+            record.set(
+                "flags",
+                Value::Array(vec![
+                    Value::Bool(true),
+                    Value::Bool(false),
+                    Value::Bool(true),
+                ]),
+            );
+            if kind == 3 {
+                record.set("extra", Value::UInt(0xabc));
+            } else {
+                record.set(
+                    "samples",
+                    Value::Array(vec![Value::Int(-64), Value::Int(63)]),
+                );
+            }
+            let mut writer = LEBitWriter::new(vec![]);
+            layout.encode(&record, &mut writer).unwrap();
+            let data = writer.into_inner().unwrap();
+            let decoded = layout.decode(&mut LEBitReader::new(&data[..])).unwrap();
+            assert_eq!(decoded, record);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        for description in [
+            "a u8",
+            "a: u0",
+            "a: u65",
+            "a: f32",
+            "a: u8[b]",
+            "a: u8 if b",
+            "a: u8\na: u8",
+            "1a: u8",
+            "a: u8[3",
+            "a: u8\nb: u8 if a == x",
+        ] {
+            let err = Layout::parse(description).unwrap_err();
+            assert_eq!(
+                err.kind(),
+                std::io::ErrorKind::InvalidInput,
+                "{description}"
+            );
+        }
+    }
+
+    #[test]
+    fn encode_errors() {
+        let layout = Layout::parse("a: u4\nb: i4[a]").unwrap();
+        let mut writer = LEBitWriter::new(vec![]);
+        let mut record = Record::new();
+        assert!(layout.encode(&record, &mut writer).is_err());
+        record.set("a", Value::UInt(16));
+        assert!(layout.encode(&record, &mut writer).is_err());
+        record.set("a", Value::UInt(1));
+        record.set("b", Value::Array(vec![Value::Int(8)]));
+        assert!(layout.encode(&record, &mut writer).is_err());
+        record.set("b", Value::Array(vec![]));
+        assert!(layout.encode(&record, &mut writer).is_err());
+        record.set("b", Value::Array(vec![Value::Int(-8)]));
+        layout.encode(&record, &mut writer).unwrap();
+    }
+}
@@ -44,9 +44,73 @@
 //! [`Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
 //! [`endio`]: https://crates.io/crates/endio
 
+mod bitmap;
+mod code8b10b;
+mod const_read;
+mod context;
+mod digest;
+mod dynamic;
+mod editor;
+mod elias_fano;
 mod endian;
+mod fixed;
+mod golomb;
+mod gorilla;
+mod huffman;
+mod inject;
+mod int;
+mod interleave;
+#[cfg(feature = "layout")]
+mod layout;
+mod lzw;
+mod num;
+mod pack;
+mod pad;
+mod rank;
 mod read;
+mod reverse;
+mod rle;
+mod seek;
+mod simple;
+mod split;
+mod string;
+mod tee;
+#[cfg(feature = "testing")]
+mod testing;
+mod traits;
+mod transcode;
+mod varint;
 mod write;
 
+pub use self::bitmap::*;
+pub use self::code8b10b::*;
+pub use self::const_read::*;
+pub use self::context::*;
+pub use self::digest::*;
+pub use self::dynamic::*;
+pub use self::editor::*;
+pub use self::elias_fano::*;
+pub use self::endian::{BitOrder, ByteOrder};
+pub use self::fixed::*;
+pub use self::gorilla::*;
+pub use self::huffman::{CodeLengthFormat, HuffmanTable};
+pub use self::inject::*;
+pub use self::int::*;
+pub use self::interleave::*;
+#[cfg(feature = "layout")]
+pub use self::layout::*;
+pub use self::lzw::*;
+pub use self::pad::*;
+pub use self::rank::*;
 pub use self::read::*;
+pub use self::reverse::*;
+pub use self::rle::*;
+pub use self::seek::*;
+pub use self::simple::*;
+pub use self::split::*;
+pub use self::tee::*;
+#[cfg(feature = "testing")]
+pub use self::testing::*;
+pub use self::traits::*;
+pub use self::transcode::*;
 pub use self::write::*;
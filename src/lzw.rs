@@ -0,0 +1,148 @@
+use std::io::Read;
+use std::io::Result as Res;
+
+use crate::BitReader;
+use crate::endian::BitEndianness;
+
+/// Reads codes of a width that changes at runtime, as in LZW streams.
+///
+/// GIF stores its LZW codes least significant bit first, so it is read with a little endian `BitReader`, while TIFF and PDF store them most significant bit first and are read with a big endian one. The width can be changed between any two codes, even in the middle of a byte.
+///
+/// The reader doesn't know when the width grows, the decoder calls `grow` or `set_width` after adding to its dictionary. This allows for both the GIF convention and the "early change" of TIFF, where the width grows one code sooner.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{LEBitReader, VariableWidthReader};
+///
+/// let mut reader = LEBitReader::new(&b"\x8c\x01"[..]);
+/// let mut codes = VariableWidthReader::new(&mut reader, 3);
+/// assert_eq!(codes.read_code().unwrap(), 4);
+/// assert_eq!(codes.read_code().unwrap(), 1);
+/// codes.grow();
+/// assert_eq!(codes.read_code().unwrap(), 6);
+/// ```
+pub struct VariableWidthReader<'a, E: BitEndianness, R: Read> {
+    reader: &'a mut BitReader<E, R>,
+    width: u8,
+}
+
+impl<'a, E: BitEndianness, R: Read> VariableWidthReader<'a, E, R> {
+    /// Creates a new reader of codes of `width` bits from `reader`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is 0 or > 16.
+    pub fn new(reader: &'a mut BitReader<E, R>, width: u8) -> Self {
+        assert_width(width);
+        Self { reader, width }
+    }
+
+    /// Returns the current code width.
+    #[must_use]
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Sets the code width, for example to reset it after a clear code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is 0 or > 16.
+    pub fn set_width(&mut self, width: u8) {
+        assert_width(width);
+        self.width = width;
+    }
+
+    /// Increases the code width by one bit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the width is already 16.
+    pub fn grow(&mut self) {
+        self.set_width(self.width + 1);
+    }
+
+    /// Reads a code of the current width.
+    ///
+    /// This is the same as `read_bits_u16(width)` on the underlying reader.
+    pub fn read_code(&mut self) -> Res<u16> {
+        self.reader.read_bits_u16(self.width)
+    }
+
+    /// Returns a mutable reference to the underlying reader, for example to align it after the end code.
+    pub fn get_mut(&mut self) -> &mut BitReader<E, R> {
+        self.reader
+    }
+}
+
+fn assert_width(width: u8) {
+    assert!(
+        (1..=16).contains(&width),
+        "code width must be between 1 and 16"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VariableWidthReader;
+    use crate::{BEBitReader, BEBitWriter, LEBitReader, LEBitWriter};
+
+    /// Codes and their widths, growing mid-byte.
+    const CODES: [(u16, u8); 8] = [
+        (4, 3),
+        (1, 3),
+        (6, 4),
+        (15, 4),
+        (0x1f, 5),
+        (0x2a, 9),
+        (0x0fff, 12),
+        (0x0100, 9),
+    ];
+
+    #[test]
+    fn known_layout() {
+        let mut reader = BEBitReader::new(&b"\x85\x80"[..]);
+        let mut codes = VariableWidthReader::new(&mut reader, 3);
+        assert_eq!(codes.read_code().unwrap(), 4);
+        assert_eq!(codes.read_code().unwrap(), 1);
+        codes.grow();
+        assert_eq!(codes.width(), 4);
+        assert_eq!(codes.read_code().unwrap(), 6);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut writer = LEBitWriter::new(vec![]);
+        for (code, width) in CODES {
+            writer.write_bits_u128(code.into(), width).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        let mut codes = VariableWidthReader::new(&mut reader, 1);
+        for (code, width) in CODES {
+            codes.set_width(width);
+            assert_eq!(codes.read_code().unwrap(), code);
+        }
+
+        let mut writer = BEBitWriter::new(vec![]);
+        for (code, width) in CODES {
+            writer.write_bits_u128(code.into(), width).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        let mut codes = VariableWidthReader::new(&mut reader, 1);
+        for (code, width) in CODES {
+            codes.set_width(width);
+            assert_eq!(codes.read_code().unwrap(), code);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "between 1 and 16")]
+    fn too_wide() {
+        let mut reader = BEBitReader::new(&b""[..]);
+        let mut codes = VariableWidthReader::new(&mut reader, 16);
+        codes.grow();
+    }
+}
@@ -0,0 +1,353 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::{BitEndianness, ByteOrder};
+use crate::{BitReader, BitWriter};
+
+/// Assembles an integer from up to 8 bytes in the given byte order.
+fn from_bytes(bytes: &[u8], byte_order: ByteOrder) -> u64 {
+    let fold = |acc, &byte| (acc << 8) | u64::from(byte);
+    match byte_order {
+        ByteOrder::Big => bytes.iter().fold(0, fold),
+        ByteOrder::Little => bytes.iter().rev().fold(0, fold),
+    }
+}
+
+/// Splits the lowest `N` bytes of an integer in the given byte order.
+fn to_bytes<const N: usize>(value: u64, byte_order: ByteOrder) -> [u8; N] {
+    let mut bytes = [0; N];
+    bytes.copy_from_slice(&value.to_le_bytes()[..N]);
+    if byte_order == ByteOrder::Big {
+        bytes.reverse();
+    }
+    bytes
+}
+
+fn check_fits(value: u64, bits: u8) -> Res<()> {
+    if value >> bits != 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{value} does not fit in {bits} bits"),
+        ));
+    }
+    Ok(())
+}
+
+impl<E: BitEndianness, R: Read> BitReader<E, R> {
+    /// Reads a packed binary-coded decimal number of `digits` decimal digits.
+    ///
+    /// Each digit is a 4-bit field read like `read_bits(4)`, most significant digit first. The digits may be at any bit offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `digits` > 19.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if a nibble is not a decimal digit. All `digits` nibbles are consumed regardless.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x20\x24"[..]);
+    /// assert_eq!(reader.read_bcd(4).unwrap(), 2024);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_bcd(&mut self, digits: u8) -> Res<u64> {
+        assert!(digits <= 19);
+        let mut value = 0;
+        let mut invalid = None;
+        for _ in 0..digits {
+            let position = self.bit_position();
+            let digit = self.read_bits(4)?;
+            if digit > 9 && invalid.is_none() {
+                invalid = Some((digit, position));
+            }
+            value = value * 10 + u64::from(digit);
+        }
+        match invalid {
+            Some((digit, position)) => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid BCD digit {digit:#x} at bit offset {position}"),
+            )),
+            None => Ok(value),
+        }
+    }
+
+    /// Reads a 24-bit unsigned integer from 3 bytes in the given byte order.
+    ///
+    /// The bytes may be at any bit offset, they are read through the shifted `Read` implementation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{BEBitReader, ByteOrder};
+    ///
+    /// let mut reader = BEBitReader::new(&b"\x01\x02\x03"[..]);
+    /// assert_eq!(reader.read_u24(ByteOrder::Big).unwrap(), 0x01_02_03);
+    /// ```
+    pub fn read_u24(&mut self, byte_order: ByteOrder) -> Res<u32> {
+        let mut bytes = [0; 3];
+        self.read_exact(&mut bytes)?;
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(from_bytes(&bytes, byte_order) as u32)
+    }
+
+    /// Reads a 48-bit unsigned integer from 6 bytes in the given byte order.
+    ///
+    /// The bytes may be at any bit offset, they are read through the shifted `Read` implementation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{BEBitReader, ByteOrder};
+    ///
+    /// let mut reader = BEBitReader::new(&b"\x01\x02\x03\x04\x05\x06"[..]);
+    /// assert_eq!(reader.read_u48(ByteOrder::Little).unwrap(), 0x06_05_04_03_02_01);
+    /// ```
+    pub fn read_u48(&mut self, byte_order: ByteOrder) -> Res<u64> {
+        let mut bytes = [0; 6];
+        self.read_exact(&mut bytes)?;
+        Ok(from_bytes(&bytes, byte_order))
+    }
+
+    /// Reads a `count`-bit biased integer (also known as excess-K or offset binary), returning the stored value minus `bias`.
+    ///
+    /// The raw field is read like `read_bits`, but may be up to 64 bits wide.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the result does not fit in an `i64`.
+    ///
+    /// # Examples
+    ///
+    /// Reading the exponent of an IEEE 754 single precision float:
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let data = 1.5f32.to_be_bytes();
+    /// let mut reader = BEBitReader::new(&data[..]);
+    /// assert!(!reader.read_bit().unwrap());
+    /// assert_eq!(reader.read_biased(8, 127).unwrap(), 0);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_biased(&mut self, count: u8, bias: i64) -> Res<i64> {
+        let raw = self.read_bits_wide(count)?;
+        i64::try_from(i128::from(raw) - i128::from(bias)).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("biased value {raw} - {bias} does not fit in an i64"),
+            )
+        })
+    }
+}
+
+impl<E: BitEndianness, W: Write> BitWriter<E, W> {
+    /// Writes `value` as a packed binary-coded decimal number of `digits` decimal digits, padded with leading zeros.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `digits` > 19.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `value` has more than `digits` digits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bcd(42, 4).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x00\x42");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_bcd(&mut self, value: u64, digits: u8) -> Res<()> {
+        assert!(digits <= 19);
+        if value >= 10u64.pow(u32::from(digits)) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{value} does not fit in {digits} BCD digits"),
+            ));
+        }
+        for i in (0..digits).rev() {
+            let digit = (value / 10u64.pow(u32::from(i))) % 10;
+            #[allow(clippy::cast_possible_truncation)]
+            self.write_bits(digit as u8, 4)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a 24-bit unsigned integer as 3 bytes in the given byte order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `value` does not fit in 24 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{BEBitWriter, ByteOrder};
+    ///
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_u24(0x01_02_03, ByteOrder::Big).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x01\x02\x03");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_u24(&mut self, value: u32, byte_order: ByteOrder) -> Res<()> {
+        check_fits(u64::from(value), 24)?;
+        self.write_all(&to_bytes::<3>(u64::from(value), byte_order))
+    }
+
+    /// Writes a 48-bit unsigned integer as 6 bytes in the given byte order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `value` does not fit in 48 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{BEBitWriter, ByteOrder};
+    ///
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_u48(0x06_05_04_03_02_01, ByteOrder::Little).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x01\x02\x03\x04\x05\x06");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_u48(&mut self, value: u64, byte_order: ByteOrder) -> Res<()> {
+        check_fits(value, 48)?;
+        self.write_all(&to_bytes::<6>(value, byte_order))
+    }
+
+    /// Writes `value` as a `count`-bit biased integer (also known as excess-K or offset binary), storing `value + bias`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `value + bias` is negative or does not fit in `count` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_biased(-3, 4, 8).unwrap();
+    /// writer.write_biased(7, 4, 8).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x5f");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_biased(&mut self, value: i64, count: u8, bias: i64) -> Res<()> {
+        assert!(count <= 64);
+        let raw = i128::from(value) + i128::from(bias);
+        if raw < 0 || raw >> count != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{value} with bias {bias} does not fit in {count} bits"),
+            ));
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        self.write_bits_wide(raw as u64, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, BEBitWriter, ByteOrder, LEBitReader, LEBitWriter};
+
+    #[test]
+    fn bcd_roundtrip_shifted() {
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bits(0x05, 3).unwrap();
+        writer.write_bcd(1_234_567_890_123_456_789, 19).unwrap();
+        writer.write_bcd(7, 1).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert_eq!(reader.read_bits(3).unwrap(), 0x05);
+        assert_eq!(reader.read_bcd(19).unwrap(), 1_234_567_890_123_456_789);
+        assert_eq!(reader.read_bcd(1).unwrap(), 7);
+    }
+
+    #[test]
+    fn bcd_invalid_digit() {
+        let mut reader = BEBitReader::new(&b"\x1a\x23"[..]);
+        let err = reader.read_bcd(3).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("bit offset 4"));
+        assert_eq!(reader.read_bits(4).unwrap(), 3);
+    }
+
+    #[test]
+    fn u24_u48_roundtrip_shifted() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bits(0x03, 5).unwrap();
+        writer.write_u24(0xab_cd_ef, ByteOrder::Big).unwrap();
+        writer.write_u24(0xab_cd_ef, ByteOrder::Little).unwrap();
+        writer.write_u48(0x1234_5678_9abc, ByteOrder::Big).unwrap();
+        writer
+            .write_u48(0x1234_5678_9abc, ByteOrder::Little)
+            .unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        assert_eq!(reader.read_bits(5).unwrap(), 0x03);
+        assert_eq!(reader.read_u24(ByteOrder::Big).unwrap(), 0xab_cd_ef);
+        let mut bytes = [0; 3];
+        std::io::Read::read_exact(&mut reader, &mut bytes).unwrap();
+        assert_eq!(bytes, [0xef, 0xcd, 0xab]);
+        assert_eq!(reader.read_u48(ByteOrder::Big).unwrap(), 0x1234_5678_9abc);
+        assert_eq!(
+            reader.read_u48(ByteOrder::Little).unwrap(),
+            0x1234_5678_9abc
+        );
+    }
+
+    #[test]
+    fn u24_u48_out_of_range() {
+        let mut writer = BEBitWriter::new(vec![]);
+        assert!(writer.write_u24(1 << 24, ByteOrder::Big).is_err());
+        assert!(writer.write_u48(1 << 48, ByteOrder::Little).is_err());
+    }
+
+    #[test]
+    fn biased_roundtrip() {
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_biased(-1023, 11, 1023).unwrap();
+        writer.write_biased(1024, 11, 1023).unwrap();
+        writer.write_biased(i64::MAX, 64, 0).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert_eq!(reader.read_biased(11, 1023).unwrap(), -1023);
+        assert_eq!(reader.read_biased(11, 1023).unwrap(), 1024);
+        assert_eq!(reader.read_biased(64, 0).unwrap(), i64::MAX);
+    }
+
+    #[test]
+    fn biased_out_of_range() {
+        let mut writer = BEBitWriter::new(vec![]);
+        assert!(writer.write_biased(-9, 4, 8).is_err());
+        assert!(writer.write_biased(8, 4, 8).is_err());
+        let mut reader = BEBitReader::new(&[0xff; 8][..]);
+        assert!(reader.read_biased(64, -1).is_err());
+    }
+
+    #[test]
+    fn bcd_value_too_large() {
+        let mut writer = BEBitWriter::new(vec![]);
+        let err = writer.write_bcd(100, 2).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
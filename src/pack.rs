@@ -0,0 +1,105 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// Number of bits of the width header written by `write_packed_array`.
+const WIDTH_BITS: u8 = 7;
+
+impl<E: BitEndianness, R: Read> BitReader<E, R> {
+    /// Reads `len` integers packed with `BitWriter::write_packed_array`.
+    ///
+    /// First a 7-bit width header is read, then `len` values of that width. The number of values is not part of the encoding and has to be known by the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the width header is larger than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x04\xd8"[..]);
+    /// assert_eq!(reader.read_packed_array(3).unwrap(), [1, 2, 3]);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_packed_array(&mut self, len: usize) -> Res<Vec<u64>> {
+        let width = self.read_bits(WIDTH_BITS)?;
+        if width > 64 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("packed array width {width} is larger than 64"),
+            ));
+        }
+        (0..len).map(|_| self.read_bits_wide(width)).collect()
+    }
+}
+
+impl<E: BitEndianness, W: Write> BitWriter<E, W> {
+    /// Writes integers packed with the smallest bit width that fits all of them.
+    ///
+    /// The width is written as a 7-bit header, followed by every value in that width. An empty slice or a slice of zeros uses width 0, so only the header is written.
+    ///
+    /// Use `BitReader::read_packed_array` with the same length to read the values back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_packed_array(&[1, 2, 3]).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x04\xd8");
+    /// ```
+    pub fn write_packed_array(&mut self, values: &[u64]) -> Res<()> {
+        let combined = values.iter().fold(0, |acc, &value| acc | value);
+        #[allow(clippy::cast_possible_truncation)]
+        let width = (u64::BITS - combined.leading_zeros()) as u8;
+        self.write_bits(width, WIDTH_BITS)?;
+        for &value in values {
+            self.write_bits_wide(value, width)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, LEBitReader, LEBitWriter};
+
+    #[test]
+    fn packed_array_roundtrip() {
+        let arrays: [&[u64]; 5] = [
+            &[],
+            &[0, 0, 0],
+            &[1, 0, 1, 1],
+            &[1000, 3, 999, 65535],
+            &[u64::MAX, 0, 1 << 63],
+        ];
+        let mut writer = LEBitWriter::new(vec![]);
+        for values in arrays {
+            writer.write_packed_array(values).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        for values in arrays {
+            assert_eq!(reader.read_packed_array(values.len()).unwrap(), values);
+        }
+    }
+
+    #[test]
+    fn packed_array_minimal_width() {
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_packed_array(&[5; 8]).unwrap();
+        // 7 bits of header and 8 values of 3 bits each
+        assert_eq!(writer.into_inner().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn packed_array_invalid_width() {
+        let mut reader = BEBitReader::new(&b"\xfe"[..]);
+        let err = reader.read_packed_array(1).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::Read;
+use std::io::Result as Res;
+
+/// Reads zero bytes forever after the end of the underlying reader.
+///
+/// Some decoders, like range decoders or bit buffers that refill ahead of use, read a few bits past the end of their input. Use this as the underlying reader of a `BitReader` to make these reads return 0 bits instead of failing with [`UnexpectedEof`]. The number of zero bytes read can be checked afterwards, to tell whether a decoder read further than it should.
+///
+/// Since the stream never ends, `read_to_end`, `read_to_string` and other methods that read until the end never return. Bound them with [`Read::take`], like `reader.take(64).read_to_end(&mut buf)`.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, ZeroPadded};
+///
+/// let mut reader = BEBitReader::new(ZeroPadded::new(&b"\xff"[..]));
+/// assert_eq!(reader.read_bits(6).unwrap(), 0x3f);
+/// assert_eq!(reader.read_bits(4).unwrap(), 0x0c);
+/// assert_eq!(reader.into_inner().padded_bytes(), 1);
+/// ```
+///
+/// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+/// [`Read::take`]: https://doc.rust-lang.org/std/io/trait.Read.html#method.take
+#[derive(Clone, Debug)]
+pub struct ZeroPadded<R> {
+    inner: R,
+    /// Number of zero bytes read past the end, once the inner reader has ended.
+    padded: Option<u64>,
+}
+
+impl<R> ZeroPadded<R> {
+    /// Creates a new `ZeroPadded` reading from `inner` until it ends.
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            padded: None,
+        }
+    }
+
+    /// Returns whether the underlying reader has ended.
+    #[inline]
+    pub fn is_padding(&self) -> bool {
+        self.padded.is_some()
+    }
+
+    /// Returns the number of zero bytes read past the end of the underlying reader.
+    #[inline]
+    pub fn padded_bytes(&self) -> u64 {
+        self.padded.unwrap_or(0)
+    }
+
+    /// Gets a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwraps this `ZeroPadded`, returning the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// Read bytes of the underlying reader, then zeros.
+impl<R: Read> Read for ZeroPadded<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Res<usize> {
+        if self.padded.is_none() {
+            let count_read = self.inner.read(buf)?;
+            if count_read != 0 || buf.is_empty() {
+                return Ok(count_read);
+            }
+        }
+        buf.fill(0);
+        self.padded = Some(self.padded_bytes() + buf.len() as u64);
+        Ok(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::{LEBitReader, ZeroPadded};
+
+    #[test]
+    fn padding() {
+        let mut padded = ZeroPadded::new(&b"\x12\x34"[..]);
+        let mut buf = [0xff; 5];
+        assert_eq!(padded.read(&mut buf).unwrap(), 2);
+        assert!(!padded.is_padding());
+        assert_eq!(padded.read(&mut buf).unwrap(), 5);
+        assert_eq!(buf, [0; 5]);
+        assert!(padded.is_padding());
+        assert_eq!(padded.read(&mut buf[..3]).unwrap(), 3);
+        assert_eq!(padded.padded_bytes(), 8);
+    }
+
+    #[test]
+    fn bit_reader() {
+        let mut reader = LEBitReader::new(ZeroPadded::new(&b"\xab"[..]));
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0b);
+        assert_eq!(reader.read_bits_u64(64).unwrap(), 0x0a);
+        assert_eq!(reader.read_bit_opt().unwrap(), Some(false));
+        assert!(reader.get_ref().is_padding());
+    }
+}
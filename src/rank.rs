@@ -0,0 +1,256 @@
+use crate::endian::BitEndianness;
+
+/// Bits per superblock, which stores the absolute rank.
+const SUPERBLOCK_BITS: u64 = 512;
+/// Words per superblock, each word has its rank relative to the superblock stored.
+const WORDS_PER_SUPERBLOCK: usize = (SUPERBLOCK_BITS / 64) as usize;
+
+/// Returns the position of the `k`-th set bit in `word`, counting from the least significant bit.
+fn select_in_word(mut word: u64, k: u32) -> u32 {
+    for _ in 0..k {
+        word &= word - 1;
+    }
+    word.trailing_zeros()
+}
+
+/// A succinct rank/select index over a sequence of bits.
+///
+/// Bits are numbered in stream order: bit 0 is the first bit a `BitReader` with the same bit endianness would read from the data.
+///
+/// The index uses a two-level layout with an absolute rank per 512 bits and a relative rank per 64-bit word, about 3% space overhead on top of the bits themselves. `rank1` is constant time, `select1` is a binary search over the superblocks followed by a scan of at most 8 words.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::BERankSelect;
+///
+/// let index = BERankSelect::new(b"\x90\x01", 16);
+/// assert_eq!(index.count_ones(), 3);
+/// assert_eq!(index.rank1(3), 1);
+/// assert_eq!(index.rank1(4), 2);
+/// assert_eq!(index.select1(1), Some(3));
+/// assert_eq!(index.select1(2), Some(15));
+/// assert_eq!(index.select1(3), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RankSelect<E: BitEndianness> {
+    /// The bits, normalized so that stream bit `i` is bit `i % 64` of word `i / 64`.
+    words: Vec<u64>,
+    len: u64,
+    /// Number of set bits before each superblock.
+    superblocks: Vec<u64>,
+    /// Number of set bits before each word, relative to its superblock.
+    blocks: Vec<u16>,
+    ones: u64,
+    phantom: std::marker::PhantomData<E>,
+}
+
+/// Numbers bits most significant bit first.
+pub type BERankSelect = RankSelect<crate::endian::BE>;
+/// Numbers bits least significant bit first.
+pub type LERankSelect = RankSelect<crate::endian::LE>;
+
+impl<E: BitEndianness> RankSelect<E> {
+    /// Builds the index over the first `len` bits of `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` has less than `len` bits.
+    #[must_use]
+    pub fn new(data: &[u8], len: u64) -> Self {
+        assert!(len <= data.len() as u64 * 8, "data is shorter than len");
+        // map each byte to its bits in stream order, least significant bit first
+        let mut table = [0u8; 256];
+        for (byte, normalized) in (0..=255u8).zip(table.iter_mut()) {
+            for k in 0..8 {
+                if byte & E::shift_lsb(E::shift_msb(0xff, 7), k) != 0 {
+                    *normalized |= 1 << k;
+                }
+            }
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let used = &data[..len.div_ceil(8) as usize];
+        let mut words: Vec<u64> = used
+            .chunks(8)
+            .map(|chunk| {
+                let mut bytes = [0; 8];
+                for (normalized, &byte) in bytes.iter_mut().zip(chunk) {
+                    *normalized = table[usize::from(byte)];
+                }
+                u64::from_le_bytes(bytes)
+            })
+            .collect();
+        if let Some(last) = words.last_mut()
+            && !len.is_multiple_of(64)
+        {
+            *last &= (1 << (len % 64)) - 1;
+        }
+        let mut superblocks = Vec::with_capacity(words.len().div_ceil(WORDS_PER_SUPERBLOCK));
+        let mut blocks = Vec::with_capacity(words.len());
+        let mut ones = 0;
+        for superblock in words.chunks(WORDS_PER_SUPERBLOCK) {
+            superblocks.push(ones);
+            let mut relative = 0;
+            for word in superblock {
+                blocks.push(relative);
+                #[allow(clippy::cast_possible_truncation)]
+                let count = word.count_ones() as u16;
+                relative += count;
+            }
+            ones += u64::from(relative);
+        }
+        Self {
+            words,
+            len,
+            superblocks,
+            blocks,
+            ones,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns the number of bits in the index.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns whether the index contains no bits.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the total number of set bits.
+    #[inline]
+    #[must_use]
+    pub fn count_ones(&self) -> u64 {
+        self.ones
+    }
+
+    /// Returns the bit at position `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` >= `len()`.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, i: u64) -> bool {
+        assert!(i < self.len, "index out of bounds");
+        #[allow(clippy::cast_possible_truncation)]
+        let word = self.words[(i / 64) as usize];
+        word >> (i % 64) & 1 != 0
+    }
+
+    /// Returns the number of set bits before position `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` > `len()`.
+    #[must_use]
+    pub fn rank1(&self, i: u64) -> u64 {
+        assert!(i <= self.len, "index out of bounds");
+        if i == self.len {
+            return self.ones;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let word = (i / 64) as usize;
+        let within = self.words[word] & ((1 << (i % 64)) - 1);
+        self.superblocks[word / WORDS_PER_SUPERBLOCK]
+            + u64::from(self.blocks[word])
+            + u64::from(within.count_ones())
+    }
+
+    /// Returns the number of unset bits before position `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` > `len()`.
+    #[inline]
+    #[must_use]
+    pub fn rank0(&self, i: u64) -> u64 {
+        i - self.rank1(i)
+    }
+
+    /// Returns the position of the `k`-th set bit, counting from zero, or `None` if there are not more than `k` set bits.
+    #[must_use]
+    pub fn select1(&self, k: u64) -> Option<u64> {
+        if k >= self.ones {
+            return None;
+        }
+        let superblock = self.superblocks.partition_point(|&rank| rank <= k) - 1;
+        let mut remaining = k - self.superblocks[superblock];
+        let start = superblock * WORDS_PER_SUPERBLOCK;
+        let end = std::cmp::min(start + WORDS_PER_SUPERBLOCK, self.words.len());
+        for (word_index, &word) in self.words[start..end].iter().enumerate() {
+            let count = u64::from(word.count_ones());
+            if remaining < count {
+                #[allow(clippy::cast_possible_truncation)]
+                let bit = select_in_word(word, remaining as u32);
+                return Some((start + word_index) as u64 * 64 + u64::from(bit));
+            }
+            remaining -= count;
+        }
+        unreachable!("rank index is inconsistent")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BERankSelect, LERankSelect};
+
+    fn naive_rank(bits: &[bool], i: usize) -> u64 {
+        bits[..i].iter().filter(|&&b| b).count() as u64
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn matches_naive() {
+        let data: Vec<u8> = (0..300u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect();
+        let len = 2389;
+        let index = LERankSelect::new(&data, len);
+        let bits: Vec<bool> = (0..len as usize)
+            .map(|i| data[i / 8] >> (i % 8) & 1 != 0)
+            .collect();
+        let mut ones = vec![];
+        for (i, &bit) in bits.iter().enumerate() {
+            assert_eq!(index.get(i as u64), bit);
+            assert_eq!(index.rank1(i as u64), naive_rank(&bits, i));
+            if bit {
+                ones.push(i as u64);
+            }
+        }
+        assert_eq!(index.rank1(len), ones.len() as u64);
+        for (k, &pos) in ones.iter().enumerate() {
+            assert_eq!(index.select1(k as u64), Some(pos));
+        }
+        assert_eq!(index.select1(ones.len() as u64), None);
+    }
+
+    #[test]
+    fn bit_order() {
+        let be = BERankSelect::new(b"\x01", 8);
+        let le = LERankSelect::new(b"\x01", 8);
+        assert_eq!(be.select1(0), Some(7));
+        assert_eq!(le.select1(0), Some(0));
+    }
+
+    #[test]
+    fn ignores_bits_past_len() {
+        let index = BERankSelect::new(b"\xff\xff", 12);
+        assert_eq!(index.count_ones(), 12);
+        assert_eq!(index.rank0(12), 0);
+    }
+
+    #[test]
+    fn empty() {
+        let index = BERankSelect::new(b"", 0);
+        assert!(index.is_empty());
+        assert_eq!(index.rank1(0), 0);
+        assert_eq!(index.select1(0), None);
+    }
+}
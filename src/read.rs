@@ -1,5 +1,8 @@
-use std::io::Read;
+use std::collections::VecDeque;
 use std::io::Result as Res;
+use std::io::{Chain, Read};
+use std::io::{Error, ErrorKind};
+use std::ops::{Deref, DerefMut};
 
 use crate::endian::{BE, BitEndianness, LE};
 
@@ -8,6 +11,78 @@ pub type BEBitReader<R> = BitReader<BE, R>;
 /// Reads least significant bits first.
 pub type LEBitReader<R> = BitReader<LE, R>;
 
+/// Wraps any reader into a `BitReader` by method call, for building chains of readers.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::BitReaderExt;
+/// use std::io::BufReader;
+///
+/// let mut reader = BufReader::new(&b"\x12"[..]).be_bits();
+/// assert_eq!(reader.read_bits(4).unwrap(), 1);
+/// ```
+pub trait BitReaderExt: Read + Sized {
+    /// Wraps this reader into a `BitReader` reading most significant bits first.
+    #[inline]
+    fn be_bits(self) -> BEBitReader<Self> {
+        BitReader::new(self)
+    }
+
+    /// Wraps this reader into a `BitReader` reading least significant bits first.
+    #[inline]
+    fn le_bits(self) -> LEBitReader<Self> {
+        BitReader::new(self)
+    }
+}
+
+impl<R: Read> BitReaderExt for R {}
+
+/// The unread bits of a partially read byte, returned by `BitReader::into_parts`.
+///
+/// The value holds the bits as `read_bits(len)` would return them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PartialByte {
+    value: u8,
+    len: u8,
+}
+
+impl PartialByte {
+    /// Creates a partial byte of the lowest `len` bits of `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` >= 8.
+    #[must_use]
+    pub fn new(value: u8, len: u8) -> Self {
+        assert!(len < 8, "a partial byte has less than 8 bits");
+        let value = if len == 0 {
+            0
+        } else {
+            value & (0xff >> (8 - len))
+        };
+        Self { value, len }
+    }
+
+    /// Returns the unread bits.
+    #[must_use]
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+
+    /// Returns the number of unread bits.
+    #[must_use]
+    pub fn len(&self) -> u8 {
+        self.len
+    }
+
+    /// Returns whether there are no unread bits, which means the reader was aligned.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
 /// Adds bit-level reading support to something implementing [`std::io::Read`].
 ///
 /// This is accomplished through an internal buffer for storing partially read bytes. Note that this buffer is for correctness, not performance - if you want to improve performance by buffering, use [`std::io::BufReader`] as the `BitReader`'s data source.
@@ -25,6 +100,16 @@ pub struct BitReader<E: BitEndianness, R: Read> {
     bit_offset: u8,
     /// Storage for remaining bits after an unaligned read operation.
     bit_buffer: u8,
+    /// Number of bits consumed since construction.
+    position: u64,
+    /// Bytes given back by a rolled back transaction, read before `inner`.
+    replay: VecDeque<u8>,
+    /// Bytes consumed during the outermost active transaction.
+    journal: Vec<u8>,
+    /// Number of active transactions.
+    depth: usize,
+    /// Position that reads can't go past, set by `read_scope`.
+    limit: Option<u64>,
     phantom: std::marker::PhantomData<E>,
 }
 
@@ -49,23 +134,184 @@ impl<E: BitEndianness, R: Read> BitReader<E, R> {
             inner,
             bit_offset: 0,
             bit_buffer: 0,
+            position: 0,
+            replay: VecDeque::new(),
+            journal: vec![],
+            depth: 0,
+            limit: None,
             phantom: std::marker::PhantomData,
         }
     }
 
+    /// Creates a new `BitReader` resuming in the middle of a byte, with the parts returned by `into_parts`.
+    ///
+    /// The bits of `partial` are read first, then the data of `inner`. With an empty partial byte, this is the same as `new`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{BEBitReader, PartialByte};
+    ///
+    /// let mut reader = BEBitReader::new(&b"\x12\x34"[..]);
+    /// reader.read_bits(4).unwrap();
+    /// let (inner, partial) = reader.into_parts();
+    /// assert_eq!(partial, PartialByte::new(0x02, 4));
+    /// let mut reader = BEBitReader::from_parts(inner, partial);
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x23);
+    /// ```
+    pub fn from_parts(inner: R, partial: PartialByte) -> Self {
+        let mut reader = Self::new(inner);
+        if !partial.is_empty() {
+            let start = 8 - partial.len;
+            let bits = E::align_right(partial.value << start, partial.len);
+            reader.bit_buffer = E::shift_lsb(bits, start);
+            reader.bit_offset = start;
+        }
+        reader
+    }
+
     /// Returns whether the reader is aligned to the byte boundary.
-    #[inline(always)]
+    #[inline]
     pub fn is_aligned(&self) -> bool {
         self.bit_offset == 0
     }
 
+    /// Returns the offset of the next bit to be read within the current byte, which is 0 if the reader is aligned.
+    #[inline]
+    pub fn bit_offset(&self) -> u8 {
+        self.bit_offset
+    }
+
+    /// Returns the number of bits left to read until the reader is aligned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12"[..]);
+    /// assert_eq!(reader.bits_until_aligned(), 0);
+    /// reader.read_bits(3).unwrap();
+    /// assert_eq!(reader.bit_offset(), 3);
+    /// assert_eq!(reader.bits_until_aligned(), 5);
+    /// ```
+    #[inline]
+    pub fn bits_until_aligned(&self) -> u8 {
+        (8 - self.bit_offset) % 8
+    }
+
+    /// Returns the number of unread bits of the current byte and their value, as `read_bits` with that count would return it.
+    ///
+    /// This is useful for debugging, the bits stay in the reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12"[..]);
+    /// reader.read_bits(3).unwrap();
+    /// assert_eq!(reader.buffered_bits(), (5, 0x12));
+    /// ```
+    pub fn buffered_bits(&self) -> (u8, u8) {
+        let count = self.bits_until_aligned();
+        if count == 0 {
+            return (0, 0);
+        }
+        let bits = E::shift_lsb(E::shift_msb(self.bit_buffer, self.bit_offset), 8 - count);
+        (count, E::align_right(bits, count))
+    }
+
+    /// Returns the number of bits consumed since construction.
+    ///
+    /// This counts the bits read from the `BitReader`, including the bits skipped by `align`, and doesn't need the underlying reader to implement `Seek`. Rolled back `attempt`s and `unread_bits` move it back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12\x34"[..]);
+    /// reader.read_bits(3).unwrap();
+    /// assert_eq!(reader.bit_position(), 3);
+    /// reader.align();
+    /// assert_eq!(reader.bit_position(), 8);
+    /// ```
+    #[inline]
+    pub fn bit_position(&self) -> u64 {
+        self.position
+    }
+
     /// Aligns to byte boundary, discarding a partial byte if the `BitReader` was not aligned.
     #[inline]
     pub fn align(&mut self) {
+        if !self.is_aligned() {
+            self.position += u64::from(8 - self.bit_offset);
+        }
         self.bit_offset = 0;
         self.bit_buffer = 0;
     }
 
+    /// Aligns to byte boundary like `align`, checking that the discarded bits are all zero.
+    ///
+    /// The partial byte is discarded even if the check fails, so the reader stays in sync with the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] mentioning the bit offset and the value of the padding if any of the discarded bits are set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x80\x81"[..]);
+    /// reader.read_bit().unwrap();
+    /// reader.align_checked().unwrap();
+    /// reader.read_bit().unwrap();
+    /// let err = reader.align_checked().unwrap_err();
+    /// assert_eq!(err.to_string(), "alignment padding of 7 bits at bit offset 9 is not zero: 0000001");
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn align_checked(&mut self) -> Res<()> {
+        let start = self.position;
+        let (count, value) = self.buffered_bits();
+        self.align();
+        if value != 0 {
+            let width = usize::from(count);
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "alignment padding of {count} bits at bit offset {start} is not zero: {value:0width$b}"
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Aligns to a multiple of `n` bits, discarding the bits up to it.
+    ///
+    /// The boundary is relative to where the reader started, as counted by `bit_position`. With `n` = 8 and a reader created at the start of a byte, this is the same as `align`, but reads the padding, so it fails at the end of the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x80\x00\x00\x00\x12"[..]);
+    /// reader.read_bit().unwrap();
+    /// reader.align_to(32).unwrap();
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x12);
+    /// ```
+    pub fn align_to(&mut self, n: u64) -> Res<()> {
+        assert!(n != 0, "alignment must not be 0");
+        let rem = self.position % n;
+        if rem == 0 {
+            return Ok(());
+        }
+        self.skip_bits(n - rem)
+    }
+
     /// Gets a reference to the underlying reader.
     ///
     /// ```compile_fail
@@ -85,6 +331,8 @@ impl<E: BitEndianness, R: Read> BitReader<E, R> {
     ///
     /// Mutable operations on the underlying reader will corrupt this `BitReader` if it is not aligned, so the reference is only returned if the `BitReader` is aligned.
     ///
+    /// # Panics
+    ///
     /// Panics if the `BitReader` is not aligned.
     #[inline]
     pub fn get_mut(&mut self) -> &mut R {
@@ -92,119 +340,1349 @@ impl<E: BitEndianness, R: Read> BitReader<E, R> {
         &mut self.inner
     }
 
-    /// Gets a mutable reference to the underlying reader.
-    ///
-    /// Use with care: Any reading/seeking/etc operation on the underlying reader will corrupt this `BitReader` if it is not aligned.
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// # Safety
+    ///
+    /// Any reading/seeking/etc operation on the underlying reader will corrupt this `BitReader` if it is not aligned.
+    #[inline]
+    pub unsafe fn get_mut_unchecked(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwraps this `BitReader`, returning the underlying reader.
+    ///
+    /// Note that any partially read byte is lost, as are bytes given back by a rolled back `attempt`. Use `into_parts` to keep the partially read byte.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Unwraps this `BitReader`, returning the underlying reader and the unread bits of the partially read byte.
+    ///
+    /// The reader can be reconstructed with `from_parts`. Like with `into_inner`, bytes given back by a rolled back `attempt` are lost.
+    pub fn into_parts(self) -> (R, PartialByte) {
+        let (len, value) = self.buffered_bits();
+        (self.inner, PartialByte::new(value, len))
+    }
+
+    /// Continues reading from `next` once the underlying reader has ended.
+    ///
+    /// Unlike calling [`Read::chain`] on the underlying reader, this keeps the partially read byte and the bit position, so a stream split across several sources can be read as if it was a single one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12"[..]);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 1);
+    /// let mut reader = reader.chain(&b"\x34"[..]);
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x23);
+    /// assert_eq!(reader.bit_position(), 12);
+    /// ```
+    ///
+    /// [`Read::chain`]: https://doc.rust-lang.org/std/io/trait.Read.html#method.chain
+    pub fn chain<R2: Read>(self, next: R2) -> BitReader<E, Chain<R, R2>> {
+        BitReader {
+            inner: self.inner.chain(next),
+            bit_offset: self.bit_offset,
+            bit_buffer: self.bit_buffer,
+            position: self.position,
+            replay: self.replay,
+            journal: self.journal,
+            depth: self.depth,
+            limit: self.limit,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Gets a mutable reference to the underlying reader, for operations that leave its position unchanged.
+    #[inline]
+    pub(crate) fn inner_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Number of bytes given back by a rolled back `attempt` that have not been read again.
+    #[inline]
+    pub(crate) fn replayed_len(&self) -> usize {
+        self.replay.len()
+    }
+
+    /// Whether an `attempt` or a peek is active.
+    #[inline]
+    pub(crate) fn in_transaction(&self) -> bool {
+        self.depth > 0
+    }
+
+    /// Number of bits left before the end of the current scope, if there is one.
+    #[inline]
+    pub(crate) fn scope_remaining(&self) -> Option<u64> {
+        self.limit.map(|limit| limit.saturating_sub(self.position))
+    }
+
+    #[inline]
+    pub(crate) fn set_bit_position(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Discards the partial byte and the bytes given back by `attempt` after the underlying reader has been seeked, moving the position by the distance from bit `from` to bit `to` of the stream.
+    pub(crate) fn reset_after_seek(&mut self, from: u64, to: u64) {
+        self.replay.clear();
+        self.bit_offset = 0;
+        self.bit_buffer = 0;
+        self.position = if to >= from {
+            self.position + (to - from)
+        } else {
+            self.position.saturating_sub(from - to)
+        };
+    }
+
+    /// Reads bytes from the replay buffer or the underlying reader, recording them if a transaction is active.
+    fn read_source(&mut self, buf: &mut [u8]) -> Res<usize> {
+        let count_read = if self.replay.is_empty() {
+            self.inner.read(buf)?
+        } else {
+            let count = std::cmp::min(buf.len(), self.replay.len());
+            for (b, r) in buf.iter_mut().zip(self.replay.drain(..count)) {
+                *b = r;
+            }
+            count
+        };
+        if self.depth > 0 {
+            self.journal.extend_from_slice(&buf[..count_read]);
+        }
+        Ok(count_read)
+    }
+
+    /// Makes sure at least `count` bits can be read without reaching the end of the underlying reader, by moving bytes from it to the replay buffer.
+    ///
+    /// Returns false if the underlying reader ends first, keeping the bytes read so far in the replay buffer. Limits of scopes are not checked.
+    fn ensure_bits(&mut self, count: u64) -> Res<bool> {
+        let partial = if self.is_aligned() {
+            0
+        } else {
+            u64::from(8 - self.bit_offset)
+        };
+        let mut temp = [0; 8];
+        while partial + self.replay.len() as u64 * 8 < count {
+            let missing = (count - partial - self.replay.len() as u64 * 8).div_ceil(8);
+            #[allow(clippy::cast_possible_truncation)]
+            let len = std::cmp::min(missing, 8) as usize;
+            match self.inner.read(&mut temp[..len]) {
+                Ok(0) => return Ok(false),
+                Ok(count_read) => self.replay.extend(&temp[..count_read]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+
+    fn fill_buffer(&mut self) -> Res<()> {
+        if !self.try_fill_buffer()? {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "failed to fill whole buffer",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Like `fill_buffer`, but returns false instead of an error if the underlying reader is at its end.
+    fn try_fill_buffer(&mut self) -> Res<bool> {
+        let mut temp = [0; 1];
+        loop {
+            match self.read_source(&mut temp) {
+                Ok(0) => return Ok(false),
+                Ok(_) => {
+                    self.bit_buffer = temp[0];
+                    return Ok(true);
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Reads a single bit, returning true for 1, false for 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x80"[..]);
+    /// let value = reader.read_bit().unwrap();
+    /// assert_eq!(value, true);
+    /// ```
+    ///
+    /// ```
+    /// # use endio_bit::LEBitReader;
+    /// let mut reader = LEBitReader::new(&b"\x01"[..]);
+    /// let value = reader.read_bit().unwrap();
+    /// assert_eq!(value, true);
+    /// ```
+    pub fn read_bit(&mut self) -> Res<bool> {
+        self.check_limit(1)?;
+        if self.is_aligned() {
+            self.fill_buffer()?;
+        }
+        Ok(self.read_bit_filled())
+    }
+
+    /// Reads a single bit, or returns `None` if the stream ends at the current position.
+    ///
+    /// The stream can only end at a byte boundary, so if the reader is not aligned, this behaves like `read_bit`. Use this instead of matching on [`UnexpectedEof`] to read until the end of the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x80"[..]);
+    /// assert_eq!(reader.read_bit_opt().unwrap(), Some(true));
+    /// for _ in 0..7 {
+    ///     assert_eq!(reader.read_bit_opt().unwrap(), Some(false));
+    /// }
+    /// assert_eq!(reader.read_bit_opt().unwrap(), None);
+    /// ```
+    ///
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn read_bit_opt(&mut self) -> Res<Option<bool>> {
+        if self.limit == Some(self.position) {
+            return Ok(None);
+        }
+        if self.is_aligned() && !self.try_fill_buffer()? {
+            return Ok(None);
+        }
+        Ok(Some(self.read_bit_filled()))
+    }
+
+    /// Reads `N` bits as an array of flags, in the order they are read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\xa0"[..]);
+    /// let flags: [bool; 3] = reader.read_bool_array().unwrap();
+    /// assert_eq!(flags, [true, false, true]);
+    /// ```
+    pub fn read_bool_array<const N: usize>(&mut self) -> Res<[bool; N]> {
+        let mut flags = [false; N];
+        for flag in &mut flags {
+            *flag = self.read_bit()?;
+        }
+        Ok(flags)
+    }
+
+    /// Returns an error if reading `count` more bits would go past the end of the current scope.
+    fn check_limit(&self, count: u64) -> Res<()> {
+        match self.limit {
+            Some(limit) if self.position + count > limit => Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "reading {count} bits at bit offset {} goes past the end of the scope at bit offset {limit}",
+                    self.position
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads a bit from the buffer, which has to hold the current byte.
+    fn read_bit_filled(&mut self) -> bool {
+        let val = self.bit_buffer & (E::shift_lsb(E::shift_msb(0xff, 7), self.bit_offset)) != 0;
+        self.bit_offset = (self.bit_offset + 1) % 8;
+        self.position += 1;
+        val
+    }
+
+    /// Reads 8 bits or less.
+    ///
+    /// The lowest `count` bits will be filled by this, the others will be zero.
+    ///
+    /// To read wider fields, use `read_bits_u16`, `read_bits_u32` or `read_bits_u64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\xf8"[..]);
+    /// let value = reader.read_bits(5).unwrap();
+    /// assert_eq!(value, 31);
+    /// ```
+    ///
+    /// ```
+    /// # use endio_bit::LEBitReader;
+    /// let mut reader = LEBitReader::new(&b"\xf8"[..]);
+    /// let value = reader.read_bits(5).unwrap();
+    /// assert_eq!(value, 24);
+    /// ```
+    pub fn read_bits(&mut self, count: u8) -> Res<u8> {
+        assert!(count <= 8);
+        if count == 0 {
+            return Ok(0);
+        }
+        self.check_limit(count.into())?;
+        if self.is_aligned() {
+            self.fill_buffer()?;
+        }
+        self.read_bits_filled(count)
+    }
+
+    /// Reads 8 bits or less, returning an error instead of panicking if `count` is out of range.
+    ///
+    /// Use this instead of `read_bits` when the count comes from untrusted input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `count` > 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\xf8"[..]);
+    /// assert_eq!(reader.try_read_bits(5).unwrap(), 31);
+    /// assert!(reader.try_read_bits(9).is_err());
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn try_read_bits(&mut self, count: u8) -> Res<u8> {
+        if count > 8 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot read more than 8 bits at once",
+            ));
+        }
+        self.read_bits(count)
+    }
+
+    /// Reads 8 bits or less, or returns `None` if the stream ends at the current position.
+    ///
+    /// The stream can only end at a byte boundary, so if the reader is not aligned, this behaves like `read_bits`. If the stream ends in the middle of the field instead, an error of kind [`UnexpectedEof`] is returned, so truncated data is not mistaken for the end of the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12\x34"[..]);
+    /// let mut records = vec![];
+    /// while let Some(record) = reader.read_bits_opt(4).unwrap() {
+    ///     records.push(record);
+    /// }
+    /// assert_eq!(records, [1, 2, 3, 4]);
+    /// ```
+    ///
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn read_bits_opt(&mut self, count: u8) -> Res<Option<u8>> {
+        assert!(count <= 8);
+        if count == 0 {
+            return Ok(Some(0));
+        }
+        if self.limit == Some(self.position) {
+            return Ok(None);
+        }
+        self.check_limit(count.into())?;
+        if self.is_aligned() && !self.try_fill_buffer()? {
+            return Ok(None);
+        }
+        self.read_bits_filled(count).map(Some)
+    }
+
+    /// Reads 4 bits.
+    ///
+    /// This is the same as `read_bits(4)`, for formats like BCD or ADPCM where most fields are nibbles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x42"[..]);
+    /// assert_eq!(reader.read_nibble().unwrap(), 4);
+    /// assert_eq!(reader.read_nibble().unwrap(), 2);
+    /// ```
+    pub fn read_nibble(&mut self) -> Res<u8> {
+        self.read_bits(4)
+    }
+
+    /// Reads 16 bits or less.
+    ///
+    /// The lowest `count` bits will be filled by this, the others will be zero. The bits are assembled according to the bit endianness: with big endian bit numbering, the first bit read is the most significant, with little endian it is the least significant. This is the same as reading a value of `count` bits with a `read_bits` call per bit and shifting them together.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 16.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\xab\xcd"[..]);
+    /// reader.read_bits(4).unwrap();
+    /// assert_eq!(reader.read_bits_u16(12).unwrap(), 0xbcd);
+    /// ```
+    ///
+    /// ```
+    /// # use endio_bit::LEBitReader;
+    /// let mut reader = LEBitReader::new(&b"\xab\xcd"[..]);
+    /// reader.read_bits(4).unwrap();
+    /// assert_eq!(reader.read_bits_u16(12).unwrap(), 0xcda);
+    /// ```
+    pub fn read_bits_u16(&mut self, count: u8) -> Res<u16> {
+        assert!(count <= 16);
+        #[allow(clippy::cast_possible_truncation)]
+        self.read_bits_wide(count).map(|value| value as u16)
+    }
+
+    /// Reads 32 bits or less.
+    ///
+    /// See `read_bits_u16` for how the bits are assembled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12\x34\x56"[..]);
+    /// assert_eq!(reader.read_bits_u32(24).unwrap(), 0x12_3456);
+    /// ```
+    pub fn read_bits_u32(&mut self, count: u8) -> Res<u32> {
+        assert!(count <= 32);
+        #[allow(clippy::cast_possible_truncation)]
+        self.read_bits_wide(count).map(|value| value as u32)
+    }
+
+    /// Reads 64 bits or less.
+    ///
+    /// See `read_bits_u16` for how the bits are assembled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitReader;
+    /// let mut reader = LEBitReader::new(&b"\x12\x34\x56\x78\x9a"[..]);
+    /// assert_eq!(reader.read_bits_u64(40).unwrap(), 0x9a_7856_3412);
+    /// ```
+    pub fn read_bits_u64(&mut self, count: u8) -> Res<u64> {
+        self.read_bits_wide(count)
+    }
+
+    /// Reads 128 bits or less.
+    ///
+    /// See `read_bits_u16` for how the bits are assembled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 128.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let data = b"\x0f\x47\xac\x10\xb5\x8c\xc4\x37\x2a\x56\x70\xe0\x2b\x2c\x3d\x47\x90";
+    /// let mut reader = BEBitReader::new(&data[..]);
+    /// reader.read_bits(4).unwrap();
+    /// assert_eq!(reader.read_bits_u128(128).unwrap(), 0xf47a_c10b_58cc_4372_a567_0e02_b2c3_d479);
+    /// ```
+    pub fn read_bits_u128(&mut self, count: u8) -> Res<u128> {
+        assert!(count <= 128);
+        let first = std::cmp::min(count, 64);
+        let head = u128::from(self.read_bits_wide(first)?);
+        let tail = u128::from(self.read_bits_wide(count - first)?);
+        Ok(if E::LSB_FIRST {
+            head | tail << 64
+        } else {
+            head << (count - first) | tail
+        })
+    }
+
+    /// Reads 64 bits or less, assembled with the first bit read as the most significant, whatever the bit endianness of the reader.
+    ///
+    /// The same bits are read as with `read_bits_u64`, only their significance in the value can differ. With a big endian reader, this is the same as `read_bits_u64`, with a little endian reader, the value is bit-reversed. This is for fields stored in the opposite order of the rest of the stream, like the Huffman codes of DEFLATE, which are packed most significant bit first into a little endian stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitReader;
+    /// let mut reader = LEBitReader::new(&b"\x0b"[..]);
+    /// assert_eq!(reader.read_bits_be(4).unwrap(), 0x0d);
+    /// ```
+    pub fn read_bits_be(&mut self, count: u8) -> Res<u64> {
+        let value = self.read_bits_wide(count)?;
+        Ok(if E::LSB_FIRST {
+            reverse_low_bits(value, count)
+        } else {
+            value
+        })
+    }
+
+    /// Reads 64 bits or less, assembled with the first bit read as the least significant, whatever the bit endianness of the reader.
+    ///
+    /// This is the counterpart of `read_bits_be`, see there for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\xd0"[..]);
+    /// assert_eq!(reader.read_bits_le(4).unwrap(), 0x0b);
+    /// ```
+    pub fn read_bits_le(&mut self, count: u8) -> Res<u64> {
+        let value = self.read_bits_wide(count)?;
+        Ok(if E::LSB_FIRST {
+            value
+        } else {
+            reverse_low_bits(value, count)
+        })
+    }
+
+    /// Reads 8 bits or less, with the buffer holding the current byte.
+    fn read_bits_filled(&mut self, count: u8) -> Res<u8> {
+        let start = self.bit_offset;
+        let end = start + count;
+        let mut res = E::shift_msb(self.bit_buffer, start);
+        if end > 8 {
+            self.fill_buffer()?;
+            res |= E::shift_lsb(self.bit_buffer, 8 - start);
+        }
+        res = E::shift_lsb(res, 8 - count);
+        res = E::align_right(res, count);
+        self.bit_offset = end % 8;
+        self.position += u64::from(count);
+        Ok(res)
+    }
+
+    /// Reads up to 64 bits, assembled according to the bit endianness.
+    ///
+    /// With big endian bit numbering, the first bit read is the most significant, with little endian it is the least significant.
+    pub(crate) fn read_bits_wide(&mut self, count: u8) -> Res<u64> {
+        assert!(count <= 64);
+        let mut value = 0;
+        let mut len = 0;
+        while len < count {
+            let chunk = std::cmp::min(count - len, 8);
+            value = E::push_bits(value, len, self.read_bits(chunk)?, chunk);
+            len += chunk;
+        }
+        Ok(value)
+    }
+
+    /// Reads `count` bits, which must be equal to `expected`, such as a magic number or a fixed marker.
+    ///
+    /// The bits are read like `read_bits_u64(count)`. All `count` bits are consumed even if they don't match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] mentioning the bit offset of the field and the value read if it doesn't match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\xff\xf8\x12"[..]);
+    /// reader.expect_bits(0xfff, 12).unwrap();
+    /// let err = reader.expect_bits(0x0a, 4).unwrap_err();
+    /// assert_eq!(err.to_string(), "expected 0xa in 4 bits at bit offset 12, found 0x8");
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn expect_bits(&mut self, expected: u64, count: u8) -> Res<()> {
+        let start = self.position;
+        let value = self.read_bits_u64(count)?;
+        if value != expected {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "expected {expected:#x} in {count} bits at bit offset {start}, found {value:#x}"
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads a reserved field of `count` bits, which must all be zero.
+    ///
+    /// All `count` bits are consumed even if some of them are set, so the reader stays in sync with the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] mentioning the bit offset of the field if any of the bits are set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x0f"[..]);
+    /// reader.read_reserved(4).unwrap();
+    /// assert!(reader.read_reserved(4).is_err());
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_reserved(&mut self, count: u64) -> Res<()> {
+        let start = self.position;
+        if self.read_reserved_lenient(count)? {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("reserved field of {count} bits at bit offset {start} is not zero"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads `count` bits of padding, which must all be zero.
+    ///
+    /// This is like `read_reserved`, but the error describes the bits as padding.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] mentioning the bit offset of the padding if any of the bits are set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x0f"[..]);
+    /// reader.read_padding(4).unwrap();
+    /// assert!(reader.read_padding(4).is_err());
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_padding(&mut self, count: u64) -> Res<()> {
+        let start = self.position;
+        if self.read_reserved_lenient(count)? {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("padding of {count} bits at bit offset {start} is not zero"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads a reserved field of `count` bits, returning whether any of them were set instead of failing.
+    ///
+    /// This is useful for parsers that should tolerate nonconforming data but still want to log or count violations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x0f"[..]);
+    /// assert!(!reader.read_reserved_lenient(4).unwrap());
+    /// assert!(reader.read_reserved_lenient(4).unwrap());
+    /// ```
+    pub fn read_reserved_lenient(&mut self, count: u64) -> Res<bool> {
+        let mut set = false;
+        let mut remaining = count;
+        while remaining > 0 {
+            #[allow(clippy::cast_possible_truncation)]
+            let chunk = std::cmp::min(remaining, 8) as u8;
+            set |= self.read_bits(chunk)? != 0;
+            remaining -= u64::from(chunk);
+        }
+        Ok(set)
+    }
+
+    /// Discards the next `count` bits.
+    ///
+    /// Whole bytes are read from the underlying reader in bulk and discarded without shifting them, even if the reader is not aligned, so this is much faster than calling `read_bits` in a loop for long skips. This method can't seek, since choosing that by the bounds of the underlying reader would need specialization, use `skip_bits_seek` to seek over the bytes of a `Seek` reader instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`UnexpectedEof`] if the stream ends before `count` bits were skipped. The bits up to the end are consumed in this case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut data = vec![0; 1000];
+    /// data.push(0xab);
+    /// let mut reader = BEBitReader::new(&data[..]);
+    /// reader.read_bits(4).unwrap();
+    /// reader.skip_bits(7996).unwrap();
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0xab);
+    /// ```
+    ///
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn skip_bits(&mut self, count: u64) -> Res<()> {
+        self.check_limit(count)?;
+        let mut bytes = count / 8;
+        let mut buf = [0; 256];
+        while bytes > 0 {
+            let len = usize::try_from(bytes).map_or(buf.len(), |b| b.min(buf.len()));
+            match self.read_source(&mut buf[..len]) {
+                Ok(0) => {
+                    return Err(Error::new(ErrorKind::UnexpectedEof, "failed to skip bits"));
+                }
+                Ok(count_read) => {
+                    // the last byte holds the remaining bits of the partially read byte
+                    if !self.is_aligned() {
+                        self.bit_buffer = buf[count_read - 1];
+                    }
+                    bytes -= count_read as u64;
+                    self.position += 8 * count_read as u64;
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        self.read_bits((count % 8) as u8)?;
+        Ok(())
+    }
+
+    /// Reads `nbits` bits into the start of `buf`.
+    ///
+    /// The bits are stored the same way a `BitWriter` with the same bit endianness would write them: whole bytes first, then the remaining bits in the first bit positions of the last byte, with the other bits of the last byte set to zero. The rest of `buf` is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is shorter than `nbits` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x0a\xbc\xde\xf1\x23"[..]);
+    /// reader.read_bits(4).unwrap();
+    /// let mut buf = [0; 5];
+    /// reader.read_bits_slice(&mut buf, 29).unwrap();
+    /// assert_eq!(buf, [0xab, 0xcd, 0xef, 0x10, 0x00]);
+    /// ```
+    pub fn read_bits_slice(&mut self, buf: &mut [u8], nbits: u64) -> Res<()> {
+        assert!(
+            nbits.div_ceil(8) <= buf.len() as u64,
+            "buffer is shorter than nbits"
+        );
+        #[allow(clippy::cast_possible_truncation)]
+        let (bytes, rest) = ((nbits / 8) as usize, (nbits % 8) as u8);
+        self.read_exact(&mut buf[..bytes])?;
+        if rest > 0 {
+            let bits = self.read_bits(rest)?;
+            buf[bytes] = if E::LSB_FIRST {
+                bits
+            } else {
+                bits << (8 - rest)
+            };
+        }
+        Ok(())
+    }
+
+    /// Runs `f` as a transaction, rolling the reader back to where it was if `f` returns an error.
+    ///
+    /// The bytes consumed during the transaction are kept in memory, and given back to the reader on rollback, so this works on readers that can't seek, like sockets and pipes. Transactions can be nested, a failed inner transaction only rolls back its own reads. The memory is released when the outermost transaction ends.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by `f`, after rolling back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\xa5\x0f"[..]);
+    /// // try to parse a 4 bit tag followed by a zero reserved byte
+    /// let res = reader.attempt(|r| {
+    ///     let tag = r.read_bits(4)?;
+    ///     r.read_reserved(8)?;
+    ///     Ok(tag)
+    /// });
+    /// assert!(res.is_err());
+    /// // the reader is back at the start
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0xa5);
+    /// ```
+    pub fn attempt<T>(&mut self, f: impl FnOnce(&mut Self) -> Res<T>) -> Res<T> {
+        self.transaction(false, f)
+    }
+
+    /// Reads a single bit without consuming it.
+    ///
+    /// A byte read from the underlying reader for this is kept in memory and returned again by the next read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x80"[..]);
+    /// assert!(reader.peek_bit().unwrap());
+    /// assert!(reader.read_bit().unwrap());
+    /// assert!(!reader.peek_bit().unwrap());
+    /// ```
+    pub fn peek_bit(&mut self) -> Res<bool> {
+        self.transaction(true, Self::read_bit)
+    }
+
+    /// Reads 8 bits or less without consuming them.
+    ///
+    /// Bytes read from the underlying reader for this are kept in memory and returned again by the next reads. This allows looking ahead at the start of a prefix code before deciding how to decode it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12\x34"[..]);
+    /// reader.read_bits(4).unwrap();
+    /// assert_eq!(reader.peek_bits(8).unwrap(), 0x23);
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x23);
+    /// ```
+    pub fn peek_bits(&mut self, count: u8) -> Res<u8> {
+        self.transaction(true, |r| r.read_bits(count))
+    }
+
+    /// Pushes a single bit back onto the reader, so that the next read returns it.
+    ///
+    /// See `unread_bits` for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called inside `attempt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if no bits have been read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x80"[..]);
+    /// assert!(reader.read_bit().unwrap());
+    /// reader.unread_bit(false).unwrap();
+    /// assert!(!reader.read_bit().unwrap());
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn unread_bit(&mut self, bit: bool) -> Res<()> {
+        self.unread_bits(u8::from(bit), 1)
+    }
+
+    /// Pushes the lowest `count` bits of `value` back onto the reader, so that the next reads return them.
+    ///
+    /// The bits are taken like `BitWriter::write_bits` would write them, so unreading a value returned by `read_bits(count)` makes the next `read_bits(count)` return it again. The bits don't have to be the ones that were read. Unreading moves the position back by `count` bits, and can be repeated to push back up to as many bits as have been read, so that `bit_position` and the positions in the underlying stream stay valid. Bits pushed back are kept in memory, like the ones given back by `attempt`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8, or if called inside `attempt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `count` is larger than `bit_position`, leaving the reader unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitReader;
+    /// let mut reader = LEBitReader::new(&b"\x5a"[..]);
+    /// let tag = reader.read_bits(3).unwrap();
+    /// assert_eq!(tag, 2);
+    /// // not the expected tag, give it back
+    /// reader.unread_bits(tag, 3).unwrap();
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x5a);
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn unread_bits(&mut self, value: u8, count: u8) -> Res<()> {
+        assert!(count <= 8);
+        assert!(self.depth == 0, "cannot unread bits inside a transaction");
+        if u64::from(count) > self.position {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "can't unread {count} bits at bit position {}, before the start of the reader",
+                    self.position
+                ),
+            ));
+        }
+        if count == 0 {
+            return Ok(());
+        }
+        // the pushed back bits followed by the rest of the current byte, in stream order, first bit highest
+        let mut bits = 0u32;
+        for i in 0..count {
+            let shift = if E::LSB_FIRST { i } else { count - 1 - i };
+            bits = bits << 1 | u32::from(value >> shift & 1);
+        }
+        let rest = if self.is_aligned() {
+            0
+        } else {
+            8 - self.bit_offset
+        };
+        for p in self.bit_offset..self.bit_offset + rest {
+            let bit = self.bit_buffer & E::shift_lsb(E::shift_msb(0xff, 7), p) != 0;
+            bits = bits << 1 | u32::from(bit);
+        }
+        let len = count + rest;
+        let pad = (8 - len % 8) % 8;
+        let mut bytes = [0; 2];
+        for i in 0..len {
+            if bits >> (len - 1 - i) & 1 != 0 {
+                let p = pad + i;
+                bytes[usize::from(p / 8)] |= E::shift_lsb(E::shift_msb(0xff, 7), p % 8);
+            }
+        }
+        let bytes = &bytes[..usize::from((pad + len) / 8)];
+        let whole = if pad == 0 {
+            self.bit_buffer = 0;
+            bytes
+        } else {
+            self.bit_buffer = bytes[0];
+            &bytes[1..]
+        };
+        for &b in whole.iter().rev() {
+            self.replay.push_front(b);
+        }
+        self.bit_offset = pad;
+        self.position -= u64::from(count);
+        Ok(())
+    }
+
+    /// Runs `f`, rolling the reader back to where it was if `f` returns an error, or always if `rollback` is set.
+    fn transaction<T>(&mut self, rollback: bool, f: impl FnOnce(&mut Self) -> Res<T>) -> Res<T> {
+        let (bit_offset, bit_buffer, position) = (self.bit_offset, self.bit_buffer, self.position);
+        let mark = self.journal.len();
+        self.depth += 1;
+        let res = f(self);
+        self.depth -= 1;
+        if rollback || res.is_err() {
+            for b in self.journal.drain(mark..).rev() {
+                self.replay.push_front(b);
+            }
+            self.bit_offset = bit_offset;
+            self.bit_buffer = bit_buffer;
+            self.position = position;
+        }
+        if self.depth == 0 {
+            self.journal.clear();
+        }
+        res
+    }
+
+    /// Runs `f` with reads restricted to the next `bit_len` bits, and checks that it consumed exactly these bits.
+    ///
+    /// Inside the scope, the reader behaves as if the stream ended after `bit_len` bits: reading past the end fails with [`UnexpectedEof`], `read_bit_opt` and `read_bits_opt` return `None` at the end even if it is not at a byte boundary, and the `Read` implementation only returns whole bytes within the scope. Scopes can be nested. Use this for size-prefixed nested structures, to catch disagreements between the parser and the format where they happen.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by `f`. Returns an error of kind [`InvalidData`] if `f` consumed less than `bit_len` bits, or more by aligning, or if the scope extends past the end of an enclosing scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// // a 4 bit length in bytes, followed by a payload of that length
+    /// let mut reader = BEBitReader::new(&b"\x11\x20"[..]);
+    /// let len = reader.read_bits(4).unwrap();
+    /// let res = reader.read_scope(u64::from(len) * 8, |r| r.read_bits(4));
+    /// assert!(res.is_err());
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn read_scope<T>(&mut self, bit_len: u64, f: impl FnOnce(&mut Self) -> Res<T>) -> Res<T> {
+        self.scope(bit_len, false, f)
+    }
+
+    /// Like `read_scope`, but skips the bits `f` left unread instead of failing.
+    ///
+    /// This is useful for formats whose newer versions may append fields to a structure.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by `f`. Returns an error of kind [`InvalidData`] if `f` consumed more than `bit_len` bits by aligning, or if the scope extends past the end of an enclosing scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12\x34"[..]);
+    /// let value = reader.read_scope_skipping(12, |r| r.read_bits(4)).unwrap();
+    /// assert_eq!(value, 1);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 4);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_scope_skipping<T>(
+        &mut self,
+        bit_len: u64,
+        f: impl FnOnce(&mut Self) -> Res<T>,
+    ) -> Res<T> {
+        self.scope(bit_len, true, f)
+    }
+
+    fn scope<T>(
+        &mut self,
+        bit_len: u64,
+        skip: bool,
+        f: impl FnOnce(&mut Self) -> Res<T>,
+    ) -> Res<T> {
+        let start = self.position;
+        let end = start.saturating_add(bit_len);
+        let outer = self.limit;
+        if let Some(outer) = outer
+            && end > outer
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "scope of {bit_len} bits at bit offset {start} goes past the end of the enclosing scope at bit offset {outer}"
+                ),
+            ));
+        }
+        self.limit = Some(end);
+        let res = f(self);
+        let res = res.and_then(|value| {
+            if skip && self.position < end {
+                self.read_reserved_lenient(end - self.position)?;
+            }
+            if self.position == end {
+                return Ok(value);
+            }
+            Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "scope of {bit_len} bits at bit offset {start} consumed {} bits",
+                    self.position - start
+                ),
+            ))
+        });
+        self.limit = outer;
+        res
+    }
+}
+
+/// Reverses the order of the lowest `count` bits of `value`.
+pub(crate) fn reverse_low_bits(value: u64, count: u8) -> u64 {
+    if count == 0 {
+        0
+    } else {
+        value.reverse_bits() >> (64 - count)
+    }
+}
+
+/// Read bytes from a `BitReader` just like from [`Read`], but with bit shifting support for unaligned reads.
+///
+/// Directly maps to [`Read`] for aligned reads. As for any reader, `&mut BitReader` implements [`Read`] too, so a reader can be passed to functions taking `impl Read` without moving it.
+///
+/// [`Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
+impl<E: BitEndianness, R: Read> Read for BitReader<E, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Res<usize> {
+        let buf = match self.limit {
+            Some(limit) => {
+                let allowed = limit.saturating_sub(self.position) / 8;
+                let len = usize::try_from(allowed).map_or(buf.len(), |a| a.min(buf.len()));
+                &mut buf[..len]
+            }
+            None => buf,
+        };
+        let count_read = self.read_source(buf)?;
+        if self.is_aligned() {
+            self.position += 8 * count_read as u64;
+            return Ok(count_read);
+        }
+        let mut last_byte = self.bit_buffer;
+        for b in &mut buf[..count_read] {
+            let current_byte = *b;
+            *b = E::shift_msb(last_byte, self.bit_offset)
+                | E::shift_lsb(current_byte, 8 - self.bit_offset);
+            last_byte = current_byte;
+        }
+        self.bit_buffer = last_byte;
+        self.position += 8 * count_read as u64;
+        Ok(count_read)
+    }
+}
+
+/// An iterator over the bits of a `BitReader`.
+///
+/// This struct is created by `BitReader::bits`.
+pub struct Bits<'a, E: BitEndianness, R: Read> {
+    reader: &'a mut BitReader<E, R>,
+}
+
+impl<E: BitEndianness, R: Read> Iterator for Bits<'_, E, R> {
+    type Item = Res<bool>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_bit_opt().transpose()
+    }
+}
+
+/// An iterator over fixed-width chunks of a `BitReader`.
+///
+/// This struct is created by `BitReader::chunks_bits`.
+pub struct ChunksBits<'a, E: BitEndianness, R: Read> {
+    reader: &'a mut BitReader<E, R>,
+    count: u8,
+    done: bool,
+}
+
+impl<E: BitEndianness, R: Read> Iterator for ChunksBits<'_, E, R> {
+    type Item = Res<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let count = u64::from(self.count);
+        let available = match self.reader.scope_remaining() {
+            Some(remaining) if remaining < count => Ok(false),
+            _ => self.reader.ensure_bits(count),
+        };
+        match available {
+            Ok(true) => Some(self.reader.read_bits_u64(self.count)),
+            Ok(false) => {
+                self.done = true;
+                None
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// An iterator over the nibbles of a `BitReader`, starting at its current bit offset.
+///
+/// This struct is created by `BitReader::nibbles`.
+pub struct Nibbles<'a, E: BitEndianness, R: Read> {
+    reader: &'a mut BitReader<E, R>,
+    done: bool,
+}
+
+impl<E: BitEndianness, R: Read> Iterator for Nibbles<'_, E, R> {
+    type Item = Res<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.reader.read_nibble() {
+            Ok(nibble) => Some(Ok(nibble)),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                self.done = true;
+                None
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// A `BitReader` restricted to a budget of bits.
+///
+/// This struct is created by `BitReader::take_bits`.
+pub struct BitTake<'a, E: BitEndianness, R: Read> {
+    reader: &'a mut BitReader<E, R>,
+    /// Limit of the reader before the budget was set.
+    outer: Option<u64>,
+}
+
+impl<E: BitEndianness, R: Read> BitTake<'_, E, R> {
+    /// Returns the number of bits left in the budget.
+    #[must_use]
+    pub fn bits_remaining(&self) -> u64 {
+        self.reader
+            .limit
+            .map_or(0, |limit| limit.saturating_sub(self.reader.position))
+    }
+}
+
+impl<E: BitEndianness, R: Read> Deref for BitTake<'_, E, R> {
+    type Target = BitReader<E, R>;
+
     #[inline]
-    pub unsafe fn get_mut_unchecked(&mut self) -> &mut R {
-        &mut self.inner
+    fn deref(&self) -> &Self::Target {
+        self.reader
     }
+}
 
-    /// Unwraps this `BitReader`, returning the underlying reader.
-    ///
-    /// Note that any partially read byte is lost.
+impl<E: BitEndianness, R: Read> DerefMut for BitTake<'_, E, R> {
     #[inline]
-    pub fn into_inner(self) -> R {
-        self.inner
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.reader
     }
+}
 
-    fn fill_buffer(&mut self) -> Res<()> {
-        let mut temp = [0; 1];
-        self.inner.read_exact(&mut temp)?;
-        self.bit_buffer = temp[0];
-        Ok(())
+/// Read whole bytes within the budget.
+impl<E: BitEndianness, R: Read> Read for BitTake<'_, E, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Res<usize> {
+        self.reader.read(buf)
     }
+}
 
-    /// Reads a single bit, returning true for 1, false for 0.
+impl<E: BitEndianness, R: Read> Drop for BitTake<'_, E, R> {
+    fn drop(&mut self) {
+        self.reader.limit = self.outer;
+    }
+}
+
+/// An iterator over the bytes of a `BitReader`, reconstructed at its current bit offset.
+///
+/// This struct is created by `BitReader::shifted_bytes`.
+pub struct ShiftedBytes<'a, E: BitEndianness, R: Read> {
+    reader: &'a mut BitReader<E, R>,
+    done: bool,
+}
+
+impl<E: BitEndianness, R: Read> Iterator for ShiftedBytes<'_, E, R> {
+    type Item = Res<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.reader.read_bits(8) {
+            Ok(byte) => Some(Ok(byte)),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                self.done = true;
+                None
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl<E: BitEndianness, R: Read> BitReader<E, R> {
+    /// Returns an iterator over the remaining bytes, each made up of the next 8 bits at the current bit offset.
+    ///
+    /// This yields the same bytes as the `Read` implementation, but one at a time as they are pulled from the iterator. Iteration stops when less than 8 bits are left, these bits stay in the reader.
     ///
     /// # Examples
     ///
     /// ```
     /// # use endio_bit::BEBitReader;
-    /// let mut reader = BEBitReader::new(&b"\x80"[..]);
-    /// let value = reader.read_bit().unwrap();
-    /// assert_eq!(value, true);
+    /// let mut reader = BEBitReader::new(&b"\xaa\x8c\xae\x6e\x80"[..]);
+    /// reader.read_bits(3).unwrap();
+    /// let bytes: Vec<u8> = reader.shifted_bytes().collect::<Result<_, _>>().unwrap();
+    /// assert_eq!(bytes, b"Test");
     /// ```
+    pub fn shifted_bytes(&mut self) -> ShiftedBytes<'_, E, R> {
+        ShiftedBytes {
+            reader: self,
+            done: false,
+        }
+    }
+
+    /// Returns an iterator over the remaining bits, like [`Read::bytes`] for bytes.
+    ///
+    /// Iteration stops where `read_bit_opt` returns `None`.
+    ///
+    /// # Examples
     ///
     /// ```
-    /// # use endio_bit::LEBitReader;
-    /// let mut reader = LEBitReader::new(&b"\x01"[..]);
-    /// let value = reader.read_bit().unwrap();
-    /// assert_eq!(value, true);
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x81"[..]);
+    /// reader.read_bits(4).unwrap();
+    /// let set = reader.bits().filter(|bit| matches!(bit, Ok(true))).count();
+    /// assert_eq!(set, 1);
     /// ```
-    pub fn read_bit(&mut self) -> Res<bool> {
-        if self.is_aligned() {
-            self.fill_buffer()?;
-        }
-        let val = self.bit_buffer & (E::shift_lsb(E::shift_msb(0xff, 7), self.bit_offset)) != 0;
-        self.bit_offset = (self.bit_offset + 1) % 8;
-        Ok(val)
+    ///
+    /// [`Read::bytes`]: https://doc.rust-lang.org/std/io/trait.Read.html#method.bytes
+    pub fn bits(&mut self) -> Bits<'_, E, R> {
+        Bits { reader: self }
     }
 
-    /// Reads 8 bits or less.
-    ///
-    /// The lowest `count` bits will be filled by this, the others will be zero.
+    /// Returns an iterator over the remaining chunks of `count` bits, each read with `read_bits_u64`.
     ///
-    /// Reading more than 8 bits is intentionally not supported to keep the interface simple. Reading more can be accomplished by reading bytes and then reading any leftover bits.
+    /// Iteration stops when less than `count` bits are left, these bits stay in the reader, so trailing padding can be checked or skipped afterwards.
     ///
     /// # Panics
     ///
-    /// Panics if `count` > 8.
+    /// Panics if `count` is 0 or > 64.
     ///
     /// # Examples
     ///
     /// ```
     /// # use endio_bit::BEBitReader;
-    /// let mut reader = BEBitReader::new(&b"\xf8"[..]);
-    /// let value = reader.read_bits(5).unwrap();
-    /// assert_eq!(value, 31);
+    /// let mut reader = BEBitReader::new(&b"\x29\xc0"[..]);
+    /// let values: Vec<u64> = reader.chunks_bits(3).collect::<Result<_, _>>().unwrap();
+    /// assert_eq!(values, [1, 2, 3, 4, 0]);
+    /// assert_eq!(reader.read_bits(1).unwrap(), 0);
     /// ```
+    pub fn chunks_bits(&mut self, count: u8) -> ChunksBits<'_, E, R> {
+        assert!(
+            (1..=64).contains(&count),
+            "chunk width must be between 1 and 64"
+        );
+        ChunksBits {
+            reader: self,
+            count,
+            done: false,
+        }
+    }
+
+    /// Returns an iterator over the remaining nibbles, each read with `read_nibble`.
+    ///
+    /// Iteration stops when less than 4 bits are left, these bits stay in the reader.
+    ///
+    /// # Examples
     ///
     /// ```
     /// # use endio_bit::LEBitReader;
-    /// let mut reader = LEBitReader::new(&b"\xf8"[..]);
-    /// let value = reader.read_bits(5).unwrap();
-    /// assert_eq!(value, 24);
+    /// let mut reader = LEBitReader::new(&b"\x21\x43"[..]);
+    /// let nibbles: Vec<u8> = reader.nibbles().collect::<Result<_, _>>().unwrap();
+    /// assert_eq!(nibbles, [1, 2, 3, 4]);
     /// ```
-    pub fn read_bits(&mut self, count: u8) -> Res<u8> {
-        assert!(count <= 8);
-        if self.is_aligned() {
-            self.fill_buffer()?;
-        }
-        let start = self.bit_offset;
-        let end = start + count;
-        let mut res = E::shift_msb(self.bit_buffer, start);
-        if end > 8 {
-            self.fill_buffer()?;
-            res |= E::shift_lsb(self.bit_buffer, 8 - start);
+    pub fn nibbles(&mut self) -> Nibbles<'_, E, R> {
+        Nibbles {
+            reader: self,
+            done: false,
         }
-        res = E::shift_lsb(res, 8 - count);
-        res = E::align_right(res, count);
-        self.bit_offset = end % 8;
-        Ok(res)
     }
-}
 
-/// Read bytes from a `BitReader` just like from [`Read`], but with bit shifting support for unaligned reads.
-///
-/// Directly maps to [`Read`] for aligned reads.
-///
-/// [`Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
-impl<E: BitEndianness, R: Read> Read for BitReader<E, R> {
-    fn read(&mut self, buf: &mut [u8]) -> Res<usize> {
-        let count_read = self.inner.read(buf)?;
-        if self.is_aligned() {
-            return Ok(count_read);
-        }
-        let mut last_byte = self.bit_buffer;
-        for b in buf.iter_mut() {
-            let current_byte = *b;
-            *b = E::shift_msb(last_byte, self.bit_offset)
-                | E::shift_lsb(current_byte, 8 - self.bit_offset);
-            last_byte = current_byte;
+    /// Returns an adapter that reads at most `bit_len` more bits from this reader.
+    ///
+    /// The adapter dereferences to the reader, and reading through it behaves as in a `read_scope`: reads past the budget fail with [`UnexpectedEof`], and `read_bit_opt` and `read_bits_opt` return `None` at its end. Unlike `read_scope`, leaving bits of the budget unread is not an error. The budget is also bounded by an enclosing scope. The reader is unrestricted again once the adapter is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"4"[..]);
+    /// let mut take = reader.take_bits(6);
+    /// assert_eq!(take.read_bits(4).unwrap(), 1);
+    /// assert_eq!(take.bits_remaining(), 2);
+    /// assert!(take.read_bits(4).is_err());
+    /// drop(take);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 2);
+    /// ```
+    ///
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn take_bits(&mut self, bit_len: u64) -> BitTake<'_, E, R> {
+        let outer = self.limit;
+        let end = self.position.saturating_add(bit_len);
+        self.limit = Some(outer.map_or(end, |outer| outer.min(end)));
+        BitTake {
+            reader: self,
+            outer,
         }
-        self.bit_buffer = last_byte;
-        Ok(count_read)
     }
 }
 
@@ -213,6 +1691,144 @@ mod tests_common {
     use crate::BEBitReader;
     use std::io::Read;
 
+    #[test]
+    fn read_opt_clean_end() {
+        let mut reader = BEBitReader::new(&b"\xff"[..]);
+        assert_eq!(reader.read_bits_opt(5).unwrap(), Some(0x1f));
+        assert_eq!(reader.read_bit_opt().unwrap(), Some(true));
+        assert_eq!(reader.read_bits_opt(2).unwrap(), Some(3));
+        assert_eq!(reader.read_bits_opt(2).unwrap(), None);
+        assert_eq!(reader.read_bit_opt().unwrap(), None);
+    }
+
+    #[test]
+    fn read_opt_truncated() {
+        let mut reader = BEBitReader::new(&b"\xff"[..]);
+        reader.read_bits(6).unwrap();
+        let err = reader.read_bits_opt(4).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn attempt_nested() {
+        let mut reader = BEBitReader::new(&b"\x12\x34\x56\x78"[..]);
+        reader.read_bits(4).unwrap();
+        let res = reader.attempt(|r| {
+            assert_eq!(r.read_bits(8).unwrap(), 0x23);
+            let inner = r.attempt(|r| {
+                r.read_bits(8)?;
+                r.read_reserved(8)
+            });
+            assert!(inner.is_err());
+            let mut buf = [0; 2];
+            r.read_exact(&mut buf)?;
+            assert_eq!(buf, [0x45, 0x67]);
+            Ok(())
+        });
+        assert!(res.is_ok());
+        assert_eq!(reader.replayed_len(), 0);
+        assert_eq!(reader.bit_position(), 28);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x8);
+    }
+
+    #[test]
+    fn read_scope_nested() {
+        let mut reader = BEBitReader::new(&b"\x12\x34\x56\x78"[..]);
+        let res = reader.read_scope(20, |r| {
+            assert_eq!(r.read_bits(6)?, 0x04);
+            let inner = r.read_scope(4, |r| r.read_bits(2)).unwrap_err();
+            assert_eq!(inner.kind(), std::io::ErrorKind::InvalidData);
+            r.read_scope_skipping(4, |_| Ok(()))?;
+            let outer = r.read_scope(100, |_| Ok(())).unwrap_err();
+            assert_eq!(outer.kind(), std::io::ErrorKind::InvalidData);
+            assert_eq!(r.read_bits_opt(6)?, Some(0x11));
+            // only 2 bits are left in the scope
+            let eof = r.read_bits_opt(4).unwrap_err();
+            assert_eq!(eof.kind(), std::io::ErrorKind::UnexpectedEof);
+            r.read_bits(2)?;
+            r.read_bits_opt(1)
+        });
+        assert_eq!(res.unwrap(), None);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x6);
+    }
+
+    #[test]
+    fn read_scope_bytes() {
+        let mut reader = BEBitReader::new(&b"\x12\x34\x56\x78"[..]);
+        reader.read_bits(4).unwrap();
+        let bytes = reader
+            .read_scope(20, |r| {
+                let mut bytes = vec![];
+                r.read_to_end(&mut bytes)?;
+                r.read_bits(4)?;
+                Ok(bytes)
+            })
+            .unwrap();
+        assert_eq!(bytes, b"\x23\x45");
+        assert_eq!(reader.read_bits(8).unwrap(), 0x78);
+    }
+
+    #[test]
+    fn read_scope_align_past_end() {
+        let mut reader = BEBitReader::new(&b"\x12\x34"[..]);
+        let err = reader
+            .read_scope(4, |r| {
+                r.read_bit()?;
+                r.align();
+                Ok(())
+            })
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(reader.read_bits(8).unwrap(), 0x34);
+    }
+
+    #[test]
+    fn chain() {
+        let mut reader = BEBitReader::new(&b"\xab"[..]);
+        reader.read_bits(3).unwrap();
+        assert!(reader.attempt(|r| r.read_bits(8)).is_err());
+        let mut reader = reader.chain(&b"\xcd"[..]).chain(&b"\xef"[..]);
+        assert_eq!(reader.read_bits(5).unwrap(), 0x0b);
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, *b"\xcd\xef");
+        assert_eq!(reader.read_bit_opt().unwrap(), None);
+    }
+
+    #[test]
+    fn take_bits() {
+        let mut reader = BEBitReader::new(&b"\x12\x34\x56\x78"[..]);
+        reader
+            .read_scope(20, |r| {
+                let mut take = r.take_bits(100);
+                assert_eq!(take.bits_remaining(), 20);
+                let mut buf = [0; 4];
+                assert_eq!(take.read(&mut buf)?, 2);
+                assert_eq!(buf[..2], *b"\x12\x34");
+                assert_eq!(take.read_bits_opt(4)?, Some(5));
+                assert_eq!(take.read_bit_opt()?, None);
+                Ok(())
+            })
+            .unwrap();
+        let mut take = reader.take_bits(0);
+        assert_eq!(take.read_bit_opt().unwrap(), None);
+        drop(take);
+        assert_eq!(reader.read_bits(4).unwrap(), 6);
+    }
+
+    #[test]
+    fn attempt_rollback_to_eof() {
+        let mut reader = BEBitReader::new(&b"\xab\xcd"[..]);
+        let err = reader.attempt(|r| r.read_bits_wide(24)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.bit_position(), 0);
+        assert_eq!(reader.replayed_len(), 2);
+        let mut buf = vec![];
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, b"\xab\xcd");
+        assert_eq!(reader.read_bit_opt().unwrap(), None);
+    }
+
     #[test]
     fn get_ref() {
         let reader = BEBitReader::new(&b"\xf8"[..]);
@@ -225,12 +1841,12 @@ mod tests_common {
         let mut reader = BEBitReader::new(&b"\xf8"[..]);
         let inner = reader.get_mut();
         let mut buf = [0; 1];
-        inner.read(&mut buf).unwrap();
+        inner.read_exact(&mut buf).unwrap();
         assert_eq!(buf[0], 0xf8);
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "BitReader is not aligned")]
     fn get_mut_unaligned() {
         let data = &b"\xff"[..];
         let mut reader = BEBitReader::new(data);
@@ -244,7 +1860,7 @@ mod tests_common {
         reader.read_bits(4).unwrap();
         let inner = unsafe { reader.get_mut_unchecked() };
         let mut buf = [0; 1];
-        inner.read(&mut buf).unwrap();
+        inner.read_exact(&mut buf).unwrap();
         assert_eq!(buf[0], 0xff);
     }
 
@@ -266,13 +1882,39 @@ mod tests_common {
         assert_eq!(bits, 31);
         assert!(bit);
     }
+
+    #[test]
+    fn read_reserved() {
+        let mut reader = BEBitReader::new(&b"\x00\x0f"[..]);
+        reader.read_reserved(12).unwrap();
+        let err = reader.read_reserved(4).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("bit offset 12"));
+        assert!(reader.is_aligned());
+    }
+
+    #[test]
+    fn read_reserved_lenient() {
+        let mut reader = BEBitReader::new(&b"\x01\x00"[..]);
+        assert!(reader.read_reserved_lenient(9).unwrap());
+        assert!(!reader.read_reserved_lenient(7).unwrap());
+    }
 }
 
 #[cfg(test)]
 mod tests_be {
-    use crate::BEBitReader;
+    use crate::{BEBitReader, PartialByte};
     use std::io::Read;
 
+    #[test]
+    fn read_bits_le() {
+        let mut reader = BEBitReader::new(&b"\xb5\x81"[..]);
+        assert_eq!(reader.read_bits(3).unwrap(), 0x05);
+        assert_eq!(reader.read_bits_le(10).unwrap(), 0x035);
+        assert_eq!(reader.read_bits_be(3).unwrap(), 0x01);
+        assert_eq!(reader.read_bits_le(0).unwrap(), 0);
+    }
+
     #[test]
     fn read_aligned() {
         let mut reader = BEBitReader::new(&b"Test"[..]);
@@ -298,6 +1940,178 @@ mod tests_be {
         assert_eq!(&buf, b"est\0\0\0\0");
     }
 
+    #[test]
+    fn read_shifted_short() {
+        let mut reader = BEBitReader::new((&b"\xab\xcd"[..]).chain(&b"\xef"[..]));
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0a);
+        let mut buf = [0; 3];
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 0xbc);
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 0xde);
+    }
+
+    #[test]
+    fn shifted_bytes() {
+        let mut reader = BEBitReader::new(&b"\xab\xcd\xef"[..]);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0a);
+        let bytes: Vec<u8> = reader.shifted_bytes().map(Result::unwrap).collect();
+        assert_eq!(bytes, b"\xbc\xde");
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0f);
+    }
+
+    #[test]
+    fn try_read_bits() {
+        let mut reader = BEBitReader::new(&b"\xab"[..]);
+        assert_eq!(
+            reader.try_read_bits(9).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert_eq!(reader.try_read_bits(8).unwrap(), 0xab);
+        assert_eq!(
+            reader.try_read_bits(1).unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn unread_bits() {
+        let mut reader = BEBitReader::new(&b"\xab\xcd"[..]);
+        assert_eq!(reader.read_bits(3).unwrap(), 0x05);
+        reader.unread_bits(0x05, 3).unwrap();
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0a);
+        assert_eq!(reader.read_bits(3).unwrap(), 0x05);
+        reader.unread_bits(0x07, 3).unwrap();
+        reader.unread_bits(0x01, 4).unwrap();
+        assert_eq!(reader.bit_position(), 0);
+        assert_eq!(reader.read_bits(8).unwrap(), 0x1f);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0c);
+        reader.unread_bit(true).unwrap();
+        assert!(!reader.is_aligned());
+        assert_eq!(reader.read_bits(5).unwrap(), 0x1d);
+        assert!(reader.read_bit_opt().unwrap().is_none());
+    }
+
+    #[test]
+    fn unread_bits_before_start() {
+        let mut reader = BEBitReader::new(&b"\xab"[..]);
+        let err = reader.unread_bit(true).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(reader.read_bits(2).unwrap(), 0x02);
+        assert!(reader.unread_bits(0x07, 3).is_err());
+        assert_eq!(reader.bit_position(), 2);
+        assert_eq!(reader.read_bits(6).unwrap(), 0x2b);
+    }
+
+    #[test]
+    #[should_panic(expected = "inside a transaction")]
+    fn unread_bits_in_attempt() {
+        let mut reader = BEBitReader::new(&b"\xab"[..]);
+        let _ = reader.attempt(|r| {
+            r.read_bit()?;
+            r.unread_bit(true)
+        });
+    }
+
+    #[test]
+    fn expect_bits() {
+        let mut reader = BEBitReader::new(&b"\x89PNG\x0d\x0a"[..]);
+        reader.expect_bits(0x8950_4e47, 32).unwrap();
+        let err = reader.expect_bits(0x0d0b, 16).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("found 0xd0a"));
+        assert!(reader.read_bit_opt().unwrap().is_none());
+    }
+
+    #[test]
+    fn from_parts() {
+        let mut reader = BEBitReader::from_parts(&b"\x56"[..], PartialByte::new(0xab, 3));
+        assert!(!reader.is_aligned());
+        assert_eq!(reader.read_bits(5).unwrap(), 0x0d);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x05);
+        let (inner, partial) = reader.into_parts();
+        assert_eq!(partial, PartialByte::new(0x02, 2));
+        assert!(inner.is_empty());
+        let reader = BEBitReader::from_parts(&b"\x56"[..], PartialByte::default());
+        assert!(reader.is_aligned());
+        assert!(reader.into_parts().1.is_empty());
+    }
+
+    #[test]
+    fn align_to() {
+        let mut reader = BEBitReader::new(&b"\xff\xff\xff\x81\x00\x42"[..]);
+        reader.align_to(16).unwrap();
+        reader.read_bits(3).unwrap();
+        reader.align_to(3).unwrap();
+        assert_eq!(reader.bit_position(), 3);
+        reader.align_to(24).unwrap();
+        assert_eq!(reader.read_bits(8).unwrap(), 0x81);
+        reader.align_to(1).unwrap();
+        reader.align_to(40).unwrap();
+        assert_eq!(reader.read_bits(8).unwrap(), 0x42);
+        assert!(reader.align_to(64).is_err());
+    }
+
+    #[test]
+    fn align_checked() {
+        let mut reader = BEBitReader::new(&b"\x00\x01"[..]);
+        reader.align_checked().unwrap();
+        reader.read_bits(3).unwrap();
+        reader.align_checked().unwrap();
+        reader.read_bits(3).unwrap();
+        let err = reader.align_checked().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(reader.is_aligned());
+        assert_eq!(reader.bit_position(), 16);
+    }
+
+    #[test]
+    fn nibbles() {
+        let mut reader = BEBitReader::new(&b"\x9b\xcd"[..]);
+        assert_eq!(reader.read_bits(2).unwrap(), 0x02);
+        let nibbles: Vec<u8> = reader.nibbles().map(Result::unwrap).collect();
+        assert_eq!(nibbles, [0x06, 0x0f, 0x03]);
+        assert_eq!(reader.read_bits(2).unwrap(), 0x01);
+    }
+
+    #[test]
+    fn chunks_bits() {
+        let mut reader = BEBitReader::new(&b"\x12\x34\x56\x78\x9a"[..]);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x01);
+        let chunks: Vec<u64> = reader.chunks_bits(16).map(Result::unwrap).collect();
+        assert_eq!(chunks, [0x2345, 0x6789]);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0a);
+        assert!(reader.chunks_bits(64).next().is_none());
+    }
+
+    #[test]
+    fn chunks_bits_in_scope() {
+        let mut reader = BEBitReader::new(&b"\x29\xc0\xff"[..]);
+        let (chunks, rest) = reader
+            .read_scope(10, |r| {
+                let chunks: Vec<u64> = r.chunks_bits(3).collect::<Result<_, _>>()?;
+                Ok((chunks, r.read_bits(1)?))
+            })
+            .unwrap();
+        assert_eq!(chunks, [1, 2, 3]);
+        assert_eq!(rest, 0x01);
+        // bytes looked ahead at the end of the stream are not lost
+        let chunks: Vec<u64> = reader.chunks_bits(16).collect::<Result<_, _>>().unwrap();
+        assert!(chunks.is_empty());
+        assert_eq!(reader.bit_position(), 10);
+        assert_eq!(reader.read_bits(6).unwrap(), 0x00);
+        assert_eq!(reader.read_bits(8).unwrap(), 0xff);
+    }
+
+    #[test]
+    fn bits() {
+        let mut reader = BEBitReader::new(&b"\xa5"[..]);
+        let bits: Vec<bool> = reader.bits().take(3).map(Result::unwrap).collect();
+        assert_eq!(bits, [true, false, true]);
+        assert_eq!(reader.bits().count(), 5);
+        assert!(reader.bits().next().is_none());
+    }
+
     #[test]
     fn read_bit() {
         let mut reader = BEBitReader::new(&b"\x2a"[..]);
@@ -319,7 +2133,60 @@ mod tests_be {
     }
 
     #[test]
-    #[should_panic]
+    fn peek() {
+        let mut reader = BEBitReader::new(&b"\xa5\x0f"[..]);
+        assert_eq!(reader.peek_bits(4).unwrap(), 0xa);
+        assert_eq!(reader.read_bits(6).unwrap(), 0x29);
+        assert_eq!(reader.peek_bits(8).unwrap(), 0x43);
+        assert!(!reader.peek_bit().unwrap());
+        let res = reader.attempt(|r| {
+            assert_eq!(r.peek_bits(8)?, 0x43);
+            r.read_bits(8)?;
+            r.peek_bits(4)
+        });
+        assert!(res.is_err());
+        assert_eq!(reader.read_bits(8).unwrap(), 0x43);
+        assert_eq!(reader.read_bits(2).unwrap(), 0x3);
+        assert!(reader.peek_bit().is_err());
+    }
+
+    #[test]
+    fn read_bits_wide() {
+        let mut reader = BEBitReader::new(&b"\x12\x34\x56\x78\x9a\xbc\xde\xf0\x12"[..]);
+        assert_eq!(reader.read_bits_u16(4).unwrap(), 0x1);
+        assert_eq!(reader.read_bits_u32(24).unwrap(), 0x23_4567);
+        assert_eq!(reader.read_bits_u64(44).unwrap(), 0x89a_bcde_f012);
+    }
+
+    #[test]
+    fn read_bits_u128() {
+        let value = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let mut writer = crate::BEBitWriter::new(vec![]);
+        writer.write_bit(true).unwrap();
+        writer.write_bits_u128(value, 128).unwrap();
+        writer.write_bits_u128(value, 100).unwrap();
+        writer.write_bits_u128(value, 7).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        assert!(reader.read_bit().unwrap());
+        assert_eq!(reader.read_bits_u128(128).unwrap(), value);
+        assert_eq!(
+            reader.read_bits_u128(100).unwrap(),
+            value & ((1 << 100) - 1)
+        );
+        assert_eq!(reader.read_bits_u128(7).unwrap(), 0x10);
+    }
+
+    #[test]
+    fn read_zero_bits() {
+        let mut reader = BEBitReader::new(&b"\xab"[..]);
+        assert_eq!(reader.read_bits(0).unwrap(), 0);
+        assert!(reader.is_aligned());
+        assert_eq!(reader.read_bits(8).unwrap(), 0xab);
+    }
+
+    #[test]
+    #[should_panic(expected = "count <= 8")]
     fn read_too_many_bits() {
         let mut reader = BEBitReader::new(&b""[..]);
         let _ = reader.read_bits(9);
@@ -328,9 +2195,56 @@ mod tests_be {
 
 #[cfg(test)]
 mod tests_le {
-    use crate::LEBitReader;
+    use crate::{LEBitReader, PartialByte};
     use std::io::Read;
 
+    #[test]
+    fn le_bits() {
+        use crate::BitReaderExt;
+
+        let mut reader = (&b"\x01"[..]).chain(&b"\x02"[..]).le_bits();
+        assert_eq!(reader.read_bits_u16(10).unwrap(), 0x201);
+    }
+
+    #[test]
+    fn from_parts() {
+        let mut reader = LEBitReader::new(&b"\xab\xcd"[..]);
+        reader.read_bits(3).unwrap();
+        let (inner, partial) = reader.into_parts();
+        assert_eq!(partial, PartialByte::new(0x15, 5));
+        let mut reader = LEBitReader::from_parts(inner, partial);
+        assert_eq!(reader.read_bits(7).unwrap(), 0x35);
+    }
+
+    #[test]
+    fn unread_bits() {
+        for skip in 0..8 {
+            let mut reader = LEBitReader::new(&b"\xab\xcd\xef"[..]);
+            reader.read_bits(skip).unwrap();
+            let value = reader.read_bits(7).unwrap();
+            reader.unread_bits(value, 7).unwrap();
+            assert_eq!(reader.bit_position(), skip.into());
+            assert_eq!(reader.read_bits(7).unwrap(), value);
+            reader.unread_bits(value, 7).unwrap();
+            let mut rest = vec![];
+            reader.read_to_end(&mut rest).unwrap();
+            let mut expected = LEBitReader::new(&b"\xab\xcd\xef"[..]);
+            expected.read_bits(skip).unwrap();
+            let mut expected_rest = vec![];
+            expected.read_to_end(&mut expected_rest).unwrap();
+            assert_eq!(rest, expected_rest);
+        }
+    }
+
+    #[test]
+    fn shifted_bytes() {
+        let mut reader = LEBitReader::new(&b"\xab\xcd\xef"[..]);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0b);
+        let bytes: Vec<u8> = reader.shifted_bytes().map(Result::unwrap).collect();
+        assert_eq!(bytes, b"\xda\xfc");
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0e);
+    }
+
     #[test]
     fn read_aligned() {
         let mut reader = LEBitReader::new(&b"Test"[..]);
@@ -353,7 +2267,7 @@ mod tests_le {
         assert_eq!(&buf, b"\x95");
         let mut buf = [0; 7];
         assert_eq!(reader.read(&mut buf).unwrap(), 3);
-        assert_eq!(&buf, b"\xd1\xd5\x0d\x10\0\0\0");
+        assert_eq!(&buf, b"\xd1\xd5\x0d\0\0\0\0");
     }
 
     #[test]
@@ -377,9 +2291,93 @@ mod tests_le {
     }
 
     #[test]
-    #[should_panic]
+    fn read_bits_slice() {
+        let mut reader = LEBitReader::new(&b"\xab\xcd\xef\x12"[..]);
+        reader.read_bits(4).unwrap();
+        let mut buf = [0xff; 4];
+        reader.read_bits_slice(&mut buf, 21).unwrap();
+        assert_eq!(buf, [0xda, 0xfc, 0x0e, 0xff]);
+        reader.read_bits_slice(&mut buf, 0).unwrap();
+        assert_eq!(reader.read_bits(7).unwrap(), 0x09);
+        assert!(reader.read_bits_slice(&mut buf, 1).is_err());
+    }
+
+    #[test]
+    fn read_bool_array() {
+        let mut writer = crate::LEBitWriter::new(vec![]);
+        writer.write_bit(true).unwrap();
+        writer
+            .write_bool_array(&[false, true, true, false, false, true, true, true, false])
+            .unwrap();
+        let data = writer.into_inner().unwrap();
+        assert_eq!(data, b"\xcd\x01");
+        let mut reader = LEBitReader::new(&data[..]);
+        assert!(reader.read_bit().unwrap());
+        let flags: [bool; 9] = reader.read_bool_array().unwrap();
+        assert_eq!(
+            flags,
+            [false, true, true, false, false, true, true, true, false]
+        );
+        assert_eq!(reader.read_bool_array::<0>().unwrap(), []);
+        assert!(reader.read_bool_array::<7>().is_err());
+    }
+
+    #[test]
+    fn read_bits_wide() {
+        let mut reader = LEBitReader::new(&b"\x12\x34\x56\x78\x9a\xbc\xde\xf0\x12"[..]);
+        assert_eq!(reader.read_bits_u16(4).unwrap(), 0x2);
+        assert_eq!(reader.read_bits_u32(24).unwrap(), 0x85_6341);
+        assert_eq!(reader.read_bits_u64(44).unwrap(), 0x12f_0deb_c9a7);
+    }
+
+    #[test]
+    fn read_bits_u128() {
+        let value = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let mut writer = crate::LEBitWriter::new(vec![]);
+        writer.write_bit(true).unwrap();
+        writer.write_bits_u128(value, 128).unwrap();
+        writer.write_bits_u128(value, 100).unwrap();
+        writer.write_bits_u128(value, 7).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert!(reader.read_bit().unwrap());
+        assert_eq!(reader.read_bits_u128(128).unwrap(), value);
+        assert_eq!(
+            reader.read_bits_u128(100).unwrap(),
+            value & ((1 << 100) - 1)
+        );
+        assert_eq!(reader.read_bits_u128(7).unwrap(), 0x10);
+    }
+
+    #[test]
+    #[should_panic(expected = "count <= 16")]
+    fn read_too_many_bits_u16() {
+        let mut reader = LEBitReader::new(&b""[..]);
+        let _ = reader.read_bits_u16(17);
+    }
+
+    #[test]
+    #[should_panic(expected = "count <= 8")]
     fn read_too_many_bits() {
         let mut reader = LEBitReader::new(&b""[..]);
         let _ = reader.read_bits(9);
     }
+
+    #[test]
+    fn skip_bits() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut reader = LEBitReader::new(&data[..]);
+        reader.skip_bits(3).unwrap();
+        assert_eq!(reader.read_bits(5).unwrap(), 0x0);
+        reader.skip_bits(8 * 300 - 2000).unwrap();
+        assert_eq!(reader.read_bits(8).unwrap(), 51);
+        reader.skip_bits(8 * 200 + 4).unwrap();
+        assert_eq!(reader.read_bits(8).unwrap(), 0xdf);
+        assert_eq!(
+            reader.attempt(|r| r.skip_bits(8 * 3)).unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+        reader.skip_bits(8 * 2 + 4).unwrap();
+        assert!(reader.read_bit_opt().unwrap().is_none());
+    }
 }
@@ -0,0 +1,243 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind};
+
+use crate::endian::{BE, BitEndianness, LE};
+
+/// Reads most significant bits first, from the end of the data.
+pub type BEReverseBitReader<'a> = ReverseBitReader<'a, BE>;
+/// Reads least significant bits first, from the end of the data.
+pub type LEReverseBitReader<'a> = ReverseBitReader<'a, LE>;
+
+/// Reads bits from the end of a byte slice towards its beginning.
+///
+/// This is the mirror image of `BitReader`: a field written with a `BitWriter` of the same bit endianness is read back with the same value, but fields are read in the reverse order they were written. This is how backward bit streams, like the ones of rANS coders or Zstandard's FSE and Huffman streams, are decoded.
+///
+/// Since reading backwards needs random access, the reader works on a byte slice instead of a [`std::io::Read`].
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{LEBitWriter, LEReverseBitReader};
+///
+/// let mut writer = LEBitWriter::new(vec![]);
+/// writer.write_bits(5, 3).unwrap();
+/// writer.write_bits(200, 8).unwrap();
+/// let data = writer.into_inner().unwrap();
+///
+/// let mut reader = LEReverseBitReader::with_len(&data, 11);
+/// assert_eq!(reader.read_bits(8).unwrap(), 200);
+/// assert_eq!(reader.read_bits(3).unwrap(), 5);
+/// assert_eq!(reader.remaining_bits(), 0);
+/// ```
+///
+/// [`std::io::Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
+#[derive(Debug, Clone)]
+pub struct ReverseBitReader<'a, E: BitEndianness> {
+    data: &'a [u8],
+    /// Number of bits left to read, the next bit read is the one before this position.
+    position: u64,
+    phantom: std::marker::PhantomData<E>,
+}
+
+impl<'a, E: BitEndianness> ReverseBitReader<'a, E> {
+    /// Creates a new `ReverseBitReader` starting at the last bit of `data`.
+    #[inline]
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self::with_len(data, data.len() as u64 * 8)
+    }
+
+    /// Creates a new `ReverseBitReader` starting before bit `len` of `data`, ignoring any bits after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` has less than `len` bits.
+    #[inline]
+    #[must_use]
+    pub fn with_len(data: &'a [u8], len: u64) -> Self {
+        assert!(len <= data.len() as u64 * 8, "data is shorter than len");
+        Self {
+            data,
+            position: len,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns the number of bits left to read.
+    #[inline]
+    #[must_use]
+    pub fn remaining_bits(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns whether the reader is aligned to the byte boundary.
+    #[inline]
+    #[must_use]
+    pub fn is_aligned(&self) -> bool {
+        self.position.is_multiple_of(8)
+    }
+
+    /// Returns the bit at stream position `i`.
+    fn bit(&self, i: u64) -> u8 {
+        #[allow(clippy::cast_possible_truncation)]
+        let byte = self.data[(i / 8) as usize];
+        #[allow(clippy::cast_possible_truncation)]
+        E::take_bits(u64::from(byte), 8, (i % 8) as u8, 1)
+    }
+
+    /// Reads the `count` bits before the current position, assembled as a forward reader would.
+    fn read_field(&mut self, count: u8) -> Res<u64> {
+        let count_bits = u64::from(count);
+        if count_bits > self.position {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("cannot read {count} bits, only {} bits left", self.position),
+            ));
+        }
+        let start = self.position - count_bits;
+        let mut value = 0;
+        for (len, i) in (start..self.position).enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                value = E::push_bits(value, len as u8, self.bit(i), 1);
+            }
+        }
+        self.position = start;
+        Ok(value)
+    }
+
+    /// Reads a single bit, returning true for 1, false for 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEReverseBitReader;
+    /// let mut reader = BEReverseBitReader::new(b"\x01");
+    /// assert_eq!(reader.read_bit().unwrap(), true);
+    /// assert_eq!(reader.read_bit().unwrap(), false);
+    /// ```
+    pub fn read_bit(&mut self) -> Res<bool> {
+        Ok(self.read_field(1)? != 0)
+    }
+
+    /// Reads 8 bits or less.
+    ///
+    /// The value is assembled from the `count` bits before the current position in the same way `BitReader::read_bits` would assemble them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEReverseBitReader;
+    /// let mut reader = BEReverseBitReader::new(b"\xf8");
+    /// assert_eq!(reader.read_bits(3).unwrap(), 0);
+    /// assert_eq!(reader.read_bits(5).unwrap(), 31);
+    /// ```
+    pub fn read_bits(&mut self, count: u8) -> Res<u8> {
+        assert!(count <= 8);
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(self.read_field(count)? as u8)
+    }
+
+    /// Skips the zero bits and the set bit marking the end of a padded backward stream.
+    ///
+    /// Backward streams are commonly finished by writing a single set bit followed by zeros up to the byte boundary, so the decoder can find the last written bit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if no set bit is found in the last byte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEReverseBitReader;
+    /// let mut reader = LEReverseBitReader::new(b"\x1d");
+    /// reader.skip_end_marker().unwrap();
+    /// assert_eq!(reader.read_bits(4).unwrap(), 13);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn skip_end_marker(&mut self) -> Res<()> {
+        for _ in 0..8 {
+            if self.position == 0 {
+                break;
+            }
+            if self.read_bit()? {
+                return Ok(());
+            }
+        }
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            "end marker bit of backward stream not found",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitWriter, BEReverseBitReader, LEBitWriter, LEReverseBitReader};
+
+    const FIELDS: [(u8, u8); 6] = [(1, 1), (5, 3), (0, 2), (0xab, 8), (3, 7), (0x7f, 7)];
+
+    #[test]
+    fn mirrors_writer_be() {
+        let mut writer = BEBitWriter::new(vec![]);
+        for (value, count) in FIELDS {
+            writer.write_bits(value, count).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEReverseBitReader::with_len(&data, 28);
+        for (value, count) in FIELDS.iter().rev() {
+            assert_eq!(reader.read_bits(*count).unwrap(), *value);
+        }
+        assert!(reader.read_bit().is_err());
+    }
+
+    #[test]
+    fn mirrors_writer_le() {
+        let mut writer = LEBitWriter::new(vec![]);
+        for (value, count) in FIELDS {
+            writer.write_bits(value, count).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEReverseBitReader::with_len(&data, 28);
+        for (value, count) in FIELDS.iter().rev() {
+            assert_eq!(reader.read_bits(*count).unwrap(), *value);
+        }
+        assert!(reader.read_bit().is_err());
+    }
+
+    #[test]
+    fn end_marker() {
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bits(0x5a, 8).unwrap();
+        writer.write_bits(6, 3).unwrap();
+        writer.write_bit(true).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEReverseBitReader::new(&data);
+        reader.skip_end_marker().unwrap();
+        assert_eq!(reader.remaining_bits(), 11);
+        assert_eq!(reader.read_bits(3).unwrap(), 6);
+        assert!(reader.is_aligned());
+        assert_eq!(reader.read_bits(8).unwrap(), 0x5a);
+    }
+
+    #[test]
+    fn missing_end_marker() {
+        let mut reader = BEReverseBitReader::new(b"\xff\x00");
+        let err = reader.skip_end_marker().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(BEReverseBitReader::new(b"").skip_end_marker().is_err());
+    }
+
+    #[test]
+    fn not_enough_bits() {
+        let mut reader = LEReverseBitReader::with_len(b"\xff", 3);
+        let err = reader.read_bits(4).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.read_bits(3).unwrap(), 7);
+    }
+}
@@ -0,0 +1,345 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::{LEBitReader, LEBitWriter};
+
+/// Maximum number of values buffered for a bit-packed run before it is written out.
+const MAX_PACKED: usize = 504;
+
+fn read_uleb128<R: Read>(reader: &mut LEBitReader<R>) -> Res<u64> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = reader.read_bits(8)?;
+        if shift > 63 || (shift == 63 && byte & 0x7e != 0) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "run header does not fit in 64 bits",
+            ));
+        }
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn write_uleb128<W: Write>(writer: &mut LEBitWriter<W>, mut value: u64) -> Res<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_bits(byte, 8);
+        }
+        writer.write_bits(byte | 0x80, 8)?;
+    }
+}
+
+/// Number of bits used to store the value of an RLE run.
+fn rle_value_bits(bit_width: u8) -> u8 {
+    bit_width.div_ceil(8) * 8
+}
+
+/// Encodes values with the RLE/bit-packed hybrid encoding used by Apache Parquet.
+///
+/// Values are written as they come in: runs of at least 8 repeated values are run-length encoded, everything else is bit-packed in groups of 8 values. Bit-packed values are buffered until a run-length encoded run starts or the buffer is full, since their count has to be written first.
+///
+/// Only the encoded runs are written, without the 4-byte length prefix some Parquet pages use. If the number of values is not a multiple of 8, the last group is padded with zeros, so the number of values has to be stored separately, as Parquet does.
+///
+/// The encoding packs values least significant bit first, so it is only available on little endian bit streams.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{LEBitReader, LEBitWriter, RleHybridDecoder, RleHybridEncoder};
+///
+/// let values = [1, 1, 1, 1, 1, 1, 1, 1, 0, 1, 2, 3];
+/// let mut writer = LEBitWriter::new(vec![]);
+/// let mut encoder = RleHybridEncoder::new(&mut writer, 2);
+/// for value in values {
+///     encoder.put(value).unwrap();
+/// }
+/// encoder.finish().unwrap();
+/// let data = writer.into_inner().unwrap();
+/// assert_eq!(data, b"\x10\x01\x03\xe4\x00");
+///
+/// let mut reader = LEBitReader::new(&data[..]);
+/// let decoded: Vec<u64> = RleHybridDecoder::new(&mut reader, 2, values.len())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, values);
+/// ```
+pub struct RleHybridEncoder<'a, W: Write> {
+    writer: &'a mut LEBitWriter<W>,
+    bit_width: u8,
+    /// Values waiting to be written as a bit-packed run.
+    packed: Vec<u64>,
+    run_value: u64,
+    run_len: u64,
+}
+
+impl<'a, W: Write> RleHybridEncoder<'a, W> {
+    /// Creates a new encoder writing values of `bit_width` bits to `writer`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_width` > 64.
+    pub fn new(writer: &'a mut LEBitWriter<W>, bit_width: u8) -> Self {
+        assert!(bit_width <= 64);
+        Self {
+            writer,
+            bit_width,
+            packed: Vec::with_capacity(MAX_PACKED),
+            run_value: 0,
+            run_len: 0,
+        }
+    }
+
+    /// Encodes a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if the value does not fit in the bit width.
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn put(&mut self, value: u64) -> Res<()> {
+        if self.bit_width < 64 && value >> self.bit_width != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{value} does not fit in {} bits", self.bit_width),
+            ));
+        }
+        if self.run_len > 0 && value == self.run_value {
+            self.run_len += 1;
+            return Ok(());
+        }
+        self.end_run()?;
+        self.run_value = value;
+        self.run_len = 1;
+        Ok(())
+    }
+
+    /// Writes out all buffered values, padding the last bit-packed group with zeros.
+    pub fn finish(mut self) -> Res<()> {
+        self.end_run()?;
+        while !self.packed.len().is_multiple_of(8) {
+            self.packed.push(0);
+        }
+        self.write_packed()
+    }
+
+    fn end_run(&mut self) -> Res<()> {
+        // bit-packed runs must be a multiple of 8 values, so complete the last group from the run first
+        while !self.packed.len().is_multiple_of(8) && self.run_len > 0 {
+            self.packed.push(self.run_value);
+            self.run_len -= 1;
+        }
+        if self.run_len >= 8 {
+            self.write_packed()?;
+            write_uleb128(self.writer, self.run_len << 1)?;
+            self.writer
+                .write_bits_wide(self.run_value, rle_value_bits(self.bit_width))?;
+        } else {
+            for _ in 0..self.run_len {
+                self.packed.push(self.run_value);
+            }
+            if self.packed.len() >= MAX_PACKED {
+                self.write_packed()?;
+            }
+        }
+        self.run_len = 0;
+        Ok(())
+    }
+
+    /// Writes the buffered values as a bit-packed run, keeping any incomplete group buffered.
+    fn write_packed(&mut self) -> Res<()> {
+        let count = self.packed.len() / 8 * 8;
+        if count == 0 {
+            return Ok(());
+        }
+        write_uleb128(self.writer, (count as u64 / 8) << 1 | 1)?;
+        for value in self.packed.drain(..count) {
+            self.writer.write_bits_wide(value, self.bit_width)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes values encoded with the RLE/bit-packed hybrid encoding used by Apache Parquet.
+///
+/// The decoder is an iterator over the decoded values. It decodes exactly the given number of values, and skips the padding of the last bit-packed group, so the reader is positioned right after the encoded data afterwards. A bit-packed run with more groups than needed for the values left is rejected as [`InvalidData`].
+///
+/// See [`RleHybridEncoder`] for details and an example.
+///
+/// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+pub struct RleHybridDecoder<'a, R: Read> {
+    reader: &'a mut LEBitReader<R>,
+    bit_width: u8,
+    remaining: usize,
+    rle_left: u64,
+    rle_value: u64,
+    packed_left: u64,
+}
+
+impl<'a, R: Read> RleHybridDecoder<'a, R> {
+    /// Creates a new decoder reading `num_values` values of `bit_width` bits from `reader`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_width` > 64.
+    pub fn new(reader: &'a mut LEBitReader<R>, bit_width: u8, num_values: usize) -> Self {
+        assert!(bit_width <= 64);
+        Self {
+            reader,
+            bit_width,
+            remaining: num_values,
+            rle_left: 0,
+            rle_value: 0,
+            packed_left: 0,
+        }
+    }
+
+    fn next_value(&mut self) -> Res<u64> {
+        loop {
+            if self.rle_left > 0 {
+                self.rle_left -= 1;
+                return Ok(self.rle_value);
+            }
+            if self.packed_left > 0 {
+                self.packed_left -= 1;
+                let value = self.reader.read_bits_wide(self.bit_width)?;
+                if self.remaining == 1 {
+                    // skip the padding of the last group
+                    let padding = std::mem::take(&mut self.packed_left);
+                    self.reader.skip_bits(padding * u64::from(self.bit_width))?;
+                }
+                return Ok(value);
+            }
+            let header = read_uleb128(self.reader)?;
+            if header & 1 == 1 {
+                let groups = header >> 1;
+                if groups > (self.remaining as u64).div_ceil(8) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "bit-packed run of {groups} groups is longer than the {} values left",
+                            self.remaining
+                        ),
+                    ));
+                }
+                self.packed_left = groups * 8;
+            } else {
+                self.rle_left = header >> 1;
+                self.rle_value = self.reader.read_bits_wide(rle_value_bits(self.bit_width))?;
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for RleHybridDecoder<'_, R> {
+    type Item = Res<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let value = self.next_value();
+        self.remaining = match value {
+            Ok(_) => self.remaining - 1,
+            Err(_) => 0,
+        };
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LEBitReader, LEBitWriter, RleHybridDecoder, RleHybridEncoder};
+
+    fn roundtrip(values: &[u64], bit_width: u8) -> Vec<u8> {
+        let mut writer = LEBitWriter::new(vec![]);
+        let mut encoder = RleHybridEncoder::new(&mut writer, bit_width);
+        for &value in values {
+            encoder.put(value).unwrap();
+        }
+        encoder.finish().unwrap();
+        writer.write_bits(0xab, 8).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        let decoded: Vec<u64> = RleHybridDecoder::new(&mut reader, bit_width, values.len())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, values);
+        assert_eq!(reader.read_bits(8).unwrap(), 0xab);
+        data
+    }
+
+    #[test]
+    fn parquet_spec_example() {
+        // bit-packed example from the Parquet encoding specification
+        let data = roundtrip(&[0, 1, 2, 3, 4, 5, 6, 7], 3);
+        assert_eq!(data, b"\x03\x88\xc6\xfa\xab");
+    }
+
+    #[test]
+    fn long_runs() {
+        let mut values = vec![5; 1000];
+        values.extend([1, 2, 3]);
+        values.extend([0; 20]);
+        values.push(7);
+        let data = roundtrip(&values, 3);
+        assert!(data.len() < 20);
+    }
+
+    #[test]
+    fn long_bit_packed() {
+        let values: Vec<u64> = (0..2000).map(|i| i % 1000).collect();
+        roundtrip(&values, 10);
+    }
+
+    #[test]
+    fn wide_values() {
+        roundtrip(&[u64::MAX; 9], 64);
+        roundtrip(&[0, u64::MAX, 1 << 40], 64);
+        roundtrip(&[0; 17], 0);
+    }
+
+    #[test]
+    fn value_too_wide() {
+        let mut writer = LEBitWriter::new(vec![]);
+        let mut encoder = RleHybridEncoder::new(&mut writer, 3);
+        assert!(encoder.put(8).is_err());
+    }
+
+    #[test]
+    fn too_many_groups() {
+        // a bit-packed run of 2^62 groups of zero-width values
+        let mut reader = LEBitReader::new(&b"\x81\x80\x80\x80\x80\x80\x80\x80\x80\x01"[..]);
+        let mut decoder = RleHybridDecoder::new(&mut reader, 0, 2);
+        let err = decoder.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(decoder.next().is_none());
+
+        // the padding of the last group is skipped at once
+        let mut reader = LEBitReader::new(&b"\x03\xab"[..]);
+        let mut decoder = RleHybridDecoder::new(&mut reader, 0, 3);
+        assert_eq!(
+            decoder.by_ref().collect::<Result<Vec<_>, _>>().unwrap(),
+            [0; 3]
+        );
+        assert_eq!(reader.read_bits(8).unwrap(), 0xab);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut reader = LEBitReader::new(&b"\x10"[..]);
+        let mut decoder = RleHybridDecoder::new(&mut reader, 2, 8);
+        assert!(decoder.next().unwrap().is_err());
+        assert!(decoder.next().is_none());
+    }
+}
@@ -0,0 +1,717 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// Seeking in a bit stream, with positions counted in bits.
+///
+/// This is like [`Seek`], but `SeekFrom` offsets are in bits, and positions are bit offsets from the start of the underlying stream, the first bit of byte `n` being bit `8 * n`.
+///
+/// [`Seek`]: https://doc.rust-lang.org/std/io/trait.Seek.html
+pub trait SeekBits {
+    /// Seeks to a bit offset in the stream, returning the new position from the start of the stream in bits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if the new position would be negative or too large, or if called inside `BitReader::attempt`. Errors of the underlying stream are returned as they are.
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    fn seek_bits(&mut self, pos: SeekFrom) -> Res<u64>;
+
+    /// Returns the current position from the start of the stream in bits.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `seek_bits`.
+    fn stream_bit_position(&mut self) -> Res<u64> {
+        self.seek_bits(SeekFrom::Current(0))
+    }
+}
+
+/// Seek through a mutable reference, like `Seek` does, so helper functions can take `&mut` readers and writers without moving them.
+impl<S: SeekBits + ?Sized> SeekBits for &mut S {
+    #[inline]
+    fn seek_bits(&mut self, pos: SeekFrom) -> Res<u64> {
+        (**self).seek_bits(pos)
+    }
+
+    #[inline]
+    fn stream_bit_position(&mut self) -> Res<u64> {
+        (**self).stream_bit_position()
+    }
+}
+
+fn invalid_seek() -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        "invalid seek to a negative or overflowing position",
+    )
+}
+
+/// Resolves a bit offset against the current position and a lazily determined end of the stream.
+fn seek_target(pos: SeekFrom, current: u64, end: impl FnOnce() -> Res<u64>) -> Res<u64> {
+    match pos {
+        SeekFrom::Start(offset) => Some(offset),
+        SeekFrom::Current(offset) => current.checked_add_signed(offset),
+        SeekFrom::End(offset) => end()?
+            .checked_mul(8)
+            .and_then(|end| end.checked_add_signed(offset)),
+    }
+    .ok_or_else(invalid_seek)
+}
+
+/// Seeks bit by bit, reading the bits before the new position if it is not on a byte boundary.
+///
+/// Seeking discards the partially read byte and any bytes given back by a rolled back `attempt`. The position returned by `BitReader::bit_position` moves by the distance seeked.
+///
+/// # Errors
+///
+/// Returns an error of kind [`InvalidInput`] inside a transaction, or if the bits held in memory, like a partial byte passed to `BitReader::from_parts`, would lie before the start of the underlying stream.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, SeekBits};
+/// use std::io::{Cursor, SeekFrom};
+///
+/// let mut reader = BEBitReader::new(Cursor::new(b"\x12\x34\x56"));
+/// reader.seek_bits(SeekFrom::Start(12)).unwrap();
+/// assert_eq!(reader.read_bits(8).unwrap(), 0x45);
+/// reader.seek_bits(SeekFrom::Current(-16)).unwrap();
+/// assert_eq!(reader.read_bits(4).unwrap(), 0x02);
+/// assert_eq!(reader.seek_bits(SeekFrom::End(-1)).unwrap(), 23);
+/// ```
+///
+/// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+impl<E: BitEndianness, R: Read + Seek> SeekBits for BitReader<E, R> {
+    fn seek_bits(&mut self, pos: SeekFrom) -> Res<u64> {
+        if self.in_transaction() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot seek inside a transaction",
+            ));
+        }
+        let buffered = if self.is_aligned() {
+            0
+        } else {
+            u64::from(8 - self.bit_offset())
+        };
+        let replayed = self.replayed_len() as u64;
+        // bits pushed back in memory can't reach before the start of the underlying stream
+        let current = self
+            .inner_mut()
+            .stream_position()?
+            .checked_sub(replayed)
+            .and_then(|byte| (byte * 8).checked_sub(buffered))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    "bits pushed back onto the reader are before the start of the stream",
+                )
+            })?;
+        if pos == SeekFrom::Current(0) {
+            return Ok(current);
+        }
+        let target = seek_target(pos, current, || Ok(self.probe_end()?.1))?;
+        self.inner_mut().seek(SeekFrom::Start(target / 8))?;
+        self.reset_after_seek(current, target - target % 8);
+        #[allow(clippy::cast_possible_truncation)]
+        self.read_bits((target % 8) as u8)?;
+        Ok(target)
+    }
+}
+
+/// Seeks byte by byte in the underlying reader, so the `BitReader` can be passed to APIs requiring `Read + Seek`.
+///
+/// Offsets are in bytes, and positions are relative to the start of the underlying stream, taking bytes given back by a rolled back `attempt` into account. Use `SeekBits` for bit offsets.
+///
+/// # Errors
+///
+/// Returns an error of kind [`InvalidInput`] if the `BitReader` is not aligned. Otherwise returns the errors of `SeekBits::seek_bits`.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::BEBitReader;
+/// use std::io::{Cursor, Seek, SeekFrom};
+///
+/// let mut reader = BEBitReader::new(Cursor::new(b"\x12\x34\x56"));
+/// reader.read_bits(4).unwrap();
+/// assert!(reader.stream_position().is_err());
+/// reader.align();
+/// assert_eq!(reader.stream_position().unwrap(), 1);
+/// reader.seek(SeekFrom::End(-1)).unwrap();
+/// assert_eq!(reader.read_bits(8).unwrap(), 0x56);
+/// ```
+///
+/// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+impl<E: BitEndianness, R: Read + Seek> Seek for BitReader<E, R> {
+    fn seek(&mut self, pos: SeekFrom) -> Res<u64> {
+        if !self.is_aligned() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "BitReader is not aligned",
+            ));
+        }
+        let pos = match pos {
+            SeekFrom::Start(offset) => offset.checked_mul(8).map(SeekFrom::Start),
+            SeekFrom::Current(offset) => offset.checked_mul(8).map(SeekFrom::Current),
+            SeekFrom::End(offset) => offset.checked_mul(8).map(SeekFrom::End),
+        }
+        .ok_or_else(invalid_seek)?;
+        Ok(self.seek_bits(pos)? / 8)
+    }
+}
+
+/// Seeks bit by bit, keeping the data around the new position.
+///
+/// Before seeking, a partially written byte is completed with the bits after it that are in the underlying stream. If the new position is not on a byte boundary, the bits of the byte there before the position are kept. This needs the underlying writer to implement `Read`, like `File` and `Cursor` do. Past the end of the stream, missing bits are zero.
+///
+/// Aligning or finishing the writer completes a partial byte with zero bits as usual, so after patching bits in the middle of the stream, seek to the end before finishing the writer to keep the data after the patch.
+///
+/// The internal buffer of a `BitWriter` created with `with_buffer` is written out before seeking. The position returned by `BitWriter::bit_position` moves by the distance seeked.
+///
+/// # Examples
+///
+/// Patch a field after writing the data following it:
+///
+/// ```
+/// use endio_bit::{BEBitWriter, SeekBits};
+/// use std::io::{Cursor, SeekFrom};
+///
+/// let mut writer = BEBitWriter::new(Cursor::new(vec![]));
+/// writer.write_bits(0x0f, 4).unwrap();
+/// writer.write_bits(0x00, 3).unwrap();
+/// writer.write_bits(0xff, 8).unwrap();
+/// writer.seek_bits(SeekFrom::Start(4)).unwrap();
+/// writer.write_bits(0x05, 3).unwrap();
+/// writer.seek_bits(SeekFrom::End(0)).unwrap();
+/// writer.write_bits(0xaa, 8).unwrap();
+/// assert_eq!(writer.into_inner().unwrap().into_inner(), b"\xfb\xfe\xaa");
+/// ```
+impl<E: BitEndianness, W: Read + Write + Seek> SeekBits for BitWriter<E, W> {
+    fn seek_bits(&mut self, pos: SeekFrom) -> Res<u64> {
+        let pending = self.buffer().len() as u64;
+        let current =
+            (self.inner_mut().stream_position()? + pending) * 8 + u64::from(self.bit_offset());
+        if pos == SeekFrom::Current(0) {
+            return Ok(current);
+        }
+        self.flush_out()?;
+        if !self.is_aligned() {
+            let rest = peek_byte(self.inner_mut())?;
+            self.complete_partial(rest)?;
+        }
+        let target = seek_target(pos, current, || self.inner_mut().seek(SeekFrom::End(0)))?;
+        self.inner_mut().seek(SeekFrom::Start(target / 8))?;
+        let byte = if target % 8 == 0 {
+            0
+        } else {
+            peek_byte(self.inner_mut())?
+        };
+        self.reset_after_seek(current, target, byte);
+        Ok(target)
+    }
+}
+
+/// Reads the byte at the current position of `inner` without moving it, or 0 at the end of the stream.
+fn peek_byte<T: Read + Seek>(inner: &mut T) -> Res<u8> {
+    let position = inner.stream_position()?;
+    let mut byte = [0];
+    match inner.read_exact(&mut byte) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => byte[0] = 0,
+        Err(e) => return Err(e),
+    }
+    inner.seek(SeekFrom::Start(position))?;
+    Ok(byte[0])
+}
+
+/// A saved position of a `BitReader` or `BitWriter` on a seekable stream, created by `checkpoint`.
+///
+/// A checkpoint is made up of two integers, so it can be persisted and used to resume reading or writing a file with a new `BitReader` or `BitWriter`, for example after a restart.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, Checkpoint};
+/// use std::io::Cursor;
+///
+/// let mut reader = BEBitReader::new(Cursor::new(b"\x12\x34"));
+/// reader.read_bits(4).unwrap();
+/// let checkpoint = reader.checkpoint().unwrap();
+/// let saved = (checkpoint.stream_bit_position(), checkpoint.bit_position());
+///
+/// let mut reader = BEBitReader::new(Cursor::new(b"\x12\x34"));
+/// reader.restore(Checkpoint::new(saved.0, saved.1)).unwrap();
+/// assert_eq!(reader.read_bits(8).unwrap(), 0x23);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Position in the underlying stream in bits.
+    stream: u64,
+    /// Value of `bit_position`.
+    position: u64,
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint from its parts, as returned by `stream_bit_position` and `bit_position`.
+    #[must_use]
+    pub fn new(stream_bit_position: u64, bit_position: u64) -> Self {
+        Self {
+            stream: stream_bit_position,
+            position: bit_position,
+        }
+    }
+
+    /// Returns the position in the underlying stream in bits, as returned by `SeekBits::stream_bit_position`.
+    #[must_use]
+    pub fn stream_bit_position(&self) -> u64 {
+        self.stream
+    }
+
+    /// Returns the number of bits read or written since construction, as returned by `bit_position`.
+    #[must_use]
+    pub fn bit_position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<E: BitEndianness, R: Read + Seek> BitReader<E, R> {
+    /// Saves the current position, to return to it later with `restore`.
+    ///
+    /// Unlike `attempt`, this doesn't keep the bytes read after the checkpoint in memory, restoring seeks the underlying reader instead. This is cheaper for backtracking over long distances.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `SeekBits::stream_bit_position`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = BEBitReader::new(Cursor::new(b"\x12\x34"));
+    /// reader.read_bits(4).unwrap();
+    /// let checkpoint = reader.checkpoint().unwrap();
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x23);
+    /// reader.restore(checkpoint).unwrap();
+    /// assert_eq!(reader.bit_position(), 4);
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x23);
+    /// ```
+    pub fn checkpoint(&mut self) -> Res<Checkpoint> {
+        Ok(Checkpoint {
+            stream: self.stream_bit_position()?,
+            position: self.bit_position(),
+        })
+    }
+
+    /// Returns to a position saved with `checkpoint`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `SeekBits::seek_bits`.
+    pub fn restore(&mut self, checkpoint: Checkpoint) -> Res<()> {
+        self.seek_bits(SeekFrom::Start(checkpoint.stream))?;
+        self.set_bit_position(checkpoint.position);
+        Ok(())
+    }
+}
+
+impl<E: BitEndianness, W: Read + Write + Seek> BitWriter<E, W> {
+    /// Saves the current position, to return to it later with `restore`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `SeekBits::stream_bit_position`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// use std::io::Cursor;
+    ///
+    /// let mut writer = BEBitWriter::new(Cursor::new(vec![]));
+    /// writer.write_bits(0x0a, 4).unwrap();
+    /// let checkpoint = writer.checkpoint().unwrap();
+    /// writer.write_bits(0xff, 8).unwrap();
+    /// writer.restore(checkpoint).unwrap();
+    /// writer.write_bits(0x00, 8).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap().into_inner(), b"\xa0\x00");
+    /// ```
+    pub fn checkpoint(&mut self) -> Res<Checkpoint> {
+        Ok(Checkpoint {
+            stream: self.stream_bit_position()?,
+            position: self.bit_position(),
+        })
+    }
+
+    /// Returns to a position saved with `checkpoint`, like `SeekBits::seek_bits` does.
+    ///
+    /// Data written after the checkpoint stays in the underlying stream, and is overwritten by further writes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `SeekBits::seek_bits`.
+    pub fn restore(&mut self, checkpoint: Checkpoint) -> Res<()> {
+        self.seek_bits(SeekFrom::Start(checkpoint.stream))?;
+        self.set_bit_position(checkpoint.position);
+        Ok(())
+    }
+}
+
+impl<E: BitEndianness, R: Read + Seek> BitReader<E, R> {
+    /// Gets a mutable reference to the underlying reader, seeking it back to the start of the partially read byte first.
+    ///
+    /// Unlike `get_mut`, this doesn't panic if the reader is not aligned. The unread bits of the partial byte are given back to the underlying reader, as are bytes given back by a rolled back `attempt`, so the `BitReader` continues at the start of the partial byte afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `SeekBits::seek_bits`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// use std::io::{Cursor, Read};
+    ///
+    /// let mut reader = BEBitReader::new(Cursor::new(b"\x12\x34"));
+    /// reader.read_bits(4).unwrap();
+    /// let mut buf = [0; 1];
+    /// reader.get_mut_resync().unwrap().read_exact(&mut buf).unwrap();
+    /// assert_eq!(buf, [0x12]);
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x34);
+    /// ```
+    pub fn get_mut_resync(&mut self) -> Res<&mut R> {
+        if !self.is_aligned() || self.replayed_len() != 0 {
+            let current = self.stream_bit_position()?;
+            self.seek_bits(SeekFrom::Start(current - current % 8))?;
+        }
+        Ok(self.get_mut())
+    }
+
+    /// Seeks to the start of the underlying stream and resets the bit state, to read the stream again from the top.
+    ///
+    /// Unlike `Seek::rewind`, this also works if the reader is not aligned, and resets `bit_position` to 0. To seek to another bit, use `SeekBits::seek_bits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `SeekBits::seek_bits`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = BEBitReader::new(Cursor::new(b"\x12\x34"));
+    /// reader.read_bits_u16(12).unwrap();
+    /// reader.rewind().unwrap();
+    /// assert_eq!(reader.bit_position(), 0);
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x12);
+    /// ```
+    #[doc(alias = "seek_to_bit")]
+    pub fn rewind(&mut self) -> Res<()> {
+        self.seek_bits(SeekFrom::Start(0))?;
+        self.set_bit_position(0);
+        Ok(())
+    }
+
+    /// Returns the length of the underlying stream in bits.
+    ///
+    /// The length is determined by seeking to the end of the stream and back, like the unstable `Seek::stream_len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = BEBitReader::new(Cursor::new(b"\x00\x00\x00"));
+    /// assert_eq!(reader.total_bits().unwrap(), 24);
+    /// ```
+    #[doc(alias = "stream_len_bits")]
+    pub fn total_bits(&mut self) -> Res<u64> {
+        let (_, end) = self.probe_end()?;
+        Ok(end * 8)
+    }
+
+    /// Returns the number of bits left to read until the end of the underlying stream.
+    ///
+    /// This includes the unread bits of a partially read byte and bytes given back by a rolled back `attempt`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = BEBitReader::new(Cursor::new(b"\x00\x00\x00"));
+    /// reader.read_bits(3).unwrap();
+    /// assert_eq!(reader.remaining_bits().unwrap(), 21);
+    /// ```
+    #[doc(alias = "bits_remaining")]
+    pub fn remaining_bits(&mut self) -> Res<u64> {
+        let buffered = if self.is_aligned() {
+            0
+        } else {
+            u64::from(8 - self.bit_offset())
+        };
+        let replayed = self.replayed_len() as u64 * 8;
+        let (current, end) = self.probe_end()?;
+        Ok(end.saturating_sub(current) * 8 + buffered + replayed)
+    }
+
+    /// Discards the next `count` bits like `BitReader::skip_bits`, seeking over whole bytes in the underlying reader instead of reading them.
+    ///
+    /// The unread bits of a partially read byte and bytes given back by a rolled back `attempt` are skipped first, the underlying reader is then seeked over the whole bytes left, and the last bits are read. Inside a transaction, the bytes have to be recorded to be given back, so this reads them like `skip_bits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`UnexpectedEof`] if the stream ends before `count` bits were skipped. The bits up to the end are consumed in this case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut data = vec![0; 1000];
+    /// data.push(0xab);
+    /// let mut reader = BEBitReader::new(Cursor::new(data));
+    /// reader.read_bits(4).unwrap();
+    /// reader.skip_bits_seek(7996).unwrap();
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0xab);
+    /// ```
+    ///
+    /// [`UnexpectedEof`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn skip_bits_seek(&mut self, count: u64) -> Res<()> {
+        let buffered = if self.is_aligned() {
+            0
+        } else {
+            u64::from(8 - self.bit_offset())
+        } + self.replayed_len() as u64 * 8;
+        let past_scope = self
+            .scope_remaining()
+            .is_some_and(|remaining| count > remaining);
+        if self.in_transaction() || past_scope || count <= buffered {
+            return self.skip_bits(count);
+        }
+        // leaves the reader aligned with nothing given back
+        self.skip_bits(buffered)?;
+        let rest = count - buffered;
+        let bytes = rest / 8;
+        let inner = self.inner_mut();
+        let current = inner.stream_position()?;
+        let end = inner.seek(SeekFrom::End(0))?;
+        let target = current.saturating_add(bytes).min(end.max(current));
+        inner.seek(SeekFrom::Start(target))?;
+        self.set_bit_position(self.bit_position() + (target - current) * 8);
+        if target - current < bytes {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "failed to skip bits"));
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        self.read_bits((rest % 8) as u8)?;
+        Ok(())
+    }
+
+    /// Returns the current and the end byte position of the underlying reader, leaving its position unchanged.
+    fn probe_end(&mut self) -> Res<(u64, u64)> {
+        let inner = self.inner_mut();
+        let current = inner.stream_position()?;
+        let end = inner.seek(SeekFrom::End(0))?;
+        if end != current {
+            inner.seek(SeekFrom::Start(current))?;
+        }
+        Ok((current, end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    use crate::{BEBitReader, BEBitWriter, LEBitReader, LEBitWriter, PartialByte, SeekBits};
+
+    #[test]
+    fn remaining_bits() {
+        let mut reader = LEBitReader::new(Cursor::new(vec![0xff; 4]));
+        assert_eq!(reader.total_bits().unwrap(), 32);
+        assert_eq!(reader.remaining_bits().unwrap(), 32);
+        reader.read_bits(5).unwrap();
+        assert_eq!(reader.remaining_bits().unwrap(), 27);
+        reader.read_bits(8).unwrap();
+        assert_eq!(reader.remaining_bits().unwrap(), 19);
+        assert_eq!(reader.total_bits().unwrap(), 32);
+        // the position is restored after probing
+        assert_eq!(reader.read_bits(8).unwrap(), 0xff);
+        assert_eq!(reader.remaining_bits().unwrap(), 11);
+        assert!(reader.attempt(|r| r.read_reserved(9)).is_err());
+        assert_eq!(reader.remaining_bits().unwrap(), 11);
+    }
+
+    #[test]
+    fn remaining_bits_at_end() {
+        let mut reader = BEBitReader::new(Cursor::new(b"\x12"));
+        reader.read_bits(8).unwrap();
+        assert_eq!(reader.remaining_bits().unwrap(), 0);
+        assert!(reader.read_bit().is_err());
+    }
+
+    #[test]
+    fn seek_bits_reader() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut reader = LEBitReader::new(Cursor::new(&data));
+        for target in [1000, 3, 8, 2047, 17, 0] {
+            assert_eq!(reader.seek_bits(SeekFrom::Start(target)).unwrap(), target);
+            assert_eq!(reader.stream_bit_position().unwrap(), target);
+            let mut expected = LEBitReader::new(&data[..]);
+            expected.skip_bits(target).unwrap();
+            assert_eq!(reader.read_bit().unwrap(), expected.read_bit().unwrap());
+        }
+        reader.read_bits(7).unwrap();
+        assert_eq!(reader.seek_bits(SeekFrom::Current(-3)).unwrap(), 5);
+        assert_eq!(reader.bit_position(), 5);
+        assert!(reader.seek_bits(SeekFrom::Current(-6)).is_err());
+        assert_eq!(reader.seek_bits(SeekFrom::End(-4)).unwrap(), 2044);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x0f);
+        assert!(reader.read_bit_opt().unwrap().is_none());
+        // bytes given back by an attempt are discarded
+        reader.seek_bits(SeekFrom::Start(4)).unwrap();
+        assert!(reader.attempt(|r| r.read_reserved(16)).is_err());
+        assert_eq!(reader.stream_bit_position().unwrap(), 4);
+        assert_eq!(reader.seek_bits(SeekFrom::Current(8)).unwrap(), 12);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x00);
+        assert_eq!(reader.read_bits(8).unwrap(), 0x02);
+    }
+
+    #[test]
+    fn unread_then_position() {
+        let mut reader = BEBitReader::new(Cursor::new(vec![1, 2, 3]));
+        reader.read_bits(8).unwrap();
+        reader.read_bits(3).unwrap();
+        reader.unread_bits(0xff, 8).unwrap();
+        assert_eq!(reader.stream_bit_position().unwrap(), 3);
+        assert_eq!(reader.seek_bits(SeekFrom::Current(5)).unwrap(), 8);
+        assert_eq!(reader.read_bits(8).unwrap(), 0x02);
+        assert!(reader.unread_bits(0xff, 8).is_ok());
+        assert!(reader.unread_bits(0xff, 8).is_ok());
+        assert!(reader.unread_bits(0xff, 1).is_err());
+        assert_eq!(reader.stream_bit_position().unwrap(), 0);
+
+        // a partial byte taken from before the start of the underlying stream
+        let mut reader = BEBitReader::from_parts(Cursor::new(vec![1]), PartialByte::new(0x1f, 5));
+        let err = reader.stream_bit_position().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn skip_bits_seek() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut reader = LEBitReader::new(Cursor::new(&data));
+        reader.skip_bits_seek(3).unwrap();
+        assert_eq!(reader.read_bits(5).unwrap(), 0x0);
+        reader.skip_bits_seek(8 * 300 - 2000).unwrap();
+        assert_eq!(reader.read_bits(8).unwrap(), 51);
+        // the bytes given back by the attempt are skipped before seeking
+        let _ = reader.attempt(|r| {
+            r.skip_bits(8 * 3)?;
+            Err::<(), _>(std::io::Error::other("roll back"))
+        });
+        reader.skip_bits_seek(8 * 5 + 4).unwrap();
+        assert_eq!(reader.get_ref().position(), 58);
+        assert_eq!(reader.read_bits(4).unwrap(), 0x3);
+        reader.skip_bits_seek(8 * 194 + 4).unwrap();
+        assert_eq!(reader.read_bits(8).unwrap(), 0xdf);
+        assert_eq!(reader.bit_position(), 8 * 253 + 4);
+        assert!(reader.attempt(|r| r.skip_bits_seek(8 * 3)).is_err());
+        let err = reader.skip_bits_seek(8 * 4).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.bit_position(), 8 * 256);
+        assert!(reader.read_bit_opt().unwrap().is_none());
+    }
+
+    #[test]
+    fn seek_aligned() {
+        let mut reader = LEBitReader::new(Cursor::new(b"\x01\x02\x03\x04"));
+        assert_eq!(reader.seek(SeekFrom::Start(2)).unwrap(), 2);
+        assert_eq!(reader.read_bits(8).unwrap(), 0x03);
+        assert!(reader.attempt(|r| r.read_reserved(9)).is_err());
+        assert_eq!(reader.stream_position().unwrap(), 3);
+        assert_eq!(reader.seek(SeekFrom::Current(-3)).unwrap(), 0);
+        let mut buf = vec![];
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, b"\x01\x02\x03\x04");
+        assert!(reader.seek(SeekFrom::Current(-5)).is_err());
+        assert!(reader.seek(SeekFrom::Start(u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn by_mut_ref() {
+        fn skip_header(mut reader: impl Read + SeekBits) -> std::io::Result<u64> {
+            let mut magic = [0; 2];
+            reader.read_exact(&mut magic)?;
+            reader.seek_bits(SeekFrom::Current(3))
+        }
+
+        let mut reader = LEBitReader::new(Cursor::new(b"\x01\x02\x1f"));
+        assert_eq!(skip_header(&mut reader).unwrap(), 19);
+        assert_eq!(reader.read_bits(5).unwrap(), 0x03);
+    }
+
+    #[test]
+    fn get_mut_resync() {
+        let mut reader = LEBitReader::new(Cursor::new(b"\x01\x02\x03\x04"));
+        reader.read_bits(8).unwrap();
+        assert!(reader.attempt(|r| r.read_reserved(9)).is_err());
+        assert_eq!(reader.get_mut_resync().unwrap().position(), 1);
+        reader.read_bits(3).unwrap();
+        assert_eq!(reader.get_mut_resync().unwrap().position(), 1);
+        assert_eq!(reader.read_bits(8).unwrap(), 0x02);
+        assert_eq!(reader.bit_position(), 16);
+    }
+
+    #[test]
+    fn checkpoint() {
+        let mut reader = LEBitReader::new(Cursor::new(b"\x12\x34\x56"));
+        reader.read_bits(5).unwrap();
+        let checkpoint = reader.checkpoint().unwrap();
+        reader.read_bits(8).unwrap();
+        let inner = reader.checkpoint().unwrap();
+        reader.read_bits(8).unwrap();
+        reader.restore(inner).unwrap();
+        assert_eq!(reader.bit_position(), 13);
+        reader.restore(checkpoint).unwrap();
+        assert_eq!(reader.bit_position(), 5);
+        assert_eq!(reader.read_bits_u16(16).unwrap(), 0xb1a0);
+    }
+
+    #[test]
+    fn seek_bits_writer() {
+        let mut writer = LEBitWriter::new(Cursor::new(vec![0xff; 3]));
+        writer.seek_bits(SeekFrom::Start(6)).unwrap();
+        writer.write_bits(0x00, 4).unwrap();
+        assert_eq!(writer.stream_bit_position().unwrap(), 10);
+        writer.seek_bits(SeekFrom::End(4)).unwrap();
+        writer.write_bit(false).unwrap();
+        writer.seek_bits(SeekFrom::Start(20)).unwrap();
+        writer.write_bits(0x00, 2).unwrap();
+        assert_eq!(writer.bit_position(), 22);
+        writer.seek_bits(SeekFrom::End(0)).unwrap();
+        let data = writer.into_inner().unwrap().into_inner();
+        assert_eq!(data, b"\x3f\xfc\xcf\x00");
+
+        let mut writer = BEBitWriter::with_buffer(16, Cursor::new(vec![]));
+        writer.write_bits(0xab, 8).unwrap();
+        writer.write_bits(0x0c, 4).unwrap();
+        assert_eq!(writer.stream_bit_position().unwrap(), 12);
+        writer.seek_bits(SeekFrom::Current(-10)).unwrap();
+        writer.write_bits(0x00, 2).unwrap();
+        writer.seek_bits(SeekFrom::Start(12)).unwrap();
+        writer.write_bits(0x0d, 4).unwrap();
+        let data = writer.into_inner().unwrap().into_inner();
+        assert_eq!(data, b"\x8b\xcd");
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::VecDeque;
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// Number of values and bits per value of each Simple-8b selector.
+const SIMPLE_8B: [(usize, u8); 16] = [
+    (240, 0),
+    (120, 0),
+    (60, 1),
+    (30, 2),
+    (20, 3),
+    (15, 4),
+    (12, 5),
+    (10, 6),
+    (8, 7),
+    (7, 8),
+    (6, 10),
+    (5, 12),
+    (4, 15),
+    (3, 20),
+    (2, 30),
+    (1, 60),
+];
+/// Number of values and bits per value of each Simple-9 selector.
+const SIMPLE_9: [(usize, u8); 9] = [
+    (28, 1),
+    (14, 2),
+    (9, 3),
+    (7, 4),
+    (5, 5),
+    (4, 7),
+    (3, 9),
+    (2, 14),
+    (1, 28),
+];
+
+/// A word-aligned integer packing scheme of the Simple family.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimpleScheme {
+    /// Simple-8b: 64-bit words of a 4-bit selector and 60 bits of values, which can be up to 60 bits wide. The first two selectors encode runs of 240 and 120 zeros.
+    Simple8b,
+    /// Simple-9: 32-bit words of a 4-bit selector and 28 bits of values, which can be up to 28 bits wide.
+    Simple9,
+}
+
+impl SimpleScheme {
+    fn selectors(self) -> &'static [(usize, u8)] {
+        match self {
+            Self::Simple8b => &SIMPLE_8B,
+            Self::Simple9 => &SIMPLE_9,
+        }
+    }
+
+    /// Returns the number of bits of a word.
+    #[must_use]
+    pub fn word_bits(self) -> u8 {
+        match self {
+            Self::Simple8b => 64,
+            Self::Simple9 => 32,
+        }
+    }
+
+    /// Returns the maximum width of a value in bits.
+    #[must_use]
+    pub fn max_value_bits(self) -> u8 {
+        self.word_bits() - 4
+    }
+}
+
+/// Encodes values with Simple-8b or Simple-9 word packing.
+///
+/// Each word packs as many of the next values as possible, all with the same width given by the word's selector, so runs of small values take less space than large ones. Values are buffered until there are enough to fill the largest word.
+///
+/// A word is written as its 4-bit selector followed by its values in order, each in the width of the selector, and zero padding up to the word size. With big endian bit numbering, a word is thus a big endian integer with the selector in the top 4 bits and the first value after it. The last word may contain less values than its selector holds, so the number of values has to be stored separately.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, BEBitWriter, SimpleDecoder, SimpleEncoder, SimpleScheme};
+///
+/// let values = [3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 900];
+/// let mut writer = BEBitWriter::new(vec![]);
+/// let mut encoder = SimpleEncoder::new(&mut writer, SimpleScheme::Simple8b);
+/// for value in values {
+///     encoder.put(value).unwrap();
+/// }
+/// encoder.finish().unwrap();
+/// let data = writer.into_inner().unwrap();
+/// assert_eq!(data.len(), 16);
+///
+/// let mut reader = BEBitReader::new(&data[..]);
+/// let decoded: Vec<u64> = SimpleDecoder::new(&mut reader, SimpleScheme::Simple8b, values.len())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, values);
+/// ```
+pub struct SimpleEncoder<'a, E: BitEndianness, W: Write> {
+    writer: &'a mut BitWriter<E, W>,
+    scheme: SimpleScheme,
+    /// Values waiting to be packed.
+    pending: VecDeque<u64>,
+}
+
+impl<'a, E: BitEndianness, W: Write> SimpleEncoder<'a, E, W> {
+    /// Creates a new encoder writing words of `scheme` to `writer`.
+    pub fn new(writer: &'a mut BitWriter<E, W>, scheme: SimpleScheme) -> Self {
+        Self {
+            writer,
+            scheme,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Encodes a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if the value is wider than the maximum value width of the scheme.
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn put(&mut self, value: u64) -> Res<()> {
+        let max_bits = self.scheme.max_value_bits();
+        if value >> max_bits != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{value} does not fit in {max_bits} bits"),
+            ));
+        }
+        self.pending.push_back(value);
+        if self.pending.len() >= self.scheme.selectors()[0].0 {
+            self.write_word(false)?;
+        }
+        Ok(())
+    }
+
+    /// Writes out all buffered values, in words that may be partially filled.
+    pub fn finish(mut self) -> Res<()> {
+        while !self.pending.is_empty() {
+            self.write_word(true)?;
+        }
+        Ok(())
+    }
+
+    /// Packs the next values into a word, using the selector with the most values that all fit.
+    ///
+    /// Unless `last` is set, only selectors that are filled completely are considered.
+    fn write_word(&mut self, last: bool) -> Res<()> {
+        let selectors = self.scheme.selectors();
+        let (selector, count, bits) = selectors
+            .iter()
+            .enumerate()
+            .find_map(|(selector, &(count, bits))| {
+                if self.pending.len() < count && !last {
+                    return None;
+                }
+                let count = std::cmp::min(count, self.pending.len());
+                let fit = self
+                    .pending
+                    .iter()
+                    .take(count)
+                    .all(|&value| value >> bits == 0);
+                fit.then_some((selector, count, bits))
+            })
+            .unwrap_or((selectors.len() - 1, 1, self.scheme.max_value_bits()));
+        self.writer.write_bits_wide(selector as u64, 4)?;
+        for value in self.pending.drain(..count) {
+            self.writer.write_bits_wide(value, bits)?;
+        }
+        let padding = u64::from(self.scheme.max_value_bits()) - count as u64 * u64::from(bits);
+        self.writer.write_zeros(padding)
+    }
+}
+
+/// Decodes values encoded with Simple-8b or Simple-9 word packing.
+///
+/// The decoder is an iterator over the decoded values. It decodes exactly the given number of values, and skips the rest of the last word, so the reader is positioned right after the encoded data afterwards.
+///
+/// See [`SimpleEncoder`] for details and an example.
+pub struct SimpleDecoder<'a, E: BitEndianness, R: Read> {
+    reader: &'a mut BitReader<E, R>,
+    scheme: SimpleScheme,
+    remaining: usize,
+    /// Values of the current word that have not been returned yet.
+    decoded: VecDeque<u64>,
+}
+
+impl<'a, E: BitEndianness, R: Read> SimpleDecoder<'a, E, R> {
+    /// Creates a new decoder reading `num_values` values in words of `scheme` from `reader`.
+    pub fn new(reader: &'a mut BitReader<E, R>, scheme: SimpleScheme, num_values: usize) -> Self {
+        Self {
+            reader,
+            scheme,
+            remaining: num_values,
+            decoded: VecDeque::new(),
+        }
+    }
+
+    fn next_value(&mut self) -> Res<u64> {
+        if let Some(value) = self.decoded.pop_front() {
+            return Ok(value);
+        }
+        let selectors = self.scheme.selectors();
+        let selector = self.reader.read_bits(4)?;
+        let Some(&(count, bits)) = selectors.get(usize::from(selector)) else {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid selector {selector}"),
+            ));
+        };
+        // values of a partially filled last word are padding
+        let used = std::cmp::min(count, self.remaining);
+        for i in 0..count {
+            let value = self.reader.read_bits_wide(bits)?;
+            if i < used {
+                self.decoded.push_back(value);
+            }
+        }
+        let padding = u64::from(self.scheme.max_value_bits()) - count as u64 * u64::from(bits);
+        self.reader.read_reserved_lenient(padding)?;
+        self.decoded
+            .pop_front()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "empty word"))
+    }
+}
+
+impl<E: BitEndianness, R: Read> Iterator for SimpleDecoder<'_, E, R> {
+    type Item = Res<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let value = self.next_value();
+        self.remaining = match value {
+            Ok(_) => self.remaining - 1,
+            Err(_) => 0,
+        };
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BEBitReader, BEBitWriter, LEBitReader, LEBitWriter, SimpleDecoder, SimpleEncoder,
+        SimpleScheme,
+    };
+
+    fn round_trip(values: &[u64], scheme: SimpleScheme) -> usize {
+        let mut writer = LEBitWriter::new(vec![]);
+        let mut encoder = SimpleEncoder::new(&mut writer, scheme);
+        for &value in values {
+            encoder.put(value).unwrap();
+        }
+        encoder.finish().unwrap();
+        writer.write_bits(0x5, 3).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        let decoded: Vec<u64> = SimpleDecoder::new(&mut reader, scheme, values.len())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, values);
+        assert_eq!(reader.read_bits(3).unwrap(), 0x5);
+        data.len() - 1
+    }
+
+    #[test]
+    fn simple_8b() {
+        assert_eq!(round_trip(&[0; 1000], SimpleScheme::Simple8b), 5 * 8);
+        let values: Vec<u64> = (0..1000u64).map(|i| i * i % 1013).collect();
+        round_trip(&values, SimpleScheme::Simple8b);
+        round_trip(&[1 << 59, 0, 1, 2, 3], SimpleScheme::Simple8b);
+        round_trip(&[], SimpleScheme::Simple8b);
+    }
+
+    #[test]
+    fn simple_9() {
+        assert_eq!(round_trip(&[1; 56], SimpleScheme::Simple9), 2 * 4);
+        let values: Vec<u64> = (0..1000u64).map(|i| i * 7919 % 65_521).collect();
+        round_trip(&values, SimpleScheme::Simple9);
+    }
+
+    #[test]
+    fn word_layout() {
+        let mut writer = BEBitWriter::new(vec![]);
+        let mut encoder = SimpleEncoder::new(&mut writer, SimpleScheme::Simple9);
+        encoder.put(0x1234).unwrap();
+        encoder.put(0x0567).unwrap();
+        encoder.finish().unwrap();
+        // selector 7 for 2 values of 14 bits
+        assert_eq!(writer.into_inner().unwrap(), 0x748d_0567u32.to_be_bytes());
+    }
+
+    #[test]
+    fn invalid_selector() {
+        let mut reader = BEBitReader::new(&b"\x90\x00\x00\x00"[..]);
+        let err = SimpleDecoder::new(&mut reader, SimpleScheme::Simple9, 1)
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn too_wide() {
+        let mut writer = LEBitWriter::new(vec![]);
+        let mut encoder = SimpleEncoder::new(&mut writer, SimpleScheme::Simple9);
+        let err = encoder.put(1 << 28).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
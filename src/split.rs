@@ -0,0 +1,164 @@
+use std::io::Read;
+use std::io::Result as Res;
+
+use crate::endian::BitEndianness;
+use crate::{BitEditor, BitReader, BitWriter};
+
+/// An iterator over the segments of a `BitReader` between occurrences of a delimiter bit pattern.
+///
+/// This struct is created by `BitReader::split_on`.
+pub struct Segments<'a, E: BitEndianness, R: Read> {
+    reader: &'a mut BitReader<E, R>,
+    delimiter: u64,
+    width: u8,
+    keep_delimiter: bool,
+    done: bool,
+}
+
+impl<E: BitEndianness, R: Read> Segments<'_, E, R> {
+    fn next_segment(&mut self) -> Res<Option<BitEditor<E>>> {
+        let mask = if self.width == 64 {
+            u64::MAX
+        } else {
+            (1 << self.width) - 1
+        };
+        let mut writer = BitWriter::<E, _>::new(vec![]);
+        let mut len = 0u64;
+        let mut window = 0u64;
+        loop {
+            let Some(bit) = self.reader.read_bit_opt()? else {
+                self.done = true;
+                if len == 0 {
+                    return Ok(None);
+                }
+                break;
+            };
+            writer.write_bit(bit)?;
+            len += 1;
+            window = if E::LSB_FIRST {
+                window >> 1 | u64::from(bit) << (self.width - 1)
+            } else {
+                (window << 1 | u64::from(bit)) & mask
+            };
+            if len >= u64::from(self.width) && window == self.delimiter {
+                if !self.keep_delimiter {
+                    len -= u64::from(self.width);
+                }
+                break;
+            }
+        }
+        Ok(Some(BitEditor::new(writer.finish()?, len)))
+    }
+}
+
+impl<E: BitEndianness, R: Read> Iterator for Segments<'_, E, R> {
+    type Item = Res<BitEditor<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_segment() {
+            Ok(segment) => segment.map(Ok),
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<E: BitEndianness, R: Read> BitReader<E, R> {
+    /// Returns an iterator over the segments of the remaining bits that are separated by a delimiter.
+    ///
+    /// The delimiter is given as a value of `width` bits, assembled in the bit endianness of the reader: with big endian bit numbering, the first bit of the pattern is the most significant, with little endian the least significant. It is searched for bit by bit, at any bit offset. The segments are yielded as [`BitEditor`]s.
+    ///
+    /// Like `str::split_terminator`, a delimiter at the start or two adjacent delimiters produce empty segments, but a delimiter at the end of the stream does not. If `keep_delimiter` is set, each delimiter is kept at the end of the segment it terminates, like with `str::split_inclusive`. Iteration stops at the end of the stream, which is only detected at a byte boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is 0 or > 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// // two frames of 5 bits, each followed by the marker 0b1111110
+    /// let mut reader = BEBitReader::new(&b"\x97\xe4\x7e"[..]);
+    /// let segments: Vec<_> = reader
+    ///     .split_on(0b111_1110, 7, false)
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    /// assert_eq!(segments.len(), 2);
+    /// assert_eq!(segments[0].len(), 5);
+    /// assert_eq!(segments[0].as_bytes(), b"\x90");
+    /// assert_eq!(segments[1].len(), 5);
+    /// assert_eq!(segments[1].as_bytes(), b"\x40");
+    /// ```
+    pub fn split_on(
+        &mut self,
+        delimiter: u64,
+        width: u8,
+        keep_delimiter: bool,
+    ) -> Segments<'_, E, R> {
+        assert!(
+            (1..=64).contains(&width),
+            "delimiter width must be between 1 and 64"
+        );
+        Segments {
+            reader: self,
+            delimiter,
+            width,
+            keep_delimiter,
+            done: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, BEBitWriter, LEBitReader, LEBitWriter};
+
+    #[test]
+    fn keep_delimiter() {
+        let mut reader = BEBitReader::new(&b"\x97\xe4\x7e\x80"[..]);
+        let segments: Vec<_> = reader
+            .split_on(0b111_1110, 7, true)
+            .map(|s| s.unwrap().len())
+            .collect();
+        assert_eq!(segments, [12, 12, 8]);
+    }
+
+    #[test]
+    fn little_endian() {
+        let mut writer = LEBitWriter::new(vec![]);
+        for payload in [0x0u8, 0x2, 0x0] {
+            writer.write_bits(0xf, 4).unwrap();
+            writer.write_bits(payload, 3).unwrap();
+        }
+        writer.write_bits(0xf, 4).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        let segments: Vec<_> = reader.split_on(0xf, 4, false).map(|s| s.unwrap()).collect();
+        // the leading delimiter gives an empty segment, the trailing padding a last one
+        assert_eq!(segments.len(), 5);
+        assert!(segments[0].is_empty());
+        for (segment, payload) in segments[1..4].iter().zip([0x0, 0x2, 0x0]) {
+            assert_eq!(segment.as_bytes(), [payload]);
+        }
+        assert_eq!(segments[4].len(), 7);
+    }
+
+    #[test]
+    fn delimiters_dont_overlap() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bits(0b111, 3).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        let lens: Vec<_> = reader
+            .split_on(0b11, 2, false)
+            .map(|s| s.unwrap().len())
+            .collect();
+        assert_eq!(lens, [0, 6]);
+    }
+}
@@ -0,0 +1,291 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::{BitEndianness, ByteOrder};
+use crate::{BitReader, BitWriter};
+
+/// Reads exactly `len` bytes, growing the buffer as the data arrives so that a corrupt length can't allocate more than the stream holds.
+fn read_bytes(reader: &mut impl Read, len: u64) -> Res<Vec<u8>> {
+    let mut bytes = vec![];
+    reader.take(len).read_to_end(&mut bytes)?;
+    if (bytes.len() as u64) < len {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "failed to fill whole buffer",
+        ));
+    }
+    Ok(bytes)
+}
+
+impl<E: BitEndianness, R: Read> BitReader<E, R> {
+    /// Reads a UTF-16 string of `len` code units in the given byte order.
+    ///
+    /// The string may be at any bit offset, the code units are read through the shifted `Read` implementation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the data is not valid UTF-16.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{BEBitReader, ByteOrder};
+    ///
+    /// let mut reader = BEBitReader::new(&b"H\0i\0"[..]);
+    /// assert_eq!(reader.read_string_utf16(2, ByteOrder::Little).unwrap(), "Hi");
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_string_utf16(&mut self, len: usize, byte_order: ByteOrder) -> Res<String> {
+        let byte_len = (len as u64)
+            .checked_mul(2)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "string length overflows"))?;
+        let bytes = read_bytes(self, byte_len)?;
+        let units = bytes.chunks_exact(2).map(|unit| {
+            let unit = [unit[0], unit[1]];
+            match byte_order {
+                ByteOrder::Big => u16::from_be_bytes(unit),
+                ByteOrder::Little => u16::from_le_bytes(unit),
+            }
+        });
+        char::decode_utf16(units)
+            .collect::<Result<String, _>>()
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Reads a null-terminated UTF-8 string.
+    ///
+    /// Bytes are read until a zero byte is found, which is consumed but not included in the string. The string may be at any bit offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the data is not valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"Hi\0!"[..]);
+    /// assert_eq!(reader.read_cstring().unwrap(), "Hi");
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_cstring(&mut self) -> Res<String> {
+        let mut bytes = vec![];
+        loop {
+            match self.read_bits(8)? {
+                0 => break,
+                byte => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Reads a UTF-8 string prefixed by its length in bytes, where the length is a bit field of `prefix_bits` bits.
+    ///
+    /// The length prefix is read in the bit endianness of the reader, like `read_bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_bits` > 64.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the data is not valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x12\x83\x48"[..]);
+    /// assert_eq!(reader.read_prefixed_string(5).unwrap(), "Pi");
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_prefixed_string(&mut self, prefix_bits: u8) -> Res<String> {
+        let len = self.read_bits_wide(prefix_bits)?;
+        let bytes = read_bytes(self, len)?;
+        String::from_utf8(bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+impl<E: BitEndianness, W: Write> BitWriter<E, W> {
+    /// Writes a string as UTF-16 in the given byte order.
+    ///
+    /// No length or terminator is written, only the code units.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::{BEBitWriter, ByteOrder};
+    ///
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_string_utf16("Hi", ByteOrder::Little).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"H\0i\0");
+    /// ```
+    pub fn write_string_utf16(&mut self, string: &str, byte_order: ByteOrder) -> Res<()> {
+        let mut bytes = Vec::with_capacity(string.len() * 2);
+        for unit in string.encode_utf16() {
+            bytes.extend_from_slice(&match byte_order {
+                ByteOrder::Big => unit.to_be_bytes(),
+                ByteOrder::Little => unit.to_le_bytes(),
+            });
+        }
+        self.write_all(&bytes)
+    }
+
+    /// Writes a string followed by a zero byte.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if the string contains a zero byte, since it could not be read back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_cstring("Hi").unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"Hi\0");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_cstring(&mut self, string: &str) -> Res<()> {
+        if string.as_bytes().contains(&0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "string contains a null byte",
+            ));
+        }
+        self.write_all(string.as_bytes())?;
+        self.write_bits(0, 8)
+    }
+
+    /// Writes a string prefixed by its length in bytes, where the length is a bit field of `prefix_bits` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_bits` > 64.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if the length does not fit in `prefix_bits` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_prefixed_string("Pi", 5).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x12\x83\x48");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn write_prefixed_string(&mut self, string: &str, prefix_bits: u8) -> Res<()> {
+        let len = string.len() as u64;
+        if prefix_bits < 64 && len >> prefix_bits != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("string length {len} does not fit in {prefix_bits} bits"),
+            ));
+        }
+        self.write_bits_wide(len, prefix_bits)?;
+        self.write_all(string.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, BEBitWriter, ByteOrder, LEBitReader, LEBitWriter};
+
+    #[test]
+    fn utf16_roundtrip_shifted() {
+        let mut vec = vec![];
+        {
+            let mut writer = LEBitWriter::new(&mut vec);
+            writer.write_bits(0x03, 2).unwrap();
+            writer
+                .write_string_utf16("Grüße 🦀", ByteOrder::Big)
+                .unwrap();
+        }
+        let mut reader = LEBitReader::new(&vec[..]);
+        assert_eq!(reader.read_bits(2).unwrap(), 0x03);
+        assert_eq!(
+            reader.read_string_utf16(8, ByteOrder::Big).unwrap(),
+            "Grüße 🦀"
+        );
+    }
+
+    #[test]
+    fn huge_lengths() {
+        use std::io::ErrorKind;
+
+        let mut reader = BEBitReader::new(&b"\xff\xff\xff\xff\xff\xff\xff\xffHi"[..]);
+        let err = reader.read_prefixed_string(64).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        let mut reader = BEBitReader::new(&b"H\0i\0"[..]);
+        let err = reader
+            .read_string_utf16(usize::MAX, ByteOrder::Little)
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::InvalidInput | ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn utf16_byte_order() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_string_utf16("A", ByteOrder::Big).unwrap();
+        writer.write_string_utf16("A", ByteOrder::Little).unwrap();
+        assert_eq!(writer.into_inner().unwrap(), b"\0AA\0");
+    }
+
+    #[test]
+    fn cstring_roundtrip_shifted() {
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bit(true).unwrap();
+        writer.write_cstring("first").unwrap();
+        writer.write_cstring("").unwrap();
+        writer.write_cstring("third").unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert!(reader.read_bit().unwrap());
+        assert_eq!(reader.read_cstring().unwrap(), "first");
+        assert_eq!(reader.read_cstring().unwrap(), "");
+        assert_eq!(reader.read_cstring().unwrap(), "third");
+    }
+
+    #[test]
+    fn cstring_interior_null() {
+        let mut writer = BEBitWriter::new(vec![]);
+        let err = writer.write_cstring("a\0b").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn prefixed_string_wide_prefix() {
+        let string = "x".repeat(300);
+        for prefix_bits in [9, 12, 16, 64] {
+            let mut writer = LEBitWriter::new(vec![]);
+            writer.write_prefixed_string(&string, prefix_bits).unwrap();
+            let data = writer.into_inner().unwrap();
+            let mut reader = LEBitReader::new(&data[..]);
+            assert_eq!(reader.read_prefixed_string(prefix_bits).unwrap(), string);
+        }
+    }
+
+    #[test]
+    fn prefixed_string_too_long() {
+        let mut writer = BEBitWriter::new(vec![]);
+        let err = writer.write_prefixed_string("toolong", 2).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn utf16_unpaired_surrogate() {
+        let mut reader = BEBitReader::new(&b"\xd8\x00\x00A"[..]);
+        let err = reader.read_string_utf16(2, ByteOrder::Big).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
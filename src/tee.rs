@@ -0,0 +1,130 @@
+use std::io::Result as Res;
+use std::io::{Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// Mirrors every bit read from a [`BitReader`] into a [`BitWriter`].
+///
+/// The mirror receives exactly the bits the decoder consumed, at the same bit offsets, so its output can be logged or compared with the input of another decoder. Bits skipped by aligning the underlying reader are not mirrored, use `align` on this wrapper to read and mirror them instead.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, BEBitWriter, TeeBits};
+///
+/// let mut reader = TeeBits::new(BEBitReader::new(&b"\x12\x34"[..]), BEBitWriter::new(vec![]));
+/// reader.read_bits(4).unwrap();
+/// reader.read_bit().unwrap();
+/// let (_, mirror) = reader.into_parts();
+/// assert_eq!(mirror.bit_position(), 5);
+/// assert_eq!(mirror.into_inner().unwrap(), b"\x10");
+/// ```
+pub struct TeeBits<E: BitEndianness, R: Read, W: Write> {
+    inner: BitReader<E, R>,
+    mirror: BitWriter<E, W>,
+}
+
+impl<E: BitEndianness, R: Read, W: Write> TeeBits<E, R, W> {
+    /// Creates a new `TeeBits` mirroring everything read from `inner` into `mirror`.
+    #[inline]
+    pub fn new(inner: BitReader<E, R>, mirror: BitWriter<E, W>) -> Self {
+        Self { inner, mirror }
+    }
+
+    /// Gets a reference to the underlying `BitReader`.
+    #[inline]
+    pub fn get_ref(&self) -> &BitReader<E, R> {
+        &self.inner
+    }
+
+    /// Gets a reference to the `BitWriter` receiving the mirrored bits.
+    #[inline]
+    pub fn mirror(&self) -> &BitWriter<E, W> {
+        &self.mirror
+    }
+
+    /// Returns whether the reader is aligned to the byte boundary.
+    #[inline]
+    pub fn is_aligned(&self) -> bool {
+        self.inner.is_aligned()
+    }
+
+    /// Aligns to byte boundary.
+    ///
+    /// Unlike `BitReader::align`, the rest of the partial byte is read and mirrored instead of being discarded.
+    pub fn align(&mut self) -> Res<()> {
+        if !self.is_aligned() {
+            let count = 8 - self.inner.bit_offset();
+            self.read_bits(count)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a single bit, see `BitReader::read_bit`.
+    pub fn read_bit(&mut self) -> Res<bool> {
+        let bit = self.inner.read_bit()?;
+        self.mirror.write_bit(bit)?;
+        Ok(bit)
+    }
+
+    /// Reads 8 bits or less, see `BitReader::read_bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8.
+    pub fn read_bits(&mut self, count: u8) -> Res<u8> {
+        let bits = self.inner.read_bits(count)?;
+        self.mirror.write_bits(bits, count)?;
+        Ok(bits)
+    }
+
+    /// Reads 64 bits or less, see `BitReader::read_bits_u64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    pub fn read_bits_u64(&mut self, count: u8) -> Res<u64> {
+        let bits = self.inner.read_bits_u64(count)?;
+        self.mirror.write_bits_u64(bits, count)?;
+        Ok(bits)
+    }
+
+    /// Returns the underlying `BitReader` and the `BitWriter` with the mirrored bits.
+    ///
+    /// The mirror is not aligned, so that more bits can be appended to it.
+    pub fn into_parts(self) -> (BitReader<E, R>, BitWriter<E, W>) {
+        (self.inner, self.mirror)
+    }
+}
+
+/// Read bytes while mirroring them.
+impl<E: BitEndianness, R: Read, W: Write> Read for TeeBits<E, R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> Res<usize> {
+        let count_read = self.inner.read(buf)?;
+        self.mirror.write_all(&buf[..count_read])?;
+        Ok(count_read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LEBitReader, LEBitWriter, TeeBits};
+    use std::io::Read;
+
+    #[test]
+    fn mirrors_consumed_bits() {
+        let data = b"\xab\xcd\xef\x12\x34\x56\x78\x9a\xbc\xde";
+        let mut reader = TeeBits::new(LEBitReader::new(&data[..]), LEBitWriter::new(vec![]));
+        reader.read_bit().unwrap();
+        reader.read_bits(6).unwrap();
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        reader.read_bits_u64(40).unwrap();
+        reader.align().unwrap();
+        assert_eq!(reader.mirror().bit_position(), 64);
+        let (mut inner, mirror) = reader.into_parts();
+        assert_eq!(mirror.into_inner().unwrap(), data[..8]);
+        assert_eq!(inner.read_bits(8).unwrap(), 0xbc);
+    }
+}
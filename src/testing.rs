@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::Read;
+use std::io::Result as Res;
+
+use crate::BitReader;
+use crate::endian::BitEndianness;
+
+/// Number of equal bits shown before the first difference.
+const CONTEXT_BITS: usize = 24;
+
+/// Asserts that bytes match a pattern of bits written as text, like `"1010 0111 01"`.
+///
+/// The pattern lists the bits of each byte from the most significant to the least significant, like they are written in binary, regardless of the bit endianness the bytes were written with. Whitespace and `_` are ignored. If the number of bits is not a multiple of 8, the remaining bits of the last byte are not compared, but the number of bytes has to match.
+///
+/// On failure, the panic message shows both sequences in binary with the first differing bit marked.
+///
+/// # Panics
+///
+/// Panics if the bytes don't match, or if the pattern contains characters other than `0`, `1`, whitespace and `_`.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitWriter, assert_bits_eq};
+///
+/// let mut writer = BEBitWriter::new(vec![]);
+/// writer.write_bits(0xa, 4).unwrap();
+/// writer.write_bits(0x7, 6).unwrap();
+/// assert_bits_eq!(writer.into_inner().unwrap(), "1010 0001 11");
+/// ```
+#[macro_export]
+macro_rules! assert_bits_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        if let Some(diff) =
+            $crate::bits_diff(::core::convert::AsRef::<[u8]>::as_ref(&$actual), $expected)
+        {
+            panic!("{}", diff);
+        }
+    };
+}
+
+/// Asserts that two `BitReader`s yield the same bits until their end.
+///
+/// Both readers are read bit by bit with `read_bit_opt`, so they may use different bit endianness. On failure, the panic message shows the bits before and after the first difference in stream order.
+///
+/// # Panics
+///
+/// Panics if the streams differ or have different lengths, or if reading fails.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, LEBitReader, assert_streams_eq};
+///
+/// let mut left = BEBitReader::new(&b"\x80\x01"[..]);
+/// let mut right = LEBitReader::new(&b"\x01\x80"[..]);
+/// assert_streams_eq!(left, right);
+/// ```
+#[macro_export]
+macro_rules! assert_streams_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match $crate::streams_diff(&mut $left, &mut $right) {
+            Ok(None) => {}
+            Ok(Some(diff)) => panic!("{}", diff),
+            Err(e) => panic!("failed to read streams: {}", e),
+        }
+    };
+}
+
+/// Compares bytes to a pattern of bits, returning a description of the first difference.
+///
+/// This is the function behind [`assert_bits_eq!`], see there for the pattern format.
+///
+/// # Panics
+///
+/// Panics if the pattern contains characters other than `0`, `1`, whitespace and `_`.
+#[must_use]
+pub fn bits_diff(actual: &[u8], expected: &str) -> Option<String> {
+    let expected: Vec<bool> = expected
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '_')
+        .map(|c| match c {
+            '0' => false,
+            '1' => true,
+            _ => panic!("invalid character {c:?} in bit pattern"),
+        })
+        .collect();
+    let actual: Vec<bool> = actual
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 != 0))
+        .collect();
+    let first_diff = expected.iter().zip(&actual).position(|(e, a)| e != a);
+    let headline = if let Some(index) = first_diff {
+        format!("bits differ at bit {index}")
+    } else if actual.len() != expected.len().div_ceil(8) * 8 {
+        format!(
+            "expected {} bits in {} bytes, got {} bytes",
+            expected.len(),
+            expected.len().div_ceil(8),
+            actual.len() / 8
+        )
+    } else {
+        return None;
+    };
+    let marker = first_diff.unwrap_or(std::cmp::min(expected.len(), actual.len()));
+    Some(render(
+        &headline,
+        ("expected", "actual"),
+        0,
+        (&expected, &actual),
+        marker,
+    ))
+}
+
+/// Compares the bits of two readers until their end, returning a description of the first difference.
+///
+/// This is the function behind [`assert_streams_eq!`].
+pub fn streams_diff<E1: BitEndianness, R1: Read, E2: BitEndianness, R2: Read>(
+    left: &mut BitReader<E1, R1>,
+    right: &mut BitReader<E2, R2>,
+) -> Res<Option<String>> {
+    let mut history = VecDeque::with_capacity(CONTEXT_BITS + 1);
+    let mut offset = 0u64;
+    loop {
+        let (l, r) = (left.read_bit_opt()?, right.read_bit_opt()?);
+        if l == r {
+            let Some(bit) = l else {
+                return Ok(None);
+            };
+            history.push_back(bit);
+            if history.len() > CONTEXT_BITS {
+                history.pop_front();
+            }
+            offset += 1;
+            continue;
+        }
+        let headline = match (l, r) {
+            (None, _) => format!("left stream ends at bit {offset}"),
+            (_, None) => format!("right stream ends at bit {offset}"),
+            _ => format!("streams differ at bit {offset}"),
+        };
+        let left_bits = context_bits(&history, l, || left.read_bit_opt())?;
+        let right_bits = context_bits(&history, r, || right.read_bit_opt())?;
+        let start = offset - history.len() as u64;
+        return Ok(Some(render(
+            &headline,
+            ("left", "right"),
+            start,
+            (&left_bits, &right_bits),
+            history.len(),
+        )));
+    }
+}
+
+/// Returns the bits of `history`, followed by `first` and the rest of its byte if it is not the end of the stream.
+fn context_bits(
+    history: &VecDeque<bool>,
+    first: Option<bool>,
+    mut next: impl FnMut() -> Res<Option<bool>>,
+) -> Res<Vec<bool>> {
+    let mut bits: Vec<bool> = history.iter().copied().collect();
+    if let Some(bit) = first {
+        bits.push(bit);
+        while bits.len() < history.len() + 8 {
+            match next()? {
+                Some(bit) => bits.push(bit),
+                None => break,
+            }
+        }
+    }
+    Ok(bits)
+}
+
+/// Formats two labeled bit sequences starting at bit `start` below each other, with a caret under bit `marker` of the sequences.
+fn render(
+    headline: &str,
+    labels: (&str, &str),
+    start: u64,
+    bits: (&[bool], &[bool]),
+    marker: usize,
+) -> String {
+    let (first, column) = format_bits(start, bits.0, marker);
+    let (second, _) = format_bits(start, bits.1, marker);
+    let mut out = String::new();
+    let _ = writeln!(out, "{headline}");
+    let _ = writeln!(out, "{:>8}: {first}", labels.0);
+    let _ = writeln!(out, "{:>8}: {second}", labels.1);
+    let _ = write!(out, "{:>1$}", "^", 10 + column + 1);
+    out
+}
+
+/// Formats bits in groups of the bytes they belong to, returning the string and the column of bit `marker`.
+fn format_bits(start: u64, bits: &[bool], marker: usize) -> (String, usize) {
+    let mut out = String::new();
+    let mut column = 0;
+    for i in 0..=bits.len() {
+        if i > 0 && (start + i as u64).is_multiple_of(8) && i < bits.len() {
+            out.push(' ');
+        }
+        if i == marker {
+            column = out.len();
+        }
+        if let Some(&bit) = bits.get(i) {
+            out.push(if bit { '1' } else { '0' });
+        }
+    }
+    (out, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, LEBitReader, bits_diff, streams_diff};
+
+    #[test]
+    fn bits_diff_message() {
+        assert_eq!(bits_diff(b"\xa7\x40", "1010_0111 01"), None);
+        assert_eq!(bits_diff(b"\xa7\x48", "1010 0111 0100 1"), None);
+        let expected = [
+            "bits differ at bit 10",
+            "expected: 10100111 0110",
+            "  actual: 10100111 01000000",
+            "                     ^",
+        ];
+        assert_eq!(
+            bits_diff(b"\xa7\x40", "1010 0111 0110").unwrap(),
+            expected.join("\n")
+        );
+        let short = bits_diff(b"\xa7", "1010 0111 0").unwrap();
+        assert!(short.starts_with("expected 9 bits in 2 bytes, got 1 bytes"));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid character")]
+    fn invalid_pattern() {
+        let _ = bits_diff(b"\x00", "0102");
+    }
+
+    #[test]
+    fn streams() {
+        let mut left = BEBitReader::new(&b"\x12\x34\x56"[..]);
+        let mut right = LEBitReader::new(&b"\x48\x2c"[..]);
+        let diff = streams_diff(&mut left, &mut right).unwrap().unwrap();
+        assert!(diff.starts_with("right stream ends at bit 16\n"), "{diff}");
+
+        let mut left = BEBitReader::new(&b"\x12\x34"[..]);
+        let mut right = BEBitReader::new(&b"\x12\x24"[..]);
+        let diff = streams_diff(&mut left, &mut right).unwrap().unwrap();
+        let expected = [
+            "streams differ at bit 11",
+            "    left: 00010010 00110100",
+            "   right: 00010010 00100100",
+            "                      ^",
+        ];
+        assert_eq!(diff, expected.join("\n"));
+    }
+}
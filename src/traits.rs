@@ -0,0 +1,425 @@
+use std::io::Result as Res;
+use std::io::{Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitDigest, BitReader, BitTake, BitWriter, DigestWriter, TeeBits};
+
+/// Reading bits, implemented by `BitReader` and its adapters.
+///
+/// Write decoders against this trait to use them with any bit endianness and with adapters like `BitTake` or `TeeBits`, without naming the concrete reader type. The trait is object safe, so a `&mut dyn BitRead` or `Box<dyn BitRead>` works too.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitReader, BitRead, LEBitReader};
+///
+/// fn read_flags(reader: &mut dyn BitRead) -> std::io::Result<u8> {
+///     let count = reader.read_bits(2)?;
+///     reader.read_bits(count)
+/// }
+///
+/// assert_eq!(read_flags(&mut BEBitReader::new(&b"\xf0"[..])).unwrap(), 0x06);
+/// assert_eq!(read_flags(&mut LEBitReader::new(&b"\x1b"[..])).unwrap(), 0x06);
+/// ```
+pub trait BitRead {
+    /// Reads a single bit, see `BitReader::read_bit`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the underlying reader.
+    fn read_bit(&mut self) -> Res<bool>;
+
+    /// Reads 8 bits or less, see `BitReader::read_bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the underlying reader.
+    fn read_bits(&mut self, count: u8) -> Res<u8>;
+
+    /// Reads 64 bits or less, see `BitReader::read_bits_u64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the underlying reader.
+    fn read_bits_u64(&mut self, count: u8) -> Res<u64>;
+
+    /// Returns whether the reader is aligned to the byte boundary.
+    fn is_aligned(&self) -> bool;
+
+    /// Aligns to byte boundary.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the underlying reader, for adapters that read the rest of the partial byte.
+    fn align(&mut self) -> Res<()>;
+}
+
+impl<E: BitEndianness, R: Read> BitRead for BitReader<E, R> {
+    #[inline]
+    fn read_bit(&mut self) -> Res<bool> {
+        BitReader::read_bit(self)
+    }
+
+    #[inline]
+    fn read_bits(&mut self, count: u8) -> Res<u8> {
+        BitReader::read_bits(self, count)
+    }
+
+    #[inline]
+    fn read_bits_u64(&mut self, count: u8) -> Res<u64> {
+        BitReader::read_bits_u64(self, count)
+    }
+
+    #[inline]
+    fn is_aligned(&self) -> bool {
+        BitReader::is_aligned(self)
+    }
+
+    #[inline]
+    fn align(&mut self) -> Res<()> {
+        BitReader::align(self);
+        Ok(())
+    }
+}
+
+impl<E: BitEndianness, R: Read> BitRead for BitTake<'_, E, R> {
+    #[inline]
+    fn read_bit(&mut self) -> Res<bool> {
+        BitReader::read_bit(self)
+    }
+
+    #[inline]
+    fn read_bits(&mut self, count: u8) -> Res<u8> {
+        BitReader::read_bits(self, count)
+    }
+
+    #[inline]
+    fn read_bits_u64(&mut self, count: u8) -> Res<u64> {
+        BitReader::read_bits_u64(self, count)
+    }
+
+    #[inline]
+    fn is_aligned(&self) -> bool {
+        BitReader::is_aligned(self)
+    }
+
+    #[inline]
+    fn align(&mut self) -> Res<()> {
+        BitReader::align(self);
+        Ok(())
+    }
+}
+
+/// Reads the rest of the partial byte when aligning, so that it is mirrored too.
+impl<E: BitEndianness, R: Read, W: Write> BitRead for TeeBits<E, R, W> {
+    #[inline]
+    fn read_bit(&mut self) -> Res<bool> {
+        TeeBits::read_bit(self)
+    }
+
+    #[inline]
+    fn read_bits(&mut self, count: u8) -> Res<u8> {
+        TeeBits::read_bits(self, count)
+    }
+
+    #[inline]
+    fn read_bits_u64(&mut self, count: u8) -> Res<u64> {
+        TeeBits::read_bits_u64(self, count)
+    }
+
+    #[inline]
+    fn is_aligned(&self) -> bool {
+        TeeBits::is_aligned(self)
+    }
+
+    #[inline]
+    fn align(&mut self) -> Res<()> {
+        TeeBits::align(self)
+    }
+}
+
+macro_rules! impl_bit_read_forward {
+    () => {
+        #[inline]
+        fn read_bit(&mut self) -> Res<bool> {
+            (**self).read_bit()
+        }
+
+        #[inline]
+        fn read_bits(&mut self, count: u8) -> Res<u8> {
+            (**self).read_bits(count)
+        }
+
+        #[inline]
+        fn read_bits_u64(&mut self, count: u8) -> Res<u64> {
+            (**self).read_bits_u64(count)
+        }
+
+        #[inline]
+        fn is_aligned(&self) -> bool {
+            (**self).is_aligned()
+        }
+
+        #[inline]
+        fn align(&mut self) -> Res<()> {
+            (**self).align()
+        }
+    };
+}
+
+/// Read bits through a mutable reference, like `Read` does.
+impl<T: BitRead + ?Sized> BitRead for &mut T {
+    impl_bit_read_forward!();
+}
+
+/// Read bits through a box, like `Read` does, so a `Box<dyn BitRead>` can be passed to generic decoders.
+impl<T: BitRead + ?Sized> BitRead for Box<T> {
+    impl_bit_read_forward!();
+}
+
+/// Writing bits, implemented by `BitWriter` and its adapters.
+///
+/// This is the writing counterpart of [`BitRead`], for encoders generic over the sink. Byte writes and `flush` come from the [`Write`] supertrait. The trait is object safe.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::{BEBitWriter, BitWrite};
+///
+/// fn write_flags(writer: &mut dyn BitWrite, flags: u8) -> std::io::Result<()> {
+///     writer.write_bits(3, 2)?;
+///     writer.write_bits(flags, 3)?;
+///     writer.align()
+/// }
+///
+/// let mut writer = BEBitWriter::new(vec![]);
+/// write_flags(&mut writer, 0x06).unwrap();
+/// assert_eq!(writer.into_inner().unwrap(), b"\xf0");
+/// ```
+///
+/// [`Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+pub trait BitWrite: Write {
+    /// Writes a single bit, see `BitWriter::write_bit`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the underlying writer.
+    fn write_bit(&mut self, bit: bool) -> Res<()>;
+
+    /// Writes 8 bits or less, see `BitWriter::write_bits`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 8.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the underlying writer.
+    fn write_bits(&mut self, bits: u8, count: u8) -> Res<()>;
+
+    /// Writes 64 bits or less, see `BitWriter::write_bits_u64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the underlying writer.
+    fn write_bits_u64(&mut self, value: u64, count: u8) -> Res<()>;
+
+    /// Returns whether the writer is aligned to the byte boundary.
+    fn is_aligned(&self) -> bool;
+
+    /// Aligns to byte boundary, see `BitWriter::align`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the underlying writer.
+    fn align(&mut self) -> Res<()>;
+}
+
+impl<E: BitEndianness, W: Write> BitWrite for BitWriter<E, W> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> Res<()> {
+        BitWriter::write_bit(self, bit)
+    }
+
+    #[inline]
+    fn write_bits(&mut self, bits: u8, count: u8) -> Res<()> {
+        BitWriter::write_bits(self, bits, count)
+    }
+
+    #[inline]
+    fn write_bits_u64(&mut self, value: u64, count: u8) -> Res<()> {
+        BitWriter::write_bits_u64(self, value, count)
+    }
+
+    #[inline]
+    fn is_aligned(&self) -> bool {
+        BitWriter::is_aligned(self)
+    }
+
+    #[inline]
+    fn align(&mut self) -> Res<()> {
+        BitWriter::align(self)
+    }
+}
+
+impl<E: BitEndianness, W: Write, D: BitDigest> BitWrite for DigestWriter<E, W, D> {
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> Res<()> {
+        DigestWriter::write_bit(self, bit)
+    }
+
+    #[inline]
+    fn write_bits(&mut self, bits: u8, count: u8) -> Res<()> {
+        DigestWriter::write_bits(self, bits, count)
+    }
+
+    #[inline]
+    fn write_bits_u64(&mut self, value: u64, count: u8) -> Res<()> {
+        assert!(count <= 64);
+        DigestWriter::write_bits_u128(self, value.into(), count)
+    }
+
+    #[inline]
+    fn is_aligned(&self) -> bool {
+        DigestWriter::is_aligned(self)
+    }
+
+    #[inline]
+    fn align(&mut self) -> Res<()> {
+        DigestWriter::align(self)
+    }
+}
+
+macro_rules! impl_bit_write_forward {
+    () => {
+        #[inline]
+        fn write_bit(&mut self, bit: bool) -> Res<()> {
+            (**self).write_bit(bit)
+        }
+
+        #[inline]
+        fn write_bits(&mut self, bits: u8, count: u8) -> Res<()> {
+            (**self).write_bits(bits, count)
+        }
+
+        #[inline]
+        fn write_bits_u64(&mut self, value: u64, count: u8) -> Res<()> {
+            (**self).write_bits_u64(value, count)
+        }
+
+        #[inline]
+        fn is_aligned(&self) -> bool {
+            (**self).is_aligned()
+        }
+
+        #[inline]
+        fn align(&mut self) -> Res<()> {
+            (**self).align()
+        }
+    };
+}
+
+/// Write bits through a mutable reference, like `Write` does.
+impl<T: BitWrite + ?Sized> BitWrite for &mut T {
+    impl_bit_write_forward!();
+}
+
+/// Write bits through a box, like `Write` does, so a `Box<dyn BitWrite>` can be passed to generic encoders.
+impl<T: BitWrite + ?Sized> BitWrite for Box<T> {
+    impl_bit_write_forward!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitRead, BitWrite};
+    use crate::{BEBitReader, BEBitWriter, BitDigest, DigestWriter, TeeBits};
+
+    fn read_record(reader: &mut impl BitRead) -> std::io::Result<(bool, u64)> {
+        let flag = reader.read_bit()?;
+        let value = reader.read_bits_u64(10)?;
+        reader.align()?;
+        Ok((flag, value))
+    }
+
+    #[test]
+    fn generic_decoder() {
+        let data = b"\xc0\x40\xff";
+        let mut reader = BEBitReader::new(&data[..]);
+        assert_eq!(
+            read_record(&mut reader.take_bits(16)).unwrap(),
+            (true, 0x202)
+        );
+        assert!(read_record(&mut reader.take_bits(8)).is_err());
+
+        let mut tee = TeeBits::new(BEBitReader::new(&data[..]), BEBitWriter::new(vec![]));
+        assert_eq!(read_record(&mut tee).unwrap(), (true, 0x202));
+        assert!(BitRead::is_aligned(&tee));
+        let (_, mirror) = tee.into_parts();
+        assert_eq!(mirror.into_inner().unwrap(), data[..2]);
+    }
+
+    fn write_record(writer: &mut impl BitWrite, flag: bool, value: u64) -> std::io::Result<()> {
+        writer.write_bit(flag)?;
+        writer.write_bits_u64(value, 10)?;
+        writer.align()?;
+        writer.flush()
+    }
+
+    #[derive(Default)]
+    struct Collect(Vec<u8>);
+
+    impl BitDigest for Collect {
+        fn update(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    #[test]
+    fn generic_encoder() {
+        let mut writer = BEBitWriter::new(vec![]);
+        write_record(&mut writer, true, 0x202).unwrap();
+        assert!(BitWrite::is_aligned(&writer));
+        assert_eq!(writer.into_inner().unwrap(), b"\xc0\x40");
+
+        let mut writer = DigestWriter::new(BEBitWriter::new(vec![]), Collect::default());
+        write_record(&mut writer, false, 0x3ff).unwrap();
+        let (_, digest) = writer.finish().unwrap();
+        assert_eq!(digest.0, b"\x7f\xe0");
+    }
+
+    #[test]
+    fn forwarding() {
+        fn read_twice(mut reader: impl BitRead) -> std::io::Result<(u8, u8)> {
+            Ok((reader.read_bits(4)?, reader.read_bits(4)?))
+        }
+
+        fn write_twice(mut writer: impl BitWrite) -> std::io::Result<()> {
+            writer.write_bits(0x01, 4)?;
+            writer.write_bits_u64(0x02, 4)
+        }
+
+        let mut reader = BEBitReader::new(&b"\x12\x34"[..]);
+        assert_eq!(read_twice(&mut reader).unwrap(), (1, 2));
+        let boxed: Box<dyn BitRead + '_> = Box::new(&mut reader);
+        assert_eq!(read_twice(boxed).unwrap(), (3, 4));
+
+        let mut writer = BEBitWriter::new(vec![]);
+        write_twice(&mut writer).unwrap();
+        let mut boxed: Box<dyn BitWrite> = Box::new(BEBitWriter::new(vec![]));
+        write_twice(&mut boxed).unwrap();
+        assert!(boxed.is_aligned());
+        assert_eq!(writer.into_inner().unwrap(), b"\x12");
+    }
+}
@@ -0,0 +1,204 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+/// A step of a `Transcoder`.
+enum Step {
+    Field {
+        from: u8,
+        to: u8,
+        map: Option<Box<dyn Fn(u64) -> u64>>,
+    },
+    Skip(u64),
+    Insert {
+        value: u64,
+        width: u8,
+    },
+    AlignInput,
+    AlignOutput,
+}
+
+/// Converts records from one bit layout to another.
+///
+/// A transcoder is a list of steps, built with the methods below. Running it reads each field from a `BitReader` in the source layout, optionally transforms the value with a closure, and writes it to a `BitWriter` in the target layout. Fields can also be dropped from or inserted into the output, and the input and output can be aligned independently, so padding differences between the layouts are handled by the transcoder.
+///
+/// The source and target may use different bit endianness.
+///
+/// # Examples
+///
+/// Convert a record with a 4-bit version and a 12-bit length to one with an 8-bit version, a 16-bit length in bytes instead of bits, and a new flag:
+///
+/// ```
+/// use endio_bit::{BEBitReader, BEBitWriter, Transcoder};
+///
+/// let mut transcoder = Transcoder::new();
+/// transcoder
+///     .copy(4, 8)
+///     .map(12, 16, |bits| bits / 8)
+///     .insert(1, 1)
+///     .align_output();
+///
+/// let mut reader = BEBitReader::new(&b"\x21\x00"[..]);
+/// let mut writer = BEBitWriter::new(vec![]);
+/// transcoder.run(&mut reader, &mut writer).unwrap();
+/// assert_eq!(writer.into_inner().unwrap(), b"\x02\x00\x20\x80");
+/// ```
+#[derive(Default)]
+pub struct Transcoder {
+    steps: Vec<Step>,
+}
+
+impl Transcoder {
+    /// Creates a transcoder without any steps.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copies a field of `from` bits in the input to a field of `to` bits in the output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` > 64.
+    pub fn copy(&mut self, from: u8, to: u8) -> &mut Self {
+        assert!(from <= 64 && to <= 64, "fields can be at most 64 bits wide");
+        self.steps.push(Step::Field {
+            from,
+            to,
+            map: None,
+        });
+        self
+    }
+
+    /// Reads a field of `from` bits, transforms it with `map`, and writes the result as a field of `to` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` > 64.
+    pub fn map(&mut self, from: u8, to: u8, map: impl Fn(u64) -> u64 + 'static) -> &mut Self {
+        assert!(from <= 64 && to <= 64, "fields can be at most 64 bits wide");
+        self.steps.push(Step::Field {
+            from,
+            to,
+            map: Some(Box::new(map)),
+        });
+        self
+    }
+
+    /// Skips `count` bits of the input, dropping them from the output.
+    pub fn skip(&mut self, count: u64) -> &mut Self {
+        self.steps.push(Step::Skip(count));
+        self
+    }
+
+    /// Writes a constant field of `width` bits to the output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` > 64.
+    pub fn insert(&mut self, value: u64, width: u8) -> &mut Self {
+        assert!(width <= 64, "fields can be at most 64 bits wide");
+        self.steps.push(Step::Insert { value, width });
+        self
+    }
+
+    /// Aligns the input to the byte boundary, discarding the rest of a partially read byte.
+    pub fn align_input(&mut self) -> &mut Self {
+        self.steps.push(Step::AlignInput);
+        self
+    }
+
+    /// Aligns the output to the byte boundary, padding with zeros.
+    pub fn align_output(&mut self) -> &mut Self {
+        self.steps.push(Step::AlignOutput);
+        self
+    }
+
+    /// Transcodes one record from `reader` to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if a value does not fit in its output field.
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn run<E1: BitEndianness, R: Read, E2: BitEndianness, W: Write>(
+        &self,
+        reader: &mut BitReader<E1, R>,
+        writer: &mut BitWriter<E2, W>,
+    ) -> Res<()> {
+        for (index, step) in self.steps.iter().enumerate() {
+            match step {
+                Step::Field { from, to, map } => {
+                    let value = reader.read_bits_wide(*from)?;
+                    let value = map.as_ref().map_or(value, |map| map(value));
+                    write_field(writer, index, value, *to)?;
+                }
+                Step::Skip(count) => reader.read_reserved_lenient(*count).map(|_| ())?,
+                Step::Insert { value, width } => write_field(writer, index, *value, *width)?,
+                Step::AlignInput => reader.align(),
+                Step::AlignOutput => writer.align()?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_field<E: BitEndianness, W: Write>(
+    writer: &mut BitWriter<E, W>,
+    index: usize,
+    value: u64,
+    width: u8,
+) -> Res<()> {
+    if width < 64 && value >> width != 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("value {value} of step {index} does not fit in {width} bits"),
+        ));
+    }
+    writer.write_bits_wide(value, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, BEBitWriter, LEBitReader, LEBitWriter, Transcoder};
+
+    #[test]
+    fn between_bit_orders() {
+        let mut transcoder = Transcoder::new();
+        transcoder
+            .copy(3, 3)
+            .skip(5)
+            .align_input()
+            .copy(16, 16)
+            .align_output();
+        let mut reader = BEBitReader::new(&b"\xbf\x12\x34\xa0\x56\x78"[..]);
+        let mut writer = LEBitWriter::new(vec![]);
+        transcoder.run(&mut reader, &mut writer).unwrap();
+        transcoder.run(&mut reader, &mut writer).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut check = LEBitReader::new(&data[..]);
+        for (small, large) in [(5, 0x1234), (5, 0x5678)] {
+            assert_eq!(check.read_bits(3).unwrap(), small);
+            assert_eq!(check.read_bits_wide(16).unwrap(), large);
+            check.align();
+        }
+    }
+
+    #[test]
+    fn value_does_not_fit() {
+        let mut transcoder = Transcoder::new();
+        transcoder.map(8, 4, |value| value + 1);
+        let mut writer = BEBitWriter::new(vec![]);
+        assert!(
+            transcoder
+                .run(&mut BEBitReader::new(&b"\x0e"[..]), &mut writer)
+                .is_ok()
+        );
+        let err = transcoder
+            .run(&mut BEBitReader::new(&b"\x0f"[..]), &mut writer)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,311 @@
+use std::io::Result as Res;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::endian::BitEndianness;
+use crate::{BitReader, BitWriter};
+
+fn overflow() -> Error {
+    Error::new(ErrorKind::InvalidData, "varint does not fit in 64 bits")
+}
+
+impl<E: BitEndianness, R: Read> BitReader<E, R> {
+    /// Reads a big endian base-128 variable-length quantity, as used in MIDI files.
+    ///
+    /// Each byte carries 7 bits of the value, most significant group first, with the high bit set on every byte except the last. The bytes may be at any bit offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the value does not fit in a `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x81\x80\x00"[..]);
+    /// assert_eq!(reader.read_vlq().unwrap(), 0x4000);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_vlq(&mut self) -> Res<u64> {
+        let mut value = 0u64;
+        loop {
+            let byte = self.read_bits(8)?;
+            if value >> 57 != 0 {
+                return Err(overflow());
+            }
+            value = (value << 7) | u64::from(byte & 0x7f);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Reads an offset varint as used by git packfiles for `OFS_DELTA` offsets.
+    ///
+    /// This is like [`read_vlq`](Self::read_vlq), but each continuation adds one to the value before shifting, so that every value has exactly one encoding.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the value does not fit in a `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitReader;
+    /// let mut reader = BEBitReader::new(&b"\x80\x00"[..]);
+    /// assert_eq!(reader.read_git_varint().unwrap(), 128);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_git_varint(&mut self) -> Res<u64> {
+        let mut byte = self.read_bits(8)?;
+        let mut value = u64::from(byte & 0x7f);
+        while byte & 0x80 != 0 {
+            byte = self.read_bits(8)?;
+            value = value.checked_add(1).ok_or_else(overflow)?;
+            if value >> 57 != 0 {
+                return Err(overflow());
+            }
+            value = (value << 7) | u64::from(byte & 0x7f);
+        }
+        Ok(value)
+    }
+
+    /// Reads a variable bit rate integer in chunks of `width` bits, as used in LLVM bitcode.
+    ///
+    /// Each chunk is read like `read_bits_u64(width)`. Its lowest `width - 1` bits carry a part of the value, least significant part first, and its highest bit is set if another chunk follows. LLVM bitcode is read with little endian bit numbering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not between 2 and 64.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`] if the value does not fit in a `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitReader;
+    /// // 27 in chunks of 4 bits: 1011, then 0011
+    /// let mut reader = LEBitReader::new(&b"\x3b"[..]);
+    /// assert_eq!(reader.read_vbr(4).unwrap(), 27);
+    /// ```
+    ///
+    /// [`InvalidData`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidData
+    pub fn read_vbr(&mut self, width: u8) -> Res<u64> {
+        assert!(
+            (2..=64).contains(&width),
+            "VBR width must be between 2 and 64"
+        );
+        let continuation = 1 << (width - 1);
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let chunk = self.read_bits_u64(width)?;
+            let part = chunk & (continuation - 1);
+            if shift < 64 && (part << shift) >> shift == part {
+                value |= part << shift;
+            } else if part != 0 {
+                return Err(overflow());
+            }
+            if chunk & continuation == 0 {
+                return Ok(value);
+            }
+            shift += u32::from(width) - 1;
+        }
+    }
+}
+
+impl<E: BitEndianness, W: Write> BitWriter<E, W> {
+    /// Writes a big endian base-128 variable-length quantity, as used in MIDI files.
+    ///
+    /// See `BitReader::read_vlq` for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_vlq(0x4000).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x81\x80\x00");
+    /// ```
+    pub fn write_vlq(&mut self, value: u64) -> Res<()> {
+        let mut buf = [0; 10];
+        let mut pos = buf.len() - 1;
+        buf[pos] = (value & 0x7f) as u8;
+        let mut rest = value >> 7;
+        while rest != 0 {
+            pos -= 1;
+            buf[pos] = 0x80 | (rest & 0x7f) as u8;
+            rest >>= 7;
+        }
+        self.write_all(&buf[pos..])
+    }
+
+    /// Writes an offset varint as used by git packfiles for `OFS_DELTA` offsets.
+    ///
+    /// See `BitReader::read_git_varint` for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_git_varint(128).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x80\x00");
+    /// ```
+    pub fn write_git_varint(&mut self, value: u64) -> Res<()> {
+        let mut buf = [0; 10];
+        let mut pos = buf.len() - 1;
+        buf[pos] = (value & 0x7f) as u8;
+        let mut rest = value >> 7;
+        while rest != 0 {
+            rest -= 1;
+            pos -= 1;
+            buf[pos] = 0x80 | (rest & 0x7f) as u8;
+            rest >>= 7;
+        }
+        self.write_all(&buf[pos..])
+    }
+
+    /// Writes a variable bit rate integer in chunks of `width` bits, as used in LLVM bitcode.
+    ///
+    /// See `BitReader::read_vbr` for the format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not between 2 and 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitWriter;
+    /// let mut writer = LEBitWriter::new(vec![]);
+    /// writer.write_vbr(4, 27).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x3b");
+    /// ```
+    pub fn write_vbr(&mut self, width: u8, value: u64) -> Res<()> {
+        assert!(
+            (2..=64).contains(&width),
+            "VBR width must be between 2 and 64"
+        );
+        let continuation = 1 << (width - 1);
+        let mut rest = value;
+        while rest >= continuation {
+            self.write_bits_wide(rest & (continuation - 1) | continuation, width)?;
+            rest >>= width - 1;
+        }
+        self.write_bits_wide(rest, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BEBitReader, BEBitWriter, LEBitReader, LEBitWriter};
+
+    const VALUES: [u64; 9] = [
+        0,
+        1,
+        0x7f,
+        0x80,
+        0x3fff,
+        0x4000,
+        0x0fff_ffff,
+        1 << 56,
+        u64::MAX,
+    ];
+
+    #[test]
+    fn vlq_midi_examples() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_vlq(0x7f).unwrap();
+        writer.write_vlq(0x80).unwrap();
+        writer.write_vlq(0x0fff_ffff).unwrap();
+        assert_eq!(
+            writer.into_inner().unwrap(),
+            b"\x7f\x81\x00\xff\xff\xff\x7f"
+        );
+    }
+
+    #[test]
+    fn vlq_roundtrip_shifted() {
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bits(0x05, 3).unwrap();
+        for value in VALUES {
+            writer.write_vlq(value).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert_eq!(reader.read_bits(3).unwrap(), 0x05);
+        for value in VALUES {
+            assert_eq!(reader.read_vlq().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn vlq_overflow() {
+        let mut reader = BEBitReader::new(&[0xff; 11][..]);
+        assert_eq!(
+            reader.read_vlq().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn git_varint_roundtrip_shifted() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bit(true).unwrap();
+        for value in VALUES {
+            writer.write_git_varint(value).unwrap();
+        }
+        let data = writer.into_inner().unwrap();
+        let mut reader = BEBitReader::new(&data[..]);
+        assert!(reader.read_bit().unwrap());
+        for value in VALUES {
+            assert_eq!(reader.read_git_varint().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn vbr_roundtrip() {
+        for width in [2, 4, 6, 8, 32, 64] {
+            let mut writer = BEBitWriter::new(vec![]);
+            writer.write_bits(0x05, 3).unwrap();
+            for value in VALUES {
+                writer.write_vbr(width, value).unwrap();
+            }
+            let data = writer.into_inner().unwrap();
+            let mut reader = BEBitReader::new(&data[..]);
+            assert_eq!(reader.read_bits(3).unwrap(), 0x05);
+            for value in VALUES {
+                assert_eq!(reader.read_vbr(width).unwrap(), value);
+            }
+        }
+    }
+
+    #[test]
+    fn vbr_overflow() {
+        let mut writer = LEBitWriter::new(vec![]);
+        for _ in 0..9 {
+            writer.write_bits(0xff, 8).unwrap();
+        }
+        writer.write_bits(0x02, 8).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = LEBitReader::new(&data[..]);
+        assert_eq!(
+            reader.read_vbr(8).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        // zero chunks past 64 bits are accepted
+        let mut reader = LEBitReader::new(&b"\xff\x80\x00"[..]);
+        assert_eq!(reader.read_vbr(8).unwrap(), 0x7f);
+    }
+
+    #[test]
+    fn git_varint_differs_from_vlq() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_git_varint(0x4000).unwrap();
+        assert_eq!(writer.into_inner().unwrap(), b"\xff\x00");
+    }
+}
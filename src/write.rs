@@ -1,13 +1,43 @@
 use std::io::Result as Res;
-use std::io::Write;
+use std::io::{Error, ErrorKind, Write};
 
 use crate::endian::{BE, BitEndianness, LE};
+use crate::read::reverse_low_bits;
 
 /// Writes most significant bits first.
 pub type BEBitWriter<W> = BitWriter<BE, W>;
 /// Writes least significant bits first.
 pub type LEBitWriter<W> = BitWriter<LE, W>;
 
+/// Wraps any writer into a `BitWriter` by method call, for building chains of writers.
+///
+/// # Examples
+///
+/// ```
+/// use endio_bit::BitWriterExt;
+/// use std::io::BufWriter;
+///
+/// let mut writer = BufWriter::new(vec![]).be_bit_writer();
+/// writer.write_bits(0x01, 4).unwrap();
+/// let buffered = writer.into_inner().unwrap();
+/// assert_eq!(buffered.into_inner().unwrap(), b"\x10");
+/// ```
+pub trait BitWriterExt: Write + Sized {
+    /// Wraps this writer into a `BitWriter` writing most significant bits first.
+    #[inline]
+    fn be_bit_writer(self) -> BEBitWriter<Self> {
+        BitWriter::new(self)
+    }
+
+    /// Wraps this writer into a `BitWriter` writing least significant bits first.
+    #[inline]
+    fn le_bit_writer(self) -> LEBitWriter<Self> {
+        BitWriter::new(self)
+    }
+}
+
+impl<W: Write> BitWriterExt for W {}
+
 /// An error returned by `BitWriter::into_inner`.
 ///
 /// This is a clone of [`std::io::IntoInnerError`]. The semantics and API are the exact same. Ideally I'd use `std::io::IntoInnerError` directly, but its constructor is not public.
@@ -27,13 +57,40 @@ impl<W> IntoInnerError<W> {
     pub fn into_inner(self) -> W {
         self.0
     }
+
+    /// Wraps the writer in another type, keeping the error.
+    #[inline]
+    pub(crate) fn map<U>(self, f: impl FnOnce(W) -> U) -> IntoInnerError<U> {
+        IntoInnerError(f(self.0), self.1)
+    }
+}
+
+impl<W> From<IntoInnerError<W>> for std::io::Error {
+    #[inline]
+    fn from(iie: IntoInnerError<W>) -> Self {
+        iie.1
+    }
+}
+
+/// What a `BitWriter` does when it is dropped with data that has not been written to the underlying writer yet.
+///
+/// Data is pending if a partial byte has been written, or if bytes are left in the internal buffer of a `BitWriter` created with `with_buffer`. Writers that are finished with `finish` or `into_inner` never have pending data when dropped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// Writes out the pending data, ignoring any errors. This is the default.
+    #[default]
+    Flush,
+    /// Writes out the pending data like `Flush`, and prints a warning to stderr in debug builds.
+    Warn,
+    /// Panics without writing the pending data, unless the thread is already panicking. Useful in tests to catch writers which are never finished.
+    Panic,
 }
 
 /// Adds bit-level writing support to something implementing [`std::io::Write`].
 ///
 /// This is accomplished through an internal buffer for storing partially read bytes. Note that this buffer is for correctness, not performance - if you want to improve performance by buffering, use [`std::io::BufWriter`] as the `BitWriter`'s write target.
 ///
-/// When the `BitWriter` is dropped, the partially written byte will be written out. However, any errors that happen in the process of flushing the buffer when the writer is dropped will be ignored. Code that wishes to handle such errors must manually call `flush` before the writer is dropped.
+/// When the `BitWriter` is dropped, the partially written byte will be padded and written out, use `set_padding` to choose the padding bits. However, any errors that happen in the process of flushing the buffer when the writer is dropped will be ignored. Code that wishes to handle such errors must call `finish` instead of dropping the writer. Use `set_drop_policy` to be warned about or panic on writers dropped with pending data.
 ///
 /// To use this writer, you'll have to choose a bit endianness to write in. The bit endianness determines the direction in which bits in a byte will be written. Note that this is distinct from byte endianness, and e.g. a format which is little endian at the byte level is not necessarily little endian at the bit level.
 ///
@@ -49,7 +106,16 @@ pub struct BitWriter<E: BitEndianness, W: Write> {
     bit_offset: u8,
     /// Storage for remaining bits after an unaligned write operation.
     bit_buffer: u8,
-    buffer: Vec<u8>,
+    /// Number of bits written since construction.
+    position: u64,
+    /// Scratch space for shifting data in the unaligned `Write` implementation.
+    shift_buffer: Vec<u8>,
+    /// Completed bytes not yet written to `inner`, only used if `out_capacity` is nonzero.
+    out: Vec<u8>,
+    out_capacity: usize,
+    drop_policy: DropPolicy,
+    /// Bits filling the partial byte on `finish`, `into_inner` and drop, as passed to `align_with_pattern`.
+    padding: u8,
     phantom: std::marker::PhantomData<E>,
 }
 
@@ -82,27 +148,190 @@ impl<E: BitEndianness, W: Write> BitWriter<E, W> {
             inner: Some(inner),
             bit_offset: 0,
             bit_buffer: 0,
-            buffer: vec![0; capacity],
+            position: 0,
+            shift_buffer: vec![0; capacity],
+            out: vec![],
+            out_capacity: 0,
+            drop_policy: DropPolicy::Flush,
+            padding: 0x00,
             phantom: std::marker::PhantomData,
         }
     }
 
+    /// Creates a new `BitWriter` which collects completed bytes in an internal buffer of `capacity` bytes before writing them to the underlying writer.
+    ///
+    /// By default, every completed byte is written to the underlying writer individually. With a buffer, the underlying writer is only written to when the buffer is full, on `flush`, `into_inner`, and when the `BitWriter` is dropped. Unlike wrapping the underlying writer in a [`std::io::BufWriter`], this doesn't change the type of the `BitWriter`, and `flush` writes out the buffered bytes and the partial byte together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use endio_bit::BEBitWriter;
+    /// use std::io::Write;
+    ///
+    /// let mut writer = BEBitWriter::with_buffer(64, vec![]);
+    /// writer.write_bits(0xab, 8).unwrap();
+    /// assert_eq!(writer.get_ref(), b"");
+    /// assert_eq!(writer.buffer(), b"\xab");
+    /// writer.flush().unwrap();
+    /// assert_eq!(writer.get_ref(), b"\xab");
+    /// ```
+    ///
+    /// [`std::io::BufWriter`]: https://doc.rust-lang.org/std/io/struct.BufWriter.html
+    #[inline]
+    pub fn with_buffer(capacity: usize, inner: W) -> Self {
+        let mut writer = Self::new(inner);
+        writer.out = Vec::with_capacity(capacity);
+        writer.out_capacity = capacity;
+        writer
+    }
+
     /// Returns whether the writer is aligned to the byte boundary.
-    #[inline(always)]
+    #[inline]
     pub fn is_aligned(&self) -> bool {
         self.bit_offset == 0
     }
 
+    /// Returns the offset of the next bit to be written within the current byte, which is 0 if the writer is aligned.
+    #[inline]
+    pub fn bit_offset(&self) -> u8 {
+        self.bit_offset
+    }
+
+    /// Returns the number of bits left to write until the writer is aligned.
+    #[inline]
+    pub fn bits_until_aligned(&self) -> u8 {
+        (8 - self.bit_offset) % 8
+    }
+
+    /// Returns the number of bits written to the current byte and their value, as `BitReader::read_bits` with that count would return it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitWriter;
+    /// let mut writer = LEBitWriter::new(vec![]);
+    /// writer.write_bits(0x05, 3).unwrap();
+    /// assert_eq!(writer.buffered_bits(), (3, 0x05));
+    /// assert_eq!(writer.bits_until_aligned(), 5);
+    /// ```
+    pub fn buffered_bits(&self) -> (u8, u8) {
+        let count = self.bit_offset;
+        if count == 0 {
+            return (0, 0);
+        }
+        let bits = E::shift_lsb(self.bit_buffer, 8 - count);
+        (count, E::align_right(bits, count))
+    }
+
+    /// Returns the number of bits written since construction.
+    ///
+    /// This counts the bits passed to the `BitWriter`, including the padding bits of `align`, whether or not they have been written to the underlying writer yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bits(0x05, 3).unwrap();
+    /// assert_eq!(writer.bit_position(), 3);
+    /// writer.align().unwrap();
+    /// assert_eq!(writer.bit_position(), 8);
+    /// ```
+    #[inline]
+    pub fn bit_position(&self) -> u64 {
+        self.position
+    }
+
     /// Aligns to byte boundary, skipping a partial byte if the `BitWriter` was not aligned.
     #[inline]
     pub fn align(&mut self) -> Res<()> {
         if !self.is_aligned() {
             self.flush_buffer()?;
+            self.position += u64::from(8 - self.bit_offset);
             self.bit_offset = 0;
         }
         Ok(())
     }
 
+    /// Aligns to byte boundary, filling the rest of a partial byte with `bit` instead of zeros.
+    ///
+    /// This is for formats padding with one bits, like JPEG entropy-coded segments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bits(0x00, 3).unwrap();
+    /// writer.align_with(true).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x1f");
+    /// ```
+    #[doc(alias = "pad_to_byte_with")]
+    pub fn align_with(&mut self, bit: bool) -> Res<()> {
+        self.align_with_pattern(if bit { 0xff } else { 0x00 })
+    }
+
+    /// Aligns to byte boundary, filling the rest of a partial byte with the bits of `pattern` at the same positions.
+    ///
+    /// `pattern` is the byte as it would be written as a whole, so only its bits after the current bit offset are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitWriter;
+    /// let mut writer = LEBitWriter::new(vec![]);
+    /// writer.write_bits(0x07, 3).unwrap();
+    /// writer.align_with_pattern(0x55).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x57");
+    /// ```
+    pub fn align_with_pattern(&mut self, pattern: u8) -> Res<()> {
+        if self.is_aligned() {
+            return Ok(());
+        }
+        let bits = if E::LSB_FIRST {
+            pattern >> self.bit_offset
+        } else {
+            pattern
+        };
+        self.write_bits(bits, 8 - self.bit_offset)
+    }
+
+    /// Aligns to a multiple of `n` bits by writing zero bits up to it.
+    ///
+    /// The boundary is relative to where the writer started, as counted by `bit_position`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bit(true).unwrap();
+    /// writer.align_to(16).unwrap();
+    /// writer.write_bits(0x12, 8).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x80\x00\x12");
+    /// ```
+    #[doc(alias = "pad_to_alignment")]
+    pub fn align_to(&mut self, n: u64) -> Res<()> {
+        assert!(n != 0, "alignment must not be 0");
+        let rem = self.position % n;
+        if rem == 0 {
+            return Ok(());
+        }
+        self.write_zeros(n - rem)
+    }
+
+    /// Returns the completed bytes in the internal buffer which have not been written to the underlying writer yet.
+    ///
+    /// This is always empty if the `BitWriter` was not created with `with_buffer`.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.out
+    }
+
     /// Gets a reference to the underlying writer.
     ///
     /// ```compile_fail
@@ -113,47 +342,207 @@ impl<E: BitEndianness, W: Write> BitWriter<E, W> {
     /// ```
     #[inline]
     pub fn get_ref(&self) -> &W {
-        self.inner.as_ref().unwrap()
+        self.inner()
     }
 
     /// Gets a mutable reference to the underlying writer.
     ///
-    /// Mutable operations on the underlying writer will corrupt this `BitWriter` if it is not aligned, so the reference is only returned if the `BitWriter` is aligned.
+    /// Mutable operations on the underlying writer will corrupt this `BitWriter` if it is not aligned, so the reference is only returned if the `BitWriter` is aligned. Bytes in the internal buffer are not written out by this, call `flush` first if the `BitWriter` was created with `with_buffer`.
+    ///
+    /// # Panics
     ///
     /// Panics if the `BitWriter` is not aligned.
     #[inline]
     pub fn get_mut(&mut self) -> &mut W {
         assert!(self.is_aligned(), "BitWriter is not aligned");
-        self.inner.as_mut().unwrap()
+        self.inner_mut()
     }
 
     /// Gets a mutable reference to the underlying writer.
     ///
-    /// Use with care: Any writing/seeking/etc operation on the underlying writer will corrupt this `BitWriter` if it is not aligned.
+    /// # Safety
+    ///
+    /// Any writing/seeking/etc operation on the underlying writer will corrupt this `BitWriter` if it is not aligned.
     #[inline]
     pub unsafe fn get_mut_unchecked(&mut self) -> &mut W {
-        self.inner.as_mut().unwrap()
+        self.inner_mut()
+    }
+
+    /// Sets what happens when this `BitWriter` is dropped with pending data.
+    ///
+    /// # Examples
+    ///
+    /// ```should_panic
+    /// use endio_bit::{BEBitWriter, DropPolicy};
+    ///
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.set_drop_policy(DropPolicy::Panic);
+    /// writer.write_bit(true).unwrap();
+    /// // panics, since the partial byte would be written on drop
+    /// ```
+    #[inline]
+    pub fn set_drop_policy(&mut self, policy: DropPolicy) {
+        self.drop_policy = policy;
+    }
+
+    /// Sets the bits filling the partial byte when this `BitWriter` is finished, unwrapped with `into_inner` or dropped, instead of zeros.
+    ///
+    /// The pattern is used like with `align_with_pattern`. Explicit calls to `align` still pad with zeros.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut vec = vec![];
+    /// {
+    ///     let mut writer = BEBitWriter::new(&mut vec);
+    ///     writer.set_padding(0xff);
+    ///     writer.write_bits(0x00, 3).unwrap();
+    /// }
+    /// assert_eq!(vec, b"\x1f");
+    /// ```
+    #[inline]
+    pub fn set_padding(&mut self, pattern: u8) {
+        self.padding = pattern;
+    }
+
+    /// Pads the partial byte with the configured padding, writes out all buffered data, flushes the underlying writer and returns it.
+    ///
+    /// Unlike `into_inner`, this also flushes the underlying writer, and returns a plain `std::io::Error`. On error, the `BitWriter` is dropped like with `DropPolicy::Flush`, whatever its drop policy, since the error is returned to the caller.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bits(5, 3).unwrap();
+    /// assert_eq!(writer.finish().unwrap(), b"\xa0");
+    /// ```
+    pub fn finish(mut self) -> Res<W> {
+        let result = self
+            .align_with_pattern(self.padding)
+            .and_then(|()| self.flush_out())
+            .and_then(|()| self.inner_mut().flush());
+        if let Err(e) = result {
+            // the caller gets the error, so don't panic or warn on drop
+            self.drop_policy = DropPolicy::Flush;
+            return Err(e);
+        }
+        match self.inner.take() {
+            Some(inner) => Ok(inner),
+            None => unreachable!(),
+        }
+    }
+
+    /// Returns whether there is data which has not been written to the underlying writer.
+    fn has_pending(&self) -> bool {
+        !self.is_aligned() || !self.out.is_empty()
     }
 
     /// Unwraps this `BitWriter`, returning the underlying writer.
     ///
-    /// The buffer for partial writes will be flushed before returning the writer. If an error occurs during the flushing it will be returned.
+    /// The buffer for partial writes will be padded with the configured padding and flushed before returning the writer. If an error occurs during the flushing it will be returned.
     #[inline]
     pub fn into_inner(mut self) -> Result<W, IntoInnerError<Self>> {
-        match self.align() {
-            Ok(()) => Ok(self.inner.take().unwrap()),
+        match self
+            .align_with_pattern(self.padding)
+            .and_then(|()| self.flush_out())
+        {
+            Ok(()) => match self.inner.take() {
+                Some(inner) => Ok(inner),
+                None => unreachable!(),
+            },
             Err(e) => Err(IntoInnerError(self, e)),
         }
     }
 
+    /// The inner writer is only taken out by `into_inner`, so it is always present otherwise.
+    fn inner(&self) -> &W {
+        self.inner.as_ref().unwrap()
+    }
+
+    pub(crate) fn inner_mut(&mut self) -> &mut W {
+        self.inner.as_mut().unwrap()
+    }
+
+    /// Writes out the partial byte with its unwritten bits taken from `rest`, before seeking the underlying writer.
+    pub(crate) fn complete_partial(&mut self, rest: u8) -> Res<()> {
+        self.bit_buffer |= rest & E::shift_lsb(0xff, self.bit_offset);
+        self.flush_buffer()?;
+        self.bit_offset = 0;
+        self.flush_out()
+    }
+
+    #[inline]
+    pub(crate) fn set_bit_position(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Continues writing at bit `to` of the stream after the underlying writer has been seeked to its byte, moving the position by the distance from bit `from`.
+    ///
+    /// The bits of `byte` before `to` are kept as the start of the partial byte.
+    pub(crate) fn reset_after_seek(&mut self, from: u64, to: u64, byte: u8) {
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = (to % 8) as u8;
+        self.bit_offset = offset;
+        self.bit_buffer = if offset == 0 {
+            0
+        } else {
+            byte & !E::shift_lsb(0xff, offset)
+        };
+        self.position = if to >= from {
+            self.position + (to - from)
+        } else {
+            self.position.saturating_sub(from - to)
+        };
+    }
+
     fn flush_buffer(&mut self) -> Res<()> {
-        let mut temp = [0; 1];
-        temp[0] = self.bit_buffer;
-        unsafe { self.get_mut_unchecked() }.write(&temp)?;
+        self.emit(&[self.bit_buffer])?;
         self.bit_buffer = 0;
         Ok(())
     }
 
+    /// Writes completed bytes, through the internal buffer if there is one.
+    fn emit(&mut self, bytes: &[u8]) -> Res<()> {
+        if self.out_capacity == 0 {
+            return self.inner_mut().write_all(bytes);
+        }
+        if self.out.len() + bytes.len() > self.out_capacity {
+            self.flush_out()?;
+        }
+        if bytes.len() >= self.out_capacity {
+            self.inner_mut().write_all(bytes)
+        } else {
+            self.out.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    /// Writes the internal buffer to the underlying writer, keeping any bytes which could not be written.
+    pub(crate) fn flush_out(&mut self) -> Res<()> {
+        let mut written = 0;
+        let inner = self.inner.as_mut().unwrap();
+        let res = loop {
+            if written == self.out.len() {
+                break Ok(());
+            }
+            match inner.write(&self.out[written..]) {
+                Ok(0) => {
+                    break Err(std::io::Error::new(
+                        std::io::ErrorKind::WriteZero,
+                        "failed to write the buffered data",
+                    ));
+                }
+                Ok(n) => written += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        self.out.drain(..written);
+        res
+    }
+
     /// Writes a single bit, writing 1 for true, 0 for false.
     ///
     /// # Examples
@@ -178,12 +567,51 @@ impl<E: BitEndianness, W: Write> BitWriter<E, W> {
             self.bit_buffer |= E::shift_lsb(E::shift_msb(0xff, 7), self.bit_offset);
         }
         self.bit_offset = (self.bit_offset + 1) % 8;
+        self.position += 1;
         if self.is_aligned() {
             self.flush_buffer()?;
         }
         Ok(())
     }
 
+    /// Writes a slice of flags as bits, in order.
+    ///
+    /// The flags are packed into bytes before writing, so this is faster than calling `write_bit` for each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitWriter;
+    /// let mut writer = LEBitWriter::new(vec![]);
+    /// writer.write_bools(&[true, false, true]).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x05");
+    /// ```
+    pub fn write_bools(&mut self, flags: &[bool]) -> Res<()> {
+        for chunk in flags.chunks(8) {
+            let mut bits = 0;
+            for (len, &flag) in (0..).zip(chunk) {
+                bits = E::push_bits(bits, len, flag.into(), 1);
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            self.write_bits(bits as u8, chunk.len() as u8)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an array of flags as one bit each, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bool_array(&[true, false, true]).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\xa0");
+    /// ```
+    pub fn write_bool_array<const N: usize>(&mut self, flags: &[bool; N]) -> Res<()> {
+        self.write_bools(flags)
+    }
+
     /// Writes 8 bits or less.
     ///
     /// The lowest `count` bits will be used, others will be ignored.
@@ -213,6 +641,9 @@ impl<E: BitEndianness, W: Write> BitWriter<E, W> {
     /// ```
     pub fn write_bits(&mut self, bits: u8, count: u8) -> Res<()> {
         assert!(count <= 8);
+        if count == 0 {
+            return Ok(());
+        }
         let start = self.bit_offset;
         let end = start + count;
         let bits = bits << (8 - count);
@@ -225,6 +656,334 @@ impl<E: BitEndianness, W: Write> BitWriter<E, W> {
             self.bit_buffer = E::shift_msb(bits, 8 - start);
         }
         self.bit_offset = end % 8;
+        self.position += u64::from(count);
+        Ok(())
+    }
+
+    /// Writes 8 bits or less, returning an error instead of panicking if `count` is out of range.
+    ///
+    /// Use this instead of `write_bits` when the count comes from untrusted input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidInput`] if `count` > 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// assert!(writer.try_write_bits(31, 9).is_err());
+    /// writer.try_write_bits(31, 5).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\xf8");
+    /// ```
+    ///
+    /// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+    pub fn try_write_bits(&mut self, bits: u8, count: u8) -> Res<()> {
+        if count > 8 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot write more than 8 bits at once",
+            ));
+        }
+        self.write_bits(bits, count)
+    }
+
+    /// Writes 4 bits.
+    ///
+    /// This is the same as `write_bits(nibble, 4)`. The lowest 4 bits will be used, others will be ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_nibble(4).unwrap();
+    /// writer.write_nibble(2).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x42");
+    /// ```
+    pub fn write_nibble(&mut self, nibble: u8) -> Res<()> {
+        self.write_bits(nibble, 4)
+    }
+
+    /// Writes 16 bits or less.
+    ///
+    /// The lowest `count` bits of `value` are used. With big endian bit numbering, the most significant of these is written first, with little endian the least significant. This is the counterpart of `BitReader::read_bits_u16`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 16.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bits(0x0a, 4).unwrap();
+    /// writer.write_bits_u16(0xbcd, 12).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\xab\xcd");
+    /// ```
+    ///
+    /// ```
+    /// # use endio_bit::LEBitWriter;
+    /// let mut writer = LEBitWriter::new(vec![]);
+    /// writer.write_bits(0x0b, 4).unwrap();
+    /// writer.write_bits_u16(0xcda, 12).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\xab\xcd");
+    /// ```
+    pub fn write_bits_u16(&mut self, value: u16, count: u8) -> Res<()> {
+        assert!(count <= 16);
+        self.write_bits_wide(value.into(), count)
+    }
+
+    /// Writes 32 bits or less.
+    ///
+    /// See `write_bits_u16` for how the bits are split.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bits_u32(0x12_3456, 24).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x12\x34\x56");
+    /// ```
+    pub fn write_bits_u32(&mut self, value: u32, count: u8) -> Res<()> {
+        assert!(count <= 32);
+        self.write_bits_wide(value.into(), count)
+    }
+
+    /// Writes 64 bits or less.
+    ///
+    /// See `write_bits_u16` for how the bits are split.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitWriter;
+    /// let mut writer = LEBitWriter::new(vec![]);
+    /// writer.write_bits_u64(0x9a_7856_3412, 40).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x12\x34\x56\x78\x9a");
+    /// ```
+    pub fn write_bits_u64(&mut self, value: u64, count: u8) -> Res<()> {
+        self.write_bits_wide(value, count)
+    }
+
+    /// Writes 128 bits or less.
+    ///
+    /// The lowest `count` bits of `value` are used. With big endian bit numbering, the most significant of these is written first, with little endian the least significant.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 128.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitWriter;
+    /// let mut writer = LEBitWriter::new(vec![]);
+    /// writer.write_bits(0x1, 4).unwrap();
+    /// writer.write_bits_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef, 124).unwrap();
+    /// let vec = writer.into_inner().unwrap();
+    /// assert_eq!(vec[..3], [0xf1, 0xde, 0xbc]);
+    /// assert_eq!(vec[15], 0x12);
+    /// ```
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn write_bits_u128(&mut self, value: u128, count: u8) -> Res<()> {
+        assert!(count <= 128);
+        let low = std::cmp::min(count, 64);
+        let (low_bits, high_bits) = (value as u64, (value >> 64) as u64);
+        if E::LSB_FIRST {
+            self.write_bits_wide(low_bits, low)?;
+            self.write_bits_wide(high_bits, count - low)
+        } else {
+            self.write_bits_wide(high_bits, count - low)?;
+            self.write_bits_wide(low_bits, low)
+        }
+    }
+
+    /// Writes 64 bits or less, with the most significant of the lowest `count` bits of `value` written first, whatever the bit endianness of the writer.
+    ///
+    /// This is the counterpart of `BitReader::read_bits_be`: the value is written bit-reversed by a little endian writer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::LEBitWriter;
+    /// let mut writer = LEBitWriter::new(vec![]);
+    /// writer.write_bits_be(0x0d, 4).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\x0b");
+    /// ```
+    pub fn write_bits_be(&mut self, value: u64, count: u8) -> Res<()> {
+        assert!(count <= 64);
+        if E::LSB_FIRST {
+            self.write_bits_wide(reverse_low_bits(value, count), count)
+        } else {
+            self.write_bits_wide(value, count)
+        }
+    }
+
+    /// Writes 64 bits or less, with the least significant bit of `value` written first, whatever the bit endianness of the writer.
+    ///
+    /// This is the counterpart of `BitReader::read_bits_le`: the value is written bit-reversed by a big endian writer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` > 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bits_le(0x0b, 4).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\xd0");
+    /// ```
+    pub fn write_bits_le(&mut self, value: u64, count: u8) -> Res<()> {
+        assert!(count <= 64);
+        if E::LSB_FIRST {
+            self.write_bits_wide(value, count)
+        } else {
+            self.write_bits_wide(reverse_low_bits(value, count), count)
+        }
+    }
+
+    /// Writes up to 64 bits, split according to the bit endianness.
+    ///
+    /// The lowest `count` bits of `value` are used. With big endian bit numbering, the most significant of these is written first, with little endian the least significant.
+    pub(crate) fn write_bits_wide(&mut self, value: u64, count: u8) -> Res<()> {
+        assert!(count <= 64);
+        let mut offset = 0;
+        while offset < count {
+            let chunk = std::cmp::min(count - offset, 8);
+            self.write_bits(E::take_bits(value, count, offset, chunk), chunk)?;
+            offset += chunk;
+        }
+        Ok(())
+    }
+
+    /// Writes the first `nbits` bits of `buf`.
+    ///
+    /// This is the counterpart of `BitReader::read_bits_slice`: whole bytes are written first, then the remaining bits from the first bit positions of the last byte. The other bits of the last byte are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is shorter than `nbits` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bits(0x0a, 4).unwrap();
+    /// writer.write_bits_slice(&[0xbc, 0xdf], 12).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\xab\xcd");
+    /// ```
+    pub fn write_bits_slice(&mut self, buf: &[u8], nbits: u64) -> Res<()> {
+        assert!(
+            nbits.div_ceil(8) <= buf.len() as u64,
+            "buffer is shorter than nbits"
+        );
+        #[allow(clippy::cast_possible_truncation)]
+        let (bytes, rest) = ((nbits / 8) as usize, (nbits % 8) as u8);
+        self.write_all(&buf[..bytes])?;
+        if rest > 0 {
+            let bits = if E::LSB_FIRST {
+                buf[bytes]
+            } else {
+                buf[bytes] >> (8 - rest)
+            };
+            self.write_bits(bits, rest)?;
+        }
+        Ok(())
+    }
+
+    /// Writes `count` zero bits.
+    ///
+    /// Whole bytes are written to the underlying writer directly, so this is much faster than calling `write_bit` in a loop for long runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bit(true).unwrap();
+    /// writer.write_zeros(15).unwrap();
+    /// let vec = writer.into_inner().unwrap();
+    /// assert_eq!(vec, b"\x80\x00");
+    /// ```
+    pub fn write_zeros(&mut self, count: u64) -> Res<()> {
+        self.write_run(0x00, count)
+    }
+
+    /// Writes `count` bits of zero padding.
+    ///
+    /// This is the same as `write_zeros`, and the counterpart of `BitReader::read_padding`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bits(0x0f, 4).unwrap();
+    /// writer.write_padding(12).unwrap();
+    /// assert_eq!(writer.into_inner().unwrap(), b"\xf0\x00");
+    /// ```
+    pub fn write_padding(&mut self, count: u64) -> Res<()> {
+        self.write_zeros(count)
+    }
+
+    /// Writes `count` one bits.
+    ///
+    /// Whole bytes are written to the underlying writer directly, so this is much faster than calling `write_bit` in a loop for long runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use endio_bit::BEBitWriter;
+    /// let mut writer = BEBitWriter::new(vec![]);
+    /// writer.write_bit(false).unwrap();
+    /// writer.write_ones(15).unwrap();
+    /// let vec = writer.into_inner().unwrap();
+    /// assert_eq!(vec, b"\x7f\xff");
+    /// ```
+    pub fn write_ones(&mut self, count: u64) -> Res<()> {
+        self.write_run(0xff, count)
+    }
+
+    fn write_run(&mut self, byte: u8, mut count: u64) -> Res<()> {
+        if !self.is_aligned() {
+            #[allow(clippy::cast_possible_truncation)]
+            let head = std::cmp::min(count, u64::from(8 - self.bit_offset)) as u8;
+            self.write_bits(byte, head)?;
+            count -= u64::from(head);
+        }
+        let chunk = [byte; 64];
+        let mut bytes = count / 8;
+        while bytes > 0 {
+            #[allow(clippy::cast_possible_truncation)]
+            let len = std::cmp::min(bytes, chunk.len() as u64) as usize;
+            self.emit(&chunk[..len])?;
+            self.position += 8 * len as u64;
+            bytes -= len as u64;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let tail = (count % 8) as u8;
+        if tail > 0 {
+            self.write_bits(byte, tail)?;
+        }
         Ok(())
     }
 }
@@ -233,44 +992,98 @@ impl<E: BitEndianness, W: Write> BitWriter<E, W> {
 ///
 /// Note that in order to fulfill the contract of [`Write`] and write to the underlying object at most once, this function uses a buffer for bitshifting. You can adjust the size of the buffer by creating the `BitWriter` using the `with_capacity` constructor.
 ///
-/// Directly maps to [`Write`] for aligned writes.
+/// Directly maps to [`Write`] for aligned writes. If the `BitWriter` was created with `with_buffer`, data is collected in the internal buffer instead, and the whole slice is accepted each call.
 ///
 /// [`Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
 impl<E: BitEndianness, W: Write> Write for BitWriter<E, W> {
     fn write(&mut self, buf: &[u8]) -> Res<usize> {
+        if self.out_capacity != 0 && self.is_aligned() {
+            self.emit(buf)?;
+            self.position += 8 * buf.len() as u64;
+            return Ok(buf.len());
+        }
         if self.is_aligned() {
-            return unsafe { self.get_mut_unchecked() }.write(buf);
+            let count_written = self.inner_mut().write(buf)?;
+            self.position += 8 * count_written as u64;
+            return Ok(count_written);
         }
+        let len = std::cmp::min(buf.len(), self.shift_buffer.len());
+        let (buf, buffer) = (&buf[..len], &mut self.shift_buffer[..len]);
         let mut last_byte = E::shift_lsb(self.bit_buffer, 8 - self.bit_offset);
-        for (byte, new) in buf.iter().zip(self.buffer.iter_mut()) {
+        // shift a word at a time for the bulk of the data
+        let mut words = buf.chunks_exact(8);
+        let mut new_words = buffer.chunks_exact_mut(8);
+        for (word, new) in (&mut words).zip(&mut new_words) {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(word);
+            new.copy_from_slice(&E::shift_lsb_word(bytes, last_byte, self.bit_offset));
+            last_byte = bytes[7];
+        }
+        for (byte, new) in words.remainder().iter().zip(new_words.into_remainder()) {
             *new =
                 E::shift_msb(last_byte, 8 - self.bit_offset) | E::shift_lsb(*byte, self.bit_offset);
             last_byte = *byte;
         }
         self.bit_buffer = E::shift_msb(last_byte, 8 - self.bit_offset);
-        let len = std::cmp::min(buf.len(), self.buffer.len());
-        self.inner.as_mut().unwrap().write(&self.buffer[0..len])
+        if self.out_capacity != 0 {
+            let buffer = std::mem::take(&mut self.shift_buffer);
+            let res = self.emit(&buffer[..len]);
+            self.shift_buffer = buffer;
+            res?;
+            self.position += 8 * len as u64;
+            return Ok(len);
+        }
+        let count_written = self
+            .inner
+            .as_mut()
+            .unwrap()
+            .write(&self.shift_buffer[0..len])?;
+        self.position += 8 * count_written as u64;
+        Ok(count_written)
     }
 
     fn flush(&mut self) -> Res<()> {
         if !self.is_aligned() {
             self.flush_buffer()?;
         }
-        unsafe { self.get_mut_unchecked() }.flush()
+        self.flush_out()?;
+        self.inner_mut().flush()
     }
 }
 
-/// Flushes the buffer for unaligned writes before the `BitWriter` is dropped.
+/// Flushes the buffer for unaligned writes and the internal buffer before the `BitWriter` is dropped, according to the drop policy.
 impl<E: BitEndianness, W: Write> Drop for BitWriter<E, W> {
     #[inline]
     fn drop(&mut self) {
-        let _ = self.align();
+        if self.inner.is_none() || !self.has_pending() {
+            return;
+        }
+        match self.drop_policy {
+            DropPolicy::Flush => {}
+            DropPolicy::Warn => {
+                #[cfg(debug_assertions)]
+                eprintln!(
+                    "warning: BitWriter dropped with pending data, call `finish` to handle write errors"
+                );
+            }
+            DropPolicy::Panic => {
+                assert!(
+                    std::thread::panicking(),
+                    "BitWriter dropped with pending data, call `finish` before dropping it"
+                );
+                return;
+            }
+        }
+        let _ = self
+            .align_with_pattern(self.padding)
+            .and_then(|()| self.flush_out());
     }
 }
 
 #[cfg(test)]
 mod tests_common {
-    use crate::BEBitWriter;
+    use crate::{BEBitWriter, DropPolicy};
+    use std::io::Write;
 
     #[test]
     fn get_ref() {
@@ -287,7 +1100,7 @@ mod tests_common {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "BitWriter is not aligned")]
     fn get_mut_unaligned() {
         let mut writer = BEBitWriter::new(vec![]);
         writer.write_bits(0x0a, 4).unwrap();
@@ -301,6 +1114,89 @@ mod tests_common {
         inner.into_boxed_slice();
     }
 
+    #[test]
+    fn with_buffer() {
+        use std::io::Write;
+
+        let mut expected = BEBitWriter::new(vec![]);
+        let mut writer = BEBitWriter::with_buffer(8, vec![]);
+        for w in [&mut expected, &mut writer] {
+            w.write_bits(5, 3).unwrap();
+            w.write_all(b"0123456789abcdefghij").unwrap();
+            w.write_ones(100).unwrap();
+            w.align().unwrap();
+            w.write_all(b"klmnopqrstuvwxyz").unwrap();
+            w.write_bits(0x12, 8).unwrap();
+        }
+        assert!(writer.get_ref().len() < expected.get_ref().len());
+        assert_eq!(writer.into_inner().unwrap(), expected.into_inner().unwrap());
+    }
+
+    #[test]
+    fn finish() {
+        let mut writer = BEBitWriter::with_buffer(16, vec![]);
+        writer.set_drop_policy(DropPolicy::Panic);
+        writer.write_bits(0xab, 8).unwrap();
+        writer.write_bit(true).unwrap();
+        assert_eq!(writer.finish().unwrap(), b"\xab\x80");
+    }
+
+    #[test]
+    fn finish_error() {
+        let mut buf = [0; 1];
+        let mut writer = BEBitWriter::new(&mut buf[..]);
+        writer.set_drop_policy(DropPolicy::Panic);
+        writer.write_bits(0xab, 8).unwrap();
+        writer.write_bit(true).unwrap();
+        let err = writer.finish().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+        assert_eq!(buf, [0xab]);
+    }
+
+    #[test]
+    #[should_panic(expected = "BitWriter dropped with pending data")]
+    fn drop_policy_panic() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.set_drop_policy(DropPolicy::Panic);
+        writer.write_bit(true).unwrap();
+    }
+
+    #[test]
+    fn drop_policy_aligned() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.set_drop_policy(DropPolicy::Panic);
+        writer.write_bits(0xff, 8).unwrap();
+    }
+
+    #[test]
+    fn padding() {
+        let mut vec = vec![];
+        {
+            let mut writer = BEBitWriter::with_buffer(64, &mut vec);
+            writer.set_padding(0x55);
+            writer.write_bits(0x03, 2).unwrap();
+            writer.align().unwrap();
+            writer.write_bits(0x00, 2).unwrap();
+        }
+        assert_eq!(vec, b"\xc0\x15");
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.set_padding(0xff);
+        writer.write_bit(false).unwrap();
+        assert_eq!(writer.finish().unwrap(), b"\x7f");
+    }
+
+    #[test]
+    fn with_buffer_flushes_on_drop() {
+        let mut vec = vec![];
+        {
+            let mut writer = BEBitWriter::with_buffer(64, &mut vec);
+            writer.write_bits(0xab, 8).unwrap();
+            writer.write_bit(true).unwrap();
+            assert_eq!(writer.get_ref().len(), 0);
+        }
+        assert_eq!(vec, b"\xab\x80");
+    }
+
     #[test]
     fn align() {
         let mut vec = vec![];
@@ -314,6 +1210,62 @@ mod tests_common {
         }
         assert_eq!(vec, b"\xf8\x80");
     }
+
+    #[test]
+    fn bit_position() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bit(true).unwrap();
+        writer.write_bits(0x0f, 4).unwrap();
+        writer.write_all(b"\x12\x34").unwrap();
+        writer.write_zeros(100).unwrap();
+        assert_eq!(writer.bit_position(), 121);
+        writer.align().unwrap();
+        writer.write_all(b"\x56").unwrap();
+        assert_eq!(writer.bit_position(), 136);
+        let mut writer = BEBitWriter::with_buffer(4, vec![]);
+        writer.write_all(b"\x12\x34").unwrap();
+        writer.write_bits_u128(0, 70).unwrap();
+        assert_eq!(writer.bit_position(), 86);
+    }
+
+    #[test]
+    fn align_to() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.align_to(32).unwrap();
+        writer.write_bits(0x07, 3).unwrap();
+        writer.align_to(5).unwrap();
+        writer.write_bits(0x07, 3).unwrap();
+        writer.align_to(32).unwrap();
+        assert_eq!(writer.bit_position(), 32);
+        assert_eq!(writer.into_inner().unwrap(), b"\xe7\x00\x00\x00");
+    }
+
+    #[test]
+    fn buffered_bits() {
+        let mut writer = BEBitWriter::new(vec![]);
+        assert_eq!(writer.buffered_bits(), (0, 0));
+        writer.write_bits(0x2b, 6).unwrap();
+        assert_eq!(writer.buffered_bits(), (6, 0x2b));
+        assert_eq!(writer.bits_until_aligned(), 2);
+        writer.write_bits(0x01, 3).unwrap();
+        assert_eq!(writer.bit_offset(), 1);
+        assert_eq!(writer.buffered_bits(), (1, 0x01));
+    }
+
+    #[test]
+    fn write_zeros() {
+        let mut vec = vec![];
+        {
+            let mut writer = BEBitWriter::new(&mut vec);
+            writer.write_bits(0x07, 3).unwrap();
+            writer.write_zeros(1000).unwrap();
+            writer.write_bit(true).unwrap();
+        }
+        assert_eq!(vec.len(), 126);
+        assert_eq!(vec[0], 0xe0);
+        assert!(vec[1..125].iter().all(|&b| b == 0));
+        assert_eq!(vec[125], 0x10);
+    }
 }
 
 #[cfg(test)]
@@ -321,6 +1273,51 @@ mod tests_be {
     use crate::BEBitWriter;
     use std::io::Write;
 
+    #[test]
+    fn align_with_pattern() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bits(0x00, 2).unwrap();
+        writer.align_with_pattern(0xaa).unwrap();
+        writer.write_bits(0x0f, 4).unwrap();
+        writer.align_with(false).unwrap();
+        assert_eq!(writer.into_inner().unwrap(), b"\x2a\xf0");
+    }
+
+    #[test]
+    fn write_bools() {
+        let flags = [
+            true, true, false, false, true, false, true, false, true, true, false,
+        ];
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bit(false).unwrap();
+        writer.write_bools(&flags).unwrap();
+        writer.write_bools(&[]).unwrap();
+        let data = writer.into_inner().unwrap();
+        assert_eq!(data, b"\x65\x60");
+        let mut reader = crate::BEBitReader::new(&data[..]);
+        reader.read_bit().unwrap();
+        assert_eq!(reader.read_bool_array().unwrap(), flags);
+    }
+
+    #[test]
+    fn write_bits_wide() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bits_u16(0x1, 4).unwrap();
+        writer.write_bits_u32(0x23_4567, 24).unwrap();
+        writer.write_bits_u64(0x89a_bcde_f012, 44).unwrap();
+        assert_eq!(
+            writer.into_inner().unwrap(),
+            b"\x12\x34\x56\x78\x9a\xbc\xde\xf0\x12"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "count <= 16")]
+    fn write_too_many_bits_u16() {
+        let mut writer = BEBitWriter::new(vec![]);
+        let _ = writer.write_bits_u16(0, 17);
+    }
+
     #[test]
     fn write_aligned() {
         let mut vec = vec![];
@@ -344,6 +1341,22 @@ mod tests_be {
         assert_eq!(vec, b"\xaa\x8c\xae\x6e\x80");
     }
 
+    #[test]
+    fn write_shifted_long() {
+        let payload: Vec<u8> = (0..=255).collect();
+        for offset in 1..8 {
+            let mut expected = BEBitWriter::new(vec![]);
+            let mut writer = BEBitWriter::with_capacity(64, vec![]);
+            expected.write_bits(0x55, offset).unwrap();
+            writer.write_bits(0x55, offset).unwrap();
+            for &byte in &payload {
+                expected.write_bits(byte, 8).unwrap();
+            }
+            writer.write_all(&payload).unwrap();
+            assert_eq!(writer.into_inner().unwrap(), expected.into_inner().unwrap());
+        }
+    }
+
     #[test]
     fn flush() {
         let mut writer = BEBitWriter::new(vec![]);
@@ -382,7 +1395,26 @@ mod tests_be {
     }
 
     #[test]
-    #[should_panic]
+    fn write_ones() {
+        let mut vec = vec![];
+        {
+            let mut writer = BEBitWriter::new(&mut vec);
+            writer.write_bit(false).unwrap();
+            writer.write_ones(20).unwrap();
+        }
+        assert_eq!(vec, b"\x7f\xff\xf8");
+    }
+
+    #[test]
+    fn write_zero_bits() {
+        let mut writer = BEBitWriter::new(vec![]);
+        writer.write_bits(0xff, 0).unwrap();
+        assert!(writer.is_aligned());
+        assert_eq!(writer.into_inner().unwrap(), b"");
+    }
+
+    #[test]
+    #[should_panic(expected = "count <= 8")]
     fn write_too_many_bits() {
         let mut vec = vec![];
         let mut writer = BEBitWriter::new(&mut vec);
@@ -395,6 +1427,56 @@ mod tests_le {
     use crate::LEBitWriter;
     use std::io::Write;
 
+    #[test]
+    fn align_with() {
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.align_with(true).unwrap();
+        writer.write_bits(0x00, 5).unwrap();
+        writer.align_with(true).unwrap();
+        writer.write_bit(true).unwrap();
+        writer.align_with_pattern(0xaa).unwrap();
+        assert_eq!(writer.bit_position(), 16);
+        assert_eq!(writer.into_inner().unwrap(), b"\xe0\xab");
+    }
+
+    #[test]
+    fn write_bits_slice() {
+        let data = b"\xab\xcd\xef\x12";
+        let mut reader = crate::LEBitReader::new(&data[..]);
+        reader.read_bits(3).unwrap();
+        let mut buf = [0; 3];
+        reader.read_bits_slice(&mut buf, 21).unwrap();
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bits(0x03, 3).unwrap();
+        writer.write_bits_slice(&buf, 21).unwrap();
+        writer.write_bits_slice(&[], 0).unwrap();
+        assert_eq!(writer.into_inner().unwrap(), data[..3]);
+    }
+
+    #[test]
+    fn le_bit_writer() {
+        use crate::BitWriterExt;
+
+        let mut vec = vec![];
+        let mut writer = (&mut vec).le_bit_writer();
+        writer.write_bits_u128(0x201, 10).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(vec, b"\x01\x02");
+    }
+
+    #[test]
+    fn write_bits_be() {
+        let mut writer = LEBitWriter::new(vec![]);
+        writer.write_bits(0x05, 3).unwrap();
+        writer.write_bits_be(0x2c3, 10).unwrap();
+        writer.write_bits_le(0x2c3, 10).unwrap();
+        let data = writer.into_inner().unwrap();
+        let mut reader = crate::LEBitReader::new(&data[..]);
+        assert_eq!(reader.read_bits(3).unwrap(), 0x05);
+        assert_eq!(reader.read_bits_u16(10).unwrap(), 0x30d);
+        assert_eq!(reader.read_bits_u16(10).unwrap(), 0x2c3);
+    }
+
     #[test]
     fn write_aligned() {
         let mut vec = vec![];
@@ -418,6 +1500,22 @@ mod tests_le {
         assert_eq!(vec, b"\xa5\x2a\x9b\xa3\x03");
     }
 
+    #[test]
+    fn write_shifted_long() {
+        let payload: Vec<u8> = (0..=255).collect();
+        for offset in 1..8 {
+            let mut expected = LEBitWriter::new(vec![]);
+            let mut writer = LEBitWriter::with_capacity(64, vec![]);
+            expected.write_bits(0x55, offset).unwrap();
+            writer.write_bits(0x55, offset).unwrap();
+            for &byte in &payload {
+                expected.write_bits(byte, 8).unwrap();
+            }
+            writer.write_all(&payload).unwrap();
+            assert_eq!(writer.into_inner().unwrap(), expected.into_inner().unwrap());
+        }
+    }
+
     #[test]
     fn flush() {
         let mut writer = LEBitWriter::new(vec![]);
@@ -456,7 +1554,18 @@ mod tests_le {
     }
 
     #[test]
-    #[should_panic]
+    fn write_ones() {
+        let mut vec = vec![];
+        {
+            let mut writer = LEBitWriter::new(&mut vec);
+            writer.write_bit(false).unwrap();
+            writer.write_ones(20).unwrap();
+        }
+        assert_eq!(vec, b"\xfe\xff\x1f");
+    }
+
+    #[test]
+    #[should_panic(expected = "count <= 8")]
     fn write_too_many_bits() {
         let mut vec = vec![];
         let mut writer = LEBitWriter::new(&mut vec);